@@ -1,43 +1,68 @@
+use crate::audit::{audit_generate, get_audit_records};
+use crate::auth::{client_owner, ClientInfo};
+use crate::business::cota_transaction::{
+    get_cota_cell_changes, get_cota_transaction, verify_cota_transaction,
+};
 use crate::business::helper::script_from_address;
 use crate::business::transaction::{get_cota_txs_by_block_number, get_history_transactions};
-use crate::entries::claim::generate_claim_smt;
+use crate::business::tx_builder::{
+    build_cota_tx, cota_out_point, get_fee_cell, get_live_cota_cell, next_cota_cell, next_fee_cell,
+    CotaTxParams, FeeCell, CLAIM, CLAIM_UPDATE, CREATE, MINT, TRANSFER, TRANSFER_UPDATE, UPDATE,
+    WITHDRAW,
+};
+use crate::ckb::indexer::CotaCell;
+use crate::entries::burn::generate_burn_smt;
+use crate::entries::claim::{generate_claim_all_smt, generate_claim_smt};
 use crate::entries::claim_update::generate_claim_update_smt;
 use crate::entries::define::generate_define_smt;
 use crate::entries::extension::{
     generate_adding_subkey_smt, generate_ext_social_smt, generate_ext_subkey_smt,
 };
-use crate::entries::mint::generate_mint_smt;
+use crate::entries::metadata::{generate_class_metadata, generate_issuer_metadata};
+use crate::entries::mint::{generate_mint_batches_smt, generate_mint_smt, mint_batch_lens};
+use crate::entries::registry::generate_registry_smt;
 use crate::entries::sequential_transfer::generate_sequential_transfer_smt;
 use crate::entries::social::generate_social_unlock_smt;
 use crate::entries::subkey::generate_subkey_unlock_smt;
 use crate::entries::transfer::generate_transfer_smt;
 use crate::entries::transfer_update::generate_transfer_update_smt;
-use crate::entries::update::generate_update_smt;
+use crate::entries::update::{generate_update_by_filter_smt, generate_update_smt};
+use crate::entries::verify::verify_cota_witness;
 use crate::entries::withdrawal::generate_withdrawal_smt;
 use crate::models::block::{get_syncer_tip_block_number, get_syncer_tip_block_numbers};
 use crate::models::common::{
     check_cota_claimed, get_define_info_by_cota_id, get_hold_cota, get_issuer_by_cota_id,
     get_mint_cota, get_owned_cota_count, get_sender_account_by_cota_nft, get_withdrawal_cota,
+    is_cota_nft_burned,
 };
 use crate::models::issuer::get_issuer_info_by_lock_hash;
 use crate::models::joyid::get_joyid_info_by_lock_hash;
+use crate::models::registry::check_registered;
 use crate::models::withdrawal::nft::{
     get_cota_info_by_cota_id_token_index, get_receiver_lock_by_cota_id_and_token_index,
 };
-use crate::request::claim::{ClaimReq, ClaimUpdateReq, IsClaimedReq};
+use crate::request::audit::AuditLogReq;
+use crate::request::burn::BurnReq;
+use crate::request::claim::{ClaimAllReq, ClaimReq, ClaimUpdateReq, IsClaimedReq};
 use crate::request::define::{DefineInfoReq, DefineReq};
 use crate::request::extension::{ExtSocialReq, ExtSubkeysReq};
 use crate::request::fetch::{
     FetchCotaNftInfoReq, FetchCountReq, FetchHistoryTxsReq, FetchIssuerInfoReq, FetchIssuerReq,
     FetchJoyIDReq, FetchReq, FetchTxsByBlockNumberReq,
 };
+use crate::request::metadata::{ClassMetadataReq, IssuerMetadataReq};
 use crate::request::mint::MintReq;
+use crate::request::registry::{IsRegisteredReq, RegistryReq};
 use crate::request::social::SocialUnlockReq;
 use crate::request::subkey::SubKeyUnlockReq;
+use crate::request::transaction::{BuildTxReq, CotaTransactionReq};
 use crate::request::transfer::{SequentialTransferReq, TransferReq, TransferUpdateReq};
-use crate::request::update::UpdateReq;
+use crate::request::update::{UpdateByFilterReq, UpdateReq};
+use crate::request::webhook::{WebhookIdReq, WebhookReq};
 use crate::request::withdrawal::{OwnerLockReq, SenderLockReq, WithdrawalReq};
-use crate::request::witness::WitnessReq;
+use crate::request::witness::{VerifyWitnessReq, WitnessReq};
+use crate::response::audit::parse_audit_logs_response;
+use crate::response::burn::parse_burn_smt;
 use crate::response::claim::{parse_claimed_response, parse_claimed_smt, parse_claimed_update_smt};
 use crate::response::define::{parse_define_info, parse_define_smt};
 use crate::response::extension::parse_extension_smt;
@@ -45,21 +70,29 @@ use crate::response::hold::{parse_hold_response, parse_owned_nft_count};
 use crate::response::info::{generate_aggregator_info, parse_cota_nft_info_response};
 use crate::response::issuer::{parse_issuer_info_response, parse_issuer_response};
 use crate::response::joyid_metadata::parse_joyid_metadata_response;
+use crate::response::metadata::{parse_class_metadata, parse_issuer_metadata};
 use crate::response::mint::{parse_mint_response, parse_mint_smt};
+use crate::response::registry::{parse_registered_response, parse_registry_smt};
 use crate::response::social::parse_social_unlock;
 use crate::response::subkey::parse_subkey_unlock;
-use crate::response::transaction::{parse_cota_transactions, parse_history_transactions};
+use crate::response::transaction::{
+    parse_cota_transaction, parse_cota_transactions, parse_history_transactions,
+    parse_unsigned_cota_tx, parse_unsigned_cota_txs,
+};
 use crate::response::transfer::{
     parse_sequential_transfer_smt, parse_transfer_smt, parse_transfer_update_smt,
 };
-use crate::response::update::parse_update_smt;
+use crate::response::update::{parse_update_batches, parse_update_smt};
+use crate::response::verify::{parse_transaction_verification, parse_witness_verification};
+use crate::response::webhook::{parse_webhook_response, parse_webhooks_response};
 use crate::response::withdrawal::{
     parse_owner_response, parse_sender_response, parse_withdrawal_response, parse_withdrawal_smt,
 };
 use crate::response::witness::cota::parse_cota_witness;
 use crate::utils::error;
+use crate::webhook::{get_owner_webhooks, register_webhook, remove_webhook};
 use ckb_types::prelude::Entity;
-use cota_smt::smt::blake2b_256;
+use cota_smt::smt::{blake2b_256, H256};
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::{Error, Params, Value};
 use log::info;
@@ -67,115 +100,223 @@ use log::info;
 pub async fn define_rpc(params: Params) -> Result<Value, Error> {
     info!("Define request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = DefineReq::from_map(&map).map_err(rpc_err)?;
-    let define_smt = generate_define_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_define_smt(define_smt, tip_number()?))
+    audit_generate("generate_define_cota_smt", &map, async {
+        let req = DefineReq::from_map(&map).map_err(rpc_err)?;
+        let define_smt = generate_define_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_define_smt(define_smt, tip_number()?))
+    })
+    .await
+}
+
+pub async fn issuer_metadata_rpc(params: Params) -> Result<Value, Error> {
+    info!("Issuer metadata request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    let IssuerMetadataReq { issuer } = IssuerMetadataReq::from_map(&map).map_err(rpc_err)?;
+    let output_type = generate_issuer_metadata(issuer).map_err(rpc_err)?;
+    Ok(parse_issuer_metadata(output_type))
+}
+
+pub async fn class_metadata_rpc(params: Params) -> Result<Value, Error> {
+    info!("Class metadata request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("generate_class_metadata", &map, async {
+        let ClassMetadataReq { define, class } =
+            ClassMetadataReq::from_map(&map).map_err(rpc_err)?;
+        let class_metadata = generate_class_metadata(define, class)
+            .await
+            .map_err(rpc_err)?;
+        Ok(parse_class_metadata(class_metadata, tip_number()?))
+    })
+    .await
 }
 
 pub async fn mint_rpc(params: Params) -> Result<Value, Error> {
     info!("Mint request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = MintReq::from_map(&map).map_err(rpc_err)?;
-    let mint_smt = generate_mint_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_mint_smt(mint_smt, tip_number()?))
+    audit_generate("generate_mint_cota_smt", &map, async {
+        let req = MintReq::from_map(&map).map_err(rpc_err)?;
+        let mint_smt = generate_mint_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_mint_smt(mint_smt, tip_number()?))
+    })
+    .await
+}
+
+pub async fn registry_rpc(params: Params) -> Result<Value, Error> {
+    info!("Registry request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("generate_register_cota_smt", &map, async {
+        let req = RegistryReq::from_map(&map).map_err(rpc_err)?;
+        let registry_smt = generate_registry_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_registry_smt(registry_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn withdrawal_rpc(params: Params) -> Result<Value, Error> {
     info!("Withdrawal request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = WithdrawalReq::from_map(&map).map_err(rpc_err)?;
-    let withdrawal_smt = generate_withdrawal_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_withdrawal_smt(withdrawal_smt, tip_number()?))
+    audit_generate("generate_withdrawal_cota_smt", &map, async {
+        let req = WithdrawalReq::from_map(&map).map_err(rpc_err)?;
+        let withdrawal_smt = generate_withdrawal_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_withdrawal_smt(withdrawal_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn claim_rpc(params: Params) -> Result<Value, Error> {
     info!("Claim request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = ClaimReq::from_map(&map).map_err(rpc_err)?;
-    let claim_smt = generate_claim_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_claimed_smt(claim_smt, tip_number()?))
+    audit_generate("generate_claim_cota_smt", &map, async {
+        let req = ClaimReq::from_map(&map).map_err(rpc_err)?;
+        let claim_smt = generate_claim_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_claimed_smt(claim_smt, tip_number()?))
+    })
+    .await
+}
+
+pub async fn claim_all_rpc(params: Params) -> Result<Value, Error> {
+    info!("Claim all request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("generate_claim_all_cota_smt", &map, async {
+        let req = ClaimAllReq::from_map(&map).map_err(rpc_err)?;
+        let claim_smt = generate_claim_all_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_claimed_smt(claim_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn update_rpc(params: Params) -> Result<Value, Error> {
     info!("Update request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = UpdateReq::from_map(&map).map_err(rpc_err)?;
-    let update_smt = generate_update_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_update_smt(update_smt, tip_number()?))
+    audit_generate("generate_update_cota_smt", &map, async {
+        let req = UpdateReq::from_map(&map).map_err(rpc_err)?;
+        let update_smt = generate_update_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_update_smt(update_smt, tip_number()?))
+    })
+    .await
+}
+
+pub async fn update_by_filter_rpc(params: Params) -> Result<Value, Error> {
+    info!("Update by filter request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("generate_update_by_filter_cota_smt", &map, async {
+        let req = UpdateByFilterReq::from_map(&map).map_err(rpc_err)?;
+        let update_smts = generate_update_by_filter_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_update_batches(update_smts, tip_number()?))
+    })
+    .await
+}
+
+pub async fn burn_rpc(params: Params) -> Result<Value, Error> {
+    info!("Burn request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("generate_burn_cota_smt", &map, async {
+        let req = BurnReq::from_map(&map).map_err(rpc_err)?;
+        let burn_smt = generate_burn_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_burn_smt(burn_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn transfer_rpc(params: Params) -> Result<Value, Error> {
     info!("Transfer request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let transfer_req = TransferReq::from_map(&map).map_err(rpc_err)?;
-    let transfer_smt = generate_transfer_smt(transfer_req).await.map_err(rpc_err)?;
-    Ok(parse_transfer_smt(transfer_smt, tip_number()?))
+    audit_generate("generate_transfer_cota_smt", &map, async {
+        let transfer_req = TransferReq::from_map(&map).map_err(rpc_err)?;
+        let transfer_smt = generate_transfer_smt(transfer_req).await.map_err(rpc_err)?;
+        Ok(parse_transfer_smt(transfer_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn claim_update_rpc(params: Params) -> Result<Value, Error> {
     info!("Claim & Update request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = ClaimUpdateReq::from_map(&map).map_err(rpc_err)?;
-    let claim_smt = generate_claim_update_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_claimed_update_smt(claim_smt, tip_number()?))
+    audit_generate("generate_claim_update_cota_smt", &map, async {
+        let req = ClaimUpdateReq::from_map(&map).map_err(rpc_err)?;
+        let claim_smt = generate_claim_update_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_claimed_update_smt(claim_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn transfer_update_rpc(params: Params) -> Result<Value, Error> {
     info!("Transfer & Update request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = TransferUpdateReq::from_map(&map).map_err(rpc_err)?;
-    let transfer_smt = generate_transfer_update_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_transfer_update_smt(transfer_smt, tip_number()?))
+    audit_generate("generate_transfer_update_cota_smt", &map, async {
+        let req = TransferUpdateReq::from_map(&map).map_err(rpc_err)?;
+        let transfer_smt = generate_transfer_update_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_transfer_update_smt(transfer_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn sequential_transfer_rpc(params: Params) -> Result<Value, Error> {
     info!("Sequential Transfer request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = SequentialTransferReq::from_map(&map).map_err(rpc_err)?;
-    let transfer_smt = generate_sequential_transfer_smt(req)
-        .await
-        .map_err(rpc_err)?;
-    Ok(parse_sequential_transfer_smt(transfer_smt, tip_number()?))
+    audit_generate("generate_sequential_transfer_cota_smt", &map, async {
+        let req = SequentialTransferReq::from_map(&map).map_err(rpc_err)?;
+        let transfer_smt = generate_sequential_transfer_smt(req)
+            .await
+            .map_err(rpc_err)?;
+        Ok(parse_sequential_transfer_smt(transfer_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn subkey_unlock_rpc(params: Params) -> Result<Value, Error> {
     info!("Subkey unlock request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = SubKeyUnlockReq::from_map(&map).map_err(rpc_err)?;
-    let subkey_smt = generate_subkey_unlock_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_subkey_unlock(subkey_smt, tip_number()?))
+    audit_generate("generate_subkey_unlock_smt", &map, async {
+        let req = SubKeyUnlockReq::from_map(&map).map_err(rpc_err)?;
+        let subkey_smt = generate_subkey_unlock_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_subkey_unlock(subkey_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn extension_subkey_rpc(params: Params) -> Result<Value, Error> {
     info!("Extension subkey request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = ExtSubkeysReq::from_map(&map).map_err(rpc_err)?;
-    let ext_subkey = generate_ext_subkey_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_extension_smt(ext_subkey, tip_number()?))
+    audit_generate("generate_extension_subkey_smt", &map, async {
+        let req = ExtSubkeysReq::from_map(&map).map_err(rpc_err)?;
+        let ext_subkey = generate_ext_subkey_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_extension_smt(ext_subkey, tip_number()?))
+    })
+    .await
 }
 
 pub async fn add_subkey_rpc(params: Params) -> Result<Value, Error> {
     info!("Add subkey request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = ExtSubkeysReq::from_map(&map).map_err(rpc_err)?;
-    let ext_subkey = generate_adding_subkey_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_extension_smt(ext_subkey, tip_number()?))
+    audit_generate("generate_adding_subkey_smt", &map, async {
+        let req = ExtSubkeysReq::from_map(&map).map_err(rpc_err)?;
+        let ext_subkey = generate_adding_subkey_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_extension_smt(ext_subkey, tip_number()?))
+    })
+    .await
 }
 
 pub async fn extension_social_rpc(params: Params) -> Result<Value, Error> {
     info!("Extension social request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = ExtSocialReq::from_map(&map).map_err(rpc_err)?;
-    let ext_social = generate_ext_social_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_extension_smt(ext_social, tip_number()?))
+    audit_generate("generate_extension_social_smt", &map, async {
+        let req = ExtSocialReq::from_map(&map).map_err(rpc_err)?;
+        let ext_social = generate_ext_social_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_extension_smt(ext_social, tip_number()?))
+    })
+    .await
 }
 
 pub async fn social_unlock_rpc(params: Params) -> Result<Value, Error> {
     info!("Social unlock request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
-    let req = SocialUnlockReq::from_map(&map).map_err(rpc_err)?;
-    let social_smt = generate_social_unlock_smt(req).await.map_err(rpc_err)?;
-    Ok(parse_social_unlock(social_smt, tip_number()?))
+    audit_generate("generate_social_unlock_smt", &map, async {
+        let req = SocialUnlockReq::from_map(&map).map_err(rpc_err)?;
+        let social_smt = generate_social_unlock_smt(req).await.map_err(rpc_err)?;
+        Ok(parse_social_unlock(social_smt, tip_number()?))
+    })
+    .await
 }
 
 pub async fn fetch_hold_rpc(params: Params) -> Result<Value, Error> {
@@ -233,6 +374,14 @@ pub async fn is_claimed_rpc(params: Params) -> Result<Value, Error> {
     Ok(parse_claimed_response(claimed, block_number))
 }
 
+pub async fn is_registered_rpc(params: Params) -> Result<Value, Error> {
+    info!("Is registered request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    let IsRegisteredReq { lock_hash } = IsRegisteredReq::from_map(&map).map_err(rpc_err)?;
+    let (registered, block_number) = check_registered(lock_hash).map_err(rpc_err)?;
+    Ok(parse_registered_response(registered, block_number))
+}
+
 pub async fn get_sender_account(params: Params) -> Result<Value, Error> {
     info!("Get sender account request: {:?}", params);
     let map: Map<String, Value> = Params::parse(params)?;
@@ -296,7 +445,8 @@ pub async fn get_cota_nft_info(params: Params) -> Result<Value, Error> {
         token_index,
     } = FetchCotaNftInfoReq::from_map(&map).map_err(rpc_err)?;
     let nft_info = get_cota_info_by_cota_id_token_index(cota_id, token_index).map_err(rpc_err)?;
-    parse_cota_nft_info_response(nft_info, tip_number()?).map_err(rpc_err)
+    let burned = is_cota_nft_burned(cota_id, token_index).map_err(rpc_err)?;
+    parse_cota_nft_info_response(nft_info, burned, tip_number()?).map_err(rpc_err)
 }
 
 pub async fn parse_witness(params: Params) -> Result<Value, Error> {
@@ -366,6 +516,352 @@ pub async fn get_aggregator_info(_params: Params) -> Result<Value, Error> {
         .map_err(rpc_err)
 }
 
+pub async fn register_webhook_rpc<T: ClientInfo>(params: Params, meta: T) -> Result<Value, Error> {
+    info!("Register webhook request: {:?}", params);
+    let owner = client_owner(meta.client()).map_err(rpc_err)?;
+    let map: Map<String, Value> = Params::parse(params)?;
+    let req = WebhookReq::from_map(&map).map_err(rpc_err)?;
+    let webhook = register_webhook(owner, req).map_err(rpc_err)?;
+    Ok(parse_webhook_response(webhook))
+}
+
+pub async fn remove_webhook_rpc<T: ClientInfo>(params: Params, meta: T) -> Result<Value, Error> {
+    info!("Remove webhook request: {:?}", params);
+    let owner = client_owner(meta.client()).map_err(rpc_err)?;
+    let map: Map<String, Value> = Params::parse(params)?;
+    let WebhookIdReq { webhook_id } = WebhookIdReq::from_map(&map).map_err(rpc_err)?;
+    remove_webhook(&owner, webhook_id).map_err(rpc_err)?;
+    Ok(Value::Bool(true))
+}
+
+pub async fn get_webhooks_rpc<T: ClientInfo>(_params: Params, meta: T) -> Result<Value, Error> {
+    info!("Get webhooks request");
+    let owner = client_owner(meta.client()).map_err(rpc_err)?;
+    Ok(parse_webhooks_response(get_owner_webhooks(&owner)))
+}
+
+pub async fn get_audit_logs_rpc(params: Params) -> Result<Value, Error> {
+    info!("Get audit logs request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    let AuditLogReq {
+        lock_hash,
+        smt_root,
+        limit,
+    } = AuditLogReq::from_map(&map).map_err(rpc_err)?;
+    let records = get_audit_records(lock_hash, smt_root, limit).map_err(rpc_err)?;
+    parse_audit_logs_response(records).map_err(rpc_err)
+}
+
+pub async fn parse_cota_transaction_rpc(params: Params) -> Result<Value, Error> {
+    info!("Parse cota transaction request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    let req = CotaTransactionReq::from_map(&map).map_err(rpc_err)?;
+    let (tx_hash, transaction) = get_cota_transaction(req).await.map_err(rpc_err)?;
+    let changes = get_cota_cell_changes(&transaction).await.map_err(rpc_err)?;
+    Ok(parse_cota_transaction(tx_hash, changes))
+}
+
+pub async fn verify_cota_witness_rpc(params: Params) -> Result<Value, Error> {
+    info!("Verify cota witness request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    let VerifyWitnessReq {
+        witness,
+        version,
+        old_root,
+        new_root,
+        transaction,
+    } = VerifyWitnessReq::from_map(&map).map_err(rpc_err)?;
+    match transaction {
+        Some(req) => {
+            let (tx_hash, verifications) = verify_cota_transaction(req).await.map_err(rpc_err)?;
+            Ok(parse_transaction_verification(tx_hash, verifications))
+        }
+        None => {
+            let witness = witness.unwrap_or_default();
+            let verification =
+                verify_cota_witness(&witness, version, old_root, new_root).map_err(rpc_err)?;
+            Ok(parse_witness_verification(verification))
+        }
+    }
+}
+
+pub async fn build_define_tx_rpc(params: Params) -> Result<Value, Error> {
+    info!("Build define tx request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("build_define_cota_tx", &map, async {
+        let req = DefineReq::from_map(&map).map_err(rpc_err)?;
+        let BuildTxReq {
+            fee_rate,
+            fee_out_point,
+        } = BuildTxReq::from_map(&map).map_err(rpc_err)?;
+        let cota_cell = get_live_cota_cell(&req.lock_script)
+            .await
+            .map_err(rpc_err)?;
+        let fee_cell = get_fee_cell(fee_out_point, &cota_cell)
+            .await
+            .map_err(rpc_err)?;
+        let (smt_root, entries) = generate_define_smt(req).await.map_err(rpc_err)?;
+        unsigned_cota_tx(CotaTxParams {
+            cota_cell,
+            fee_cell,
+            action: CREATE,
+            entries: entries.as_slice().to_vec(),
+            smt_root,
+            header_deps: vec![],
+            fee_rate,
+        })
+    })
+    .await
+}
+
+pub async fn build_mint_tx_rpc(params: Params) -> Result<Value, Error> {
+    info!("Build mint tx request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("build_mint_cota_tx", &map, async {
+        let req = MintReq::from_map(&map).map_err(rpc_err)?;
+        let BuildTxReq {
+            fee_rate,
+            fee_out_point,
+        } = BuildTxReq::from_map(&map).map_err(rpc_err)?;
+        let mut cota_cell = get_live_cota_cell(&req.lock_script)
+            .await
+            .map_err(rpc_err)?;
+        let mut fee_cell = get_fee_cell(fee_out_point, &cota_cell)
+            .await
+            .map_err(rpc_err)?;
+        // The mint too large for one transaction is built into the chained transactions, and the
+        // withdrawal keys of every batch contain the CoTA cell created by the previous one
+        let batch_lens = mint_batch_lens(&req.withdrawals).map_err(rpc_err)?;
+        let mut txs = Vec::with_capacity(batch_lens.len());
+        generate_mint_batches_smt(req, &batch_lens, |smt_root, entries| {
+            let tx = build_cota_tx(CotaTxParams {
+                cota_cell: cota_cell.clone(),
+                fee_cell: fee_cell.clone(),
+                action: MINT,
+                entries: entries.as_slice().to_vec(),
+                smt_root,
+                header_deps: vec![],
+                fee_rate,
+            })?;
+            cota_cell = next_cota_cell(&tx.0);
+            fee_cell = next_fee_cell(&tx.0);
+            txs.push((tx, smt_root));
+            Ok(cota_out_point(&cota_cell))
+        })
+        .await
+        .map_err(rpc_err)?;
+        parse_unsigned_cota_txs(txs, tip_number()?).map_err(rpc_err)
+    })
+    .await
+}
+
+pub async fn build_withdrawal_tx_rpc(params: Params) -> Result<Value, Error> {
+    info!("Build withdrawal tx request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("build_withdrawal_cota_tx", &map, async {
+        let req = WithdrawalReq::from_map(&map).map_err(rpc_err)?;
+        let BuildTxReq {
+            fee_rate,
+            fee_out_point,
+        } = BuildTxReq::from_map(&map).map_err(rpc_err)?;
+        let cota_cell = get_live_cota_cell(&req.lock_script)
+            .await
+            .map_err(rpc_err)?;
+        let fee_cell = get_fee_cell(fee_out_point, &cota_cell)
+            .await
+            .map_err(rpc_err)?;
+        let (smt_root, entries) = generate_withdrawal_smt(req).await.map_err(rpc_err)?;
+        unsigned_cota_tx(CotaTxParams {
+            cota_cell,
+            fee_cell,
+            action: WITHDRAW,
+            entries: entries.as_slice().to_vec(),
+            smt_root,
+            header_deps: vec![],
+            fee_rate,
+        })
+    })
+    .await
+}
+
+pub async fn build_claim_tx_rpc(params: Params) -> Result<Value, Error> {
+    info!("Build claim tx request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("build_claim_cota_tx", &map, async {
+        let req = ClaimReq::from_map(&map).map_err(rpc_err)?;
+        let BuildTxReq {
+            fee_rate,
+            fee_out_point,
+        } = BuildTxReq::from_map(&map).map_err(rpc_err)?;
+        let cota_cell = get_live_cota_cell(&req.lock_script)
+            .await
+            .map_err(rpc_err)?;
+        let fee_cell = get_fee_cell(fee_out_point, &cota_cell)
+            .await
+            .map_err(rpc_err)?;
+        let batches = generate_claim_smt(req)
+            .await
+            .map_err(rpc_err)?
+            .into_iter()
+            .map(|(smt_root, entries, block_hash)| {
+                (smt_root, entries.as_slice().to_vec(), block_hash)
+            })
+            .collect();
+        unsigned_claim_txs(cota_cell, fee_cell, CLAIM, batches, fee_rate)
+    })
+    .await
+}
+
+pub async fn build_update_tx_rpc(params: Params) -> Result<Value, Error> {
+    info!("Build update tx request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("build_update_cota_tx", &map, async {
+        let req = UpdateReq::from_map(&map).map_err(rpc_err)?;
+        let BuildTxReq {
+            fee_rate,
+            fee_out_point,
+        } = BuildTxReq::from_map(&map).map_err(rpc_err)?;
+        let cota_cell = get_live_cota_cell(&req.lock_script)
+            .await
+            .map_err(rpc_err)?;
+        let fee_cell = get_fee_cell(fee_out_point, &cota_cell)
+            .await
+            .map_err(rpc_err)?;
+        let (smt_root, entries) = generate_update_smt(req).await.map_err(rpc_err)?;
+        unsigned_cota_tx(CotaTxParams {
+            cota_cell,
+            fee_cell,
+            action: UPDATE,
+            entries: entries.as_slice().to_vec(),
+            smt_root,
+            header_deps: vec![],
+            fee_rate,
+        })
+    })
+    .await
+}
+
+pub async fn build_transfer_tx_rpc(params: Params) -> Result<Value, Error> {
+    info!("Build transfer tx request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("build_transfer_cota_tx", &map, async {
+        let req = TransferReq::from_map(&map).map_err(rpc_err)?;
+        let BuildTxReq {
+            fee_rate,
+            fee_out_point,
+        } = BuildTxReq::from_map(&map).map_err(rpc_err)?;
+        let cota_cell = get_live_cota_cell(&req.lock_script)
+            .await
+            .map_err(rpc_err)?;
+        let fee_cell = get_fee_cell(fee_out_point, &cota_cell)
+            .await
+            .map_err(rpc_err)?;
+        let (smt_root, entries, block_hash) = generate_transfer_smt(req).await.map_err(rpc_err)?;
+        unsigned_cota_tx(CotaTxParams {
+            cota_cell,
+            fee_cell,
+            action: TRANSFER,
+            entries: entries.as_slice().to_vec(),
+            smt_root,
+            header_deps: vec![block_hash],
+            fee_rate,
+        })
+    })
+    .await
+}
+
+pub async fn build_claim_update_tx_rpc(params: Params) -> Result<Value, Error> {
+    info!("Build claim update tx request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("build_claim_update_cota_tx", &map, async {
+        let req = ClaimUpdateReq::from_map(&map).map_err(rpc_err)?;
+        let BuildTxReq {
+            fee_rate,
+            fee_out_point,
+        } = BuildTxReq::from_map(&map).map_err(rpc_err)?;
+        let cota_cell = get_live_cota_cell(&req.lock_script)
+            .await
+            .map_err(rpc_err)?;
+        let fee_cell = get_fee_cell(fee_out_point, &cota_cell)
+            .await
+            .map_err(rpc_err)?;
+        let batches = generate_claim_update_smt(req)
+            .await
+            .map_err(rpc_err)?
+            .into_iter()
+            .map(|(smt_root, entries, block_hash)| {
+                (smt_root, entries.as_slice().to_vec(), block_hash)
+            })
+            .collect();
+        unsigned_claim_txs(cota_cell, fee_cell, CLAIM_UPDATE, batches, fee_rate)
+    })
+    .await
+}
+
+pub async fn build_transfer_update_tx_rpc(params: Params) -> Result<Value, Error> {
+    info!("Build transfer update tx request: {:?}", params);
+    let map: Map<String, Value> = Params::parse(params)?;
+    audit_generate("build_transfer_update_cota_tx", &map, async {
+        let req = TransferUpdateReq::from_map(&map).map_err(rpc_err)?;
+        let BuildTxReq {
+            fee_rate,
+            fee_out_point,
+        } = BuildTxReq::from_map(&map).map_err(rpc_err)?;
+        let cota_cell = get_live_cota_cell(&req.lock_script)
+            .await
+            .map_err(rpc_err)?;
+        let fee_cell = get_fee_cell(fee_out_point, &cota_cell)
+            .await
+            .map_err(rpc_err)?;
+        let (smt_root, entries, block_hash) =
+            generate_transfer_update_smt(req).await.map_err(rpc_err)?;
+        unsigned_cota_tx(CotaTxParams {
+            cota_cell,
+            fee_cell,
+            action: TRANSFER_UPDATE,
+            entries: entries.as_slice().to_vec(),
+            smt_root,
+            header_deps: vec![block_hash],
+            fee_rate,
+        })
+    })
+    .await
+}
+
+fn unsigned_cota_tx(params: CotaTxParams) -> Result<Value, Error> {
+    let smt_root = params.smt_root;
+    let tx = build_cota_tx(params).map_err(rpc_err)?;
+    parse_unsigned_cota_tx(tx, smt_root, tip_number()?).map_err(rpc_err)
+}
+
+// Every claim batch is claimed by a transaction consuming the CoTA cell and the change cell
+// created by the previous one
+fn unsigned_claim_txs(
+    mut cota_cell: CotaCell,
+    mut fee_cell: FeeCell,
+    action: u8,
+    batches: Vec<(H256, Vec<u8>, H256)>,
+    fee_rate: u64,
+) -> Result<Value, Error> {
+    let mut txs = Vec::with_capacity(batches.len());
+    for (smt_root, entries, block_hash) in batches {
+        let tx = build_cota_tx(CotaTxParams {
+            cota_cell,
+            fee_cell,
+            action,
+            entries,
+            smt_root,
+            header_deps: vec![block_hash],
+            fee_rate,
+        })
+        .map_err(rpc_err)?;
+        cota_cell = next_cota_cell(&tx.0);
+        fee_cell = next_fee_cell(&tx.0);
+        txs.push((tx, smt_root));
+    }
+    parse_unsigned_cota_txs(txs, tip_number()?).map_err(rpc_err)
+}
+
 fn tip_number() -> Result<u64, Error> {
     get_syncer_tip_block_number().map_err(rpc_err)
 }
@@ -0,0 +1,230 @@
+use crate::utils::error::Error;
+use crate::utils::helper::parse_bytes;
+use chrono::prelude::*;
+use cota_smt::smt::blake2b_256;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::{Error as RpcError, Value};
+use lazy_static::lazy_static;
+use log::error;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+const DEFAULT_AUDIT_LOG_DIR: &str = "./audit_log";
+const AUDIT_LOG_PREFIX: &str = "audit-";
+const AUDIT_LOG_SUFFIX: &str = ".jsonl";
+
+lazy_static! {
+    static ref AUDIT_LOG_DIR: PathBuf = match env::var("AUDIT_LOG_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_e) => PathBuf::from(DEFAULT_AUDIT_LOG_DIR),
+    };
+    static ref AUDIT_LOG_LOCK: Mutex<()> = Mutex::new(());
+}
+
+tokio::task_local! {
+    // The on-chain SMT roots fetched by the audited generator, keyed by the lock script
+    static PREVIOUS_ROOTS: RefCell<HashMap<Vec<u8>, Option<[u8; 32]>>>;
+}
+
+/// Remember the on-chain SMT root which the audited generator fetched before updating the SMT,
+/// only the first root of each lock script is kept.
+pub fn record_previous_root(lock_script: &[u8], root: Option<[u8; 32]>) {
+    let _ = PREVIOUS_ROOTS.try_with(|roots| {
+        roots
+            .borrow_mut()
+            .entry(lock_script.to_vec())
+            .or_insert(root);
+    });
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub timestamp:     i64,
+    pub method:        String,
+    pub lock_hash:     String,
+    pub request:       Value,
+    pub previous_root: Option<String>,
+    pub smt_root:      Option<String>,
+    pub entries:       Map<String, Value>,
+    pub block_number:  Option<u64>,
+    pub error:         Option<String>,
+    /// The index of the batch in the response whose batches are committed in order
+    #[serde(default)]
+    pub batch:         Option<usize>,
+}
+
+/// Run the SMT generating future and append the request, the on-chain SMT root which the generator
+/// fetched before generating, the resulting root and the returned entries to the daily JSONL audit
+/// log. Failed calls are recorded with the error message as well. Dry runs leave the SMT untouched
+/// and are not recorded.
+pub async fn audit_generate<F>(method: &str, map: &Map<String, Value>, generate: F) -> F::Output
+where
+    F: Future<Output = Result<Value, RpcError>>,
+{
+    if map.get("dry_run").and_then(Value::as_bool).unwrap_or(false) {
+        return generate.await;
+    }
+    let (result, previous_roots) = PREVIOUS_ROOTS
+        .scope(RefCell::new(HashMap::new()), async {
+            let result = generate.await;
+            (result, PREVIOUS_ROOTS.with(|roots| roots.take()))
+        })
+        .await;
+    let lock_script = map
+        .get("lock_script")
+        .and_then(|lock| lock.as_str())
+        .and_then(|lock| parse_bytes(lock.to_owned()).ok())
+        .unwrap_or_default();
+    let previous_root = previous_roots
+        .get(&lock_script)
+        .copied()
+        .flatten()
+        .map(|root| format!("0x{}", hex::encode(root)));
+    let mut record = AuditRecord {
+        timestamp: Local::now().timestamp_millis(),
+        method: method.to_owned(),
+        lock_hash: format!("0x{}", hex::encode(blake2b_256(&lock_script))),
+        request: Value::Object(map.clone()),
+        previous_root,
+        smt_root: None,
+        entries: Map::new(),
+        block_number: None,
+        error: None,
+        batch: None,
+    };
+    let mut records: Vec<AuditRecord> = vec![];
+    match &result {
+        Ok(Value::Object(response)) => match response.get("batches").and_then(Value::as_array) {
+            // Every batch is recorded with its own root and entries, and the previous root of a
+            // batch is the root of the batch before it
+            Some(batches) if !batches.is_empty() => {
+                for (index, batch) in batches.iter().enumerate() {
+                    let mut batch_record = record.clone();
+                    batch_record.batch = Some(index);
+                    if let Some(last_record) = records.last() {
+                        batch_record.previous_root = last_record.smt_root.clone();
+                    }
+                    if let Value::Object(batch) = batch {
+                        fill_record(&mut batch_record, batch);
+                    }
+                    fill_record(&mut batch_record, response);
+                    records.push(batch_record);
+                }
+            }
+            _ => {
+                fill_record(&mut record, response);
+                records.push(record);
+            }
+        },
+        Ok(_) => records.push(record),
+        Err(err) => {
+            record.error = Some(err.message.clone());
+            records.push(record);
+        }
+    }
+    if let Err(err) = append_records(&records) {
+        error!("Append audit log error: {}", err.to_msg());
+    }
+    result
+}
+
+// The fields already filled are kept, so the batch fields take precedence over the top level
+fn fill_record(record: &mut AuditRecord, response: &Map<String, Value>) {
+    for (key, value) in response.iter() {
+        match key.as_str() {
+            "smt_root_hash" if record.smt_root.is_none() => {
+                record.smt_root = value.as_str().map(|root| format!("0x{}", root))
+            }
+            "block_number" if record.block_number.is_none() => record.block_number = value.as_u64(),
+            _ if key.ends_with("_entry") || key.ends_with("_entries") => {
+                record
+                    .entries
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn append_records(records: &[AuditRecord]) -> Result<(), Error> {
+    let mut lines = String::new();
+    for record in records {
+        let line = serde_json::to_string(record)
+            .map_err(|e| Error::Other(format!("Serialize audit record error: {}", e)))?;
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+    let _guard = AUDIT_LOG_LOCK.lock();
+    fs::create_dir_all(&*AUDIT_LOG_DIR)
+        .map_err(|e| Error::Other(format!("Create audit log dir error: {}", e)))?;
+    let file_name = format!(
+        "{}{}{}",
+        AUDIT_LOG_PREFIX,
+        Local::now().format("%Y-%m-%d"),
+        AUDIT_LOG_SUFFIX
+    );
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(AUDIT_LOG_DIR.join(file_name))
+        .map_err(|e| Error::Other(format!("Open audit log error: {}", e)))?;
+    file.write_all(lines.as_bytes())
+        .map_err(|e| Error::Other(format!("Write audit log error: {}", e)))
+}
+
+/// Look up the latest audit records, from the newest log file to the oldest one, which match the
+/// lock hash and the resulting SMT root.
+pub fn get_audit_records(
+    lock_hash: Option<[u8; 32]>,
+    smt_root: Option<[u8; 32]>,
+    limit: usize,
+) -> Result<Vec<AuditRecord>, Error> {
+    let lock_hash = lock_hash.map(|hash| format!("0x{}", hex::encode(hash)));
+    let smt_root = smt_root.map(|root| format!("0x{}", hex::encode(root)));
+    let mut files: Vec<PathBuf> = match fs::read_dir(&*AUDIT_LOG_DIR) {
+        Ok(dir) => dir
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| {
+                        name.starts_with(AUDIT_LOG_PREFIX) && name.ends_with(AUDIT_LOG_SUFFIX)
+                    })
+            })
+            .collect(),
+        Err(_e) => return Ok(vec![]),
+    };
+    files.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut records: Vec<AuditRecord> = vec![];
+    for path in files {
+        let file = fs::File::open(&path)
+            .map_err(|e| Error::Other(format!("Open audit log error: {}", e)))?;
+        let mut file_records: Vec<AuditRecord> = BufReader::new(file)
+            .lines()
+            .filter_map(|line| line.ok())
+            .filter_map(|line| serde_json::from_str::<AuditRecord>(&line).ok())
+            .filter(|record| {
+                lock_hash
+                    .as_ref()
+                    .map_or(true, |hash| &record.lock_hash == hash)
+            })
+            .filter(|record| smt_root.is_none() || record.smt_root == smt_root)
+            .collect();
+        file_records.reverse();
+        records.extend(file_records);
+        if records.len() >= limit {
+            records.truncate(limit);
+            break;
+        }
+    }
+    Ok(records)
+}
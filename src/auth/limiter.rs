@@ -0,0 +1,72 @@
+use crate::utils::error::Error;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Quota {
+    Read,
+    Write,
+}
+
+/// Fixed window rate limiter, every identity(API key, IP or session) has its own counter for
+/// each quota in a one minute window.
+#[derive(Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<(String, Quota), (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    /// The limit zero means unlimited. All identities are checked before counting, so a rejected
+    /// call will not consume the quota of other identities.
+    pub fn check(&self, identities: &[String], quota: Quota, limit: u32) -> Result<(), Error> {
+        if limit == 0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut windows = self.windows.lock();
+        windows.retain(|_, (start, _)| now.duration_since(*start) < WINDOW);
+        for identity in identities {
+            if let Some((_, count)) = windows.get(&(identity.clone(), quota)) {
+                if *count >= limit {
+                    return Err(Error::RateLimitExceeded(identity.clone()));
+                }
+            }
+        }
+        for identity in identities {
+            windows
+                .entry((identity.clone(), quota))
+                .or_insert((now, 0))
+                .1 += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::default();
+        let identities = vec!["key:a".to_owned(), "ip:127.0.0.1".to_owned()];
+        assert!(limiter.check(&identities, Quota::Write, 2).is_ok());
+        assert!(limiter.check(&identities, Quota::Write, 2).is_ok());
+        assert_eq!(
+            limiter.check(&identities, Quota::Write, 2),
+            Err(Error::RateLimitExceeded("key:a".to_owned()))
+        );
+        assert!(limiter.check(&identities, Quota::Read, 2).is_ok());
+        assert_eq!(
+            limiter.check(&["ip:127.0.0.1".to_owned()], Quota::Write, 2),
+            Err(Error::RateLimitExceeded("ip:127.0.0.1".to_owned()))
+        );
+        assert!(limiter
+            .check(&["key:b".to_owned()], Quota::Write, 2)
+            .is_ok());
+        assert!(limiter.check(&identities, Quota::Write, 0).is_ok());
+    }
+}
@@ -0,0 +1,41 @@
+use crate::auth::{check_request, ClientInfo};
+use jsonrpc_http_server::jsonrpc_core::futures::future::{self, Either};
+use jsonrpc_http_server::jsonrpc_core::futures::Future;
+use jsonrpc_http_server::jsonrpc_core::middleware::{Middleware, NoopCallFuture};
+use jsonrpc_http_server::jsonrpc_core::{
+    Call, Error as RpcError, Failure, FutureResponse, Metadata, Output, Version,
+};
+use log::warn;
+
+/// Authenticate the API key and check the rate limits for every method call, including the calls
+/// in a batch request.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AuthMiddleware;
+
+impl<M: Metadata + ClientInfo> Middleware<M> for AuthMiddleware {
+    type CallFuture = NoopCallFuture;
+    type Future = FutureResponse;
+
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: Future<Output = Option<Output>> + Send + 'static,
+    {
+        if let Call::MethodCall(method_call) = &call {
+            if let Err(err) = check_request(&method_call.method, meta.client()) {
+                warn!(
+                    "Reject {} request from {:?}: {}",
+                    method_call.method,
+                    meta.client().ip,
+                    err
+                );
+                return Either::Left(future::ready(Some(Output::Failure(Failure {
+                    jsonrpc: Some(Version::V2),
+                    error:   RpcError::from(err),
+                    id:      method_call.id.clone(),
+                }))));
+            }
+        }
+        Either::Right(next(call, meta))
+    }
+}
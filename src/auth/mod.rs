@@ -0,0 +1,210 @@
+use crate::auth::limiter::{Quota, RateLimiter};
+use crate::utils::error::Error;
+use jsonrpc_http_server::hyper::HeaderMap;
+use jsonrpc_http_server::jsonrpc_core::Metadata;
+use jsonrpc_pubsub::{PubSubMetadata, Session};
+use lazy_static::lazy_static;
+use serde_json::from_str;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+mod limiter;
+pub(crate) mod middleware;
+
+const API_KEY_HEADER: &str = "x-api-key";
+const DEFAULT_READ_LIMIT: u32 = 600;
+const DEFAULT_WRITE_LIMIT: u32 = 60;
+
+lazy_static! {
+    static ref AUTH_CONFIG: AuthConfig = AuthConfig::from_env();
+    static ref RATE_LIMITER: RateLimiter = RateLimiter::default();
+}
+
+struct AuthConfig {
+    api_keys:            HashSet<String>,
+    read_limit:          u32,
+    write_limit:         u32,
+    /// Read the client IP from the proxy headers which can be spoofed without a reverse proxy
+    trust_proxy_headers: bool,
+}
+
+impl AuthConfig {
+    fn from_env() -> Self {
+        let api_keys = match env::var("API_KEYS") {
+            Ok(keys) => keys
+                .split(',')
+                .map(|key| key.trim().to_owned())
+                .filter(|key| !key.is_empty())
+                .collect(),
+            Err(_e) => HashSet::new(),
+        };
+        // The default limits only apply with authentication, zero means unlimited
+        let auth_enabled = !api_keys.is_empty();
+        let default_limit = |limit: u32| if auth_enabled { limit } else { 0 };
+        let read_limit = match env::var("RATE_LIMIT_READ") {
+            Ok(limit) if !limit.is_empty() => from_str::<u32>(&limit).unwrap(),
+            _ => default_limit(DEFAULT_READ_LIMIT),
+        };
+        let write_limit = match env::var("RATE_LIMIT_WRITE") {
+            Ok(limit) if !limit.is_empty() => from_str::<u32>(&limit).unwrap(),
+            _ => default_limit(DEFAULT_WRITE_LIMIT),
+        };
+        let trust_proxy_headers = match env::var("TRUST_PROXY_HEADERS") {
+            Ok(trust) => from_str::<bool>(&trust).unwrap(),
+            Err(_e) => false,
+        };
+        AuthConfig {
+            api_keys,
+            read_limit,
+            write_limit,
+            trust_proxy_headers,
+        }
+    }
+
+    fn is_auth_enabled(&self) -> bool {
+        !self.api_keys.is_empty()
+    }
+}
+
+/// The client information of a request which is used to authenticate and rate limit the calls.
+#[derive(Clone, Debug, Default)]
+pub struct ClientMeta {
+    pub api_key:    Option<String>,
+    pub ip:         Option<String>,
+    /// The WebSocket session whose API key has been checked at handshake
+    pub session_id: Option<u64>,
+}
+
+impl Metadata for ClientMeta {}
+
+impl ClientMeta {
+    /// The client IP is the peer address of the connection, or the proxy headers when
+    /// `TRUST_PROXY_HEADERS` is set.
+    pub fn from_request(headers: &HeaderMap, peer_addr: SocketAddr) -> Self {
+        let header_str = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().to_owned())
+        };
+        let api_key = header_str(API_KEY_HEADER).or_else(|| {
+            header_str("authorization")
+                .and_then(|auth| auth.strip_prefix("Bearer ").map(str::to_owned))
+        });
+        let proxy_ip = if AUTH_CONFIG.trust_proxy_headers {
+            header_str("x-real-ip").or_else(|| {
+                header_str("x-forwarded-for")
+                    .and_then(|ips| ips.split(',').next().map(|ip| ip.trim().to_owned()))
+            })
+        } else {
+            None
+        };
+        let ip = proxy_ip.unwrap_or_else(|| peer_addr.ip().to_string());
+        ClientMeta {
+            api_key,
+            ip: Some(ip),
+            session_id: None,
+        }
+    }
+}
+
+pub trait ClientInfo {
+    fn client(&self) -> &ClientMeta;
+}
+
+impl ClientInfo for ClientMeta {
+    fn client(&self) -> &ClientMeta {
+        self
+    }
+}
+
+/// The metadata of WebSocket requests which supports subscriptions.
+#[derive(Clone)]
+pub struct WsMeta {
+    pub session: Arc<Session>,
+    pub client:  ClientMeta,
+}
+
+impl Metadata for WsMeta {}
+
+impl PubSubMetadata for WsMeta {
+    fn session(&self) -> Option<Arc<Session>> {
+        Some(self.session.clone())
+    }
+}
+
+impl ClientInfo for WsMeta {
+    fn client(&self) -> &ClientMeta {
+        &self.client
+    }
+}
+
+/// Check the API key of WebSocket handshake with the `x-api-key` header or the `api_key` query
+/// parameter.
+pub fn check_handshake_api_key(header: Option<&[u8]>, resource: &str) -> bool {
+    if !AUTH_CONFIG.is_auth_enabled() {
+        return true;
+    }
+    let header_key = header.and_then(|key| std::str::from_utf8(key).ok());
+    let query_key = resource.split_once('?').and_then(|(_, query)| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("api_key="))
+    });
+    header_key
+        .or(query_key)
+        .map_or(false, |key| AUTH_CONFIG.api_keys.contains(key.trim()))
+}
+
+pub fn check_request(method: &str, client: &ClientMeta) -> Result<(), Error> {
+    let config = &*AUTH_CONFIG;
+    if config.is_auth_enabled() && client.session_id.is_none() {
+        match &client.api_key {
+            Some(api_key) if config.api_keys.contains(api_key) => {}
+            _ => return Err(Error::ApiKeyInvalid),
+        }
+    }
+    let (quota, limit) = if is_write_method(method) {
+        (Quota::Write, config.write_limit)
+    } else {
+        (Quota::Read, config.read_limit)
+    };
+    let mut identities = vec![];
+    if let Some(api_key) = &client.api_key {
+        identities.push(format!("key:{}", api_key));
+    }
+    if let Some(ip) = &client.ip {
+        identities.push(format!("ip:{}", ip));
+    }
+    if let Some(session_id) = client.session_id {
+        identities.push(format!("session:{}", session_id));
+    }
+    RATE_LIMITER.check(&identities, quota, limit)
+}
+
+/// The owner of the resources created by the client, which is the hash of its API key. The
+/// clients can not be told apart without API keys, so the calls are refused when authentication
+/// is disabled or the API key is unknown, e.g. WebSocket sessions.
+pub fn client_owner(client: &ClientMeta) -> Result<String, Error> {
+    let config = &*AUTH_CONFIG;
+    if !config.is_auth_enabled() {
+        return Err(Error::AuthDisabled);
+    }
+    match &client.api_key {
+        Some(api_key) if config.api_keys.contains(api_key) => {
+            Ok(hex::encode(Sha256::digest(api_key.as_bytes())))
+        }
+        _ => Err(Error::ApiKeyInvalid),
+    }
+}
+
+/// The SMT generating and transaction building methods which rebuild and commit SMT and the
+/// webhook registry methods are limited by the write quota, others are limited by the read quota.
+fn is_write_method(method: &str) -> bool {
+    method.starts_with("generate_")
+        || (method.starts_with("build_") && method.ends_with("_tx"))
+        || method.ends_with("_webhook")
+}
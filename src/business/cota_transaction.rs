@@ -0,0 +1,168 @@
+use crate::ckb::rpc::{get_cota_code_hash, get_input_cells, get_transaction};
+use crate::entries::verify::{verify_cota_witness, WitnessVerification};
+use crate::request::transaction::CotaTransactionReq;
+use crate::utils::error::Error;
+use ckb_jsonrpc_types::{CellOutput, JsonBytes, Transaction};
+use ckb_types::packed;
+use ckb_types::prelude::{Entity, Unpack};
+use cota_smt::smt::blake2b_256;
+
+#[derive(Clone, Debug, Default)]
+pub struct CotaCellChange {
+    pub lock_script:  Vec<u8>,
+    pub input_index:  Option<u32>,
+    pub output_index: Option<u32>,
+    pub old_root:     Option<[u8; 32]>,
+    pub new_root:     Option<[u8; 32]>,
+    pub version:      Option<u8>,
+    pub witness:      Option<Vec<u8>>,
+}
+
+impl CotaCellChange {
+    pub fn lock_hash(&self) -> [u8; 32] {
+        blake2b_256(&self.lock_script)
+    }
+}
+
+pub async fn get_cota_transaction(
+    req: CotaTransactionReq,
+) -> Result<([u8; 32], Transaction), Error> {
+    let CotaTransactionReq {
+        transaction,
+        tx_hash,
+    } = req;
+    match (transaction, tx_hash) {
+        (Some(transaction), _) => {
+            let tx: packed::Transaction = transaction.clone().into();
+            Ok((tx.calc_tx_hash().unpack().0, transaction))
+        }
+        (None, Some(tx_hash)) => Ok((tx_hash, get_transaction(tx_hash).await?)),
+        (None, None) => Err(Error::RequestParamNotFound(
+            "transaction or tx_hash".to_string(),
+        )),
+    }
+}
+
+// Every CoTA cell is paired by its lock script, the witness of the CoTA type script group is
+// placed at the index of the first input cell of the group, or the first output cell when
+// the group has no input
+pub async fn get_cota_cell_changes(tx: &Transaction) -> Result<Vec<CotaCellChange>, Error> {
+    let input_cells = get_input_cells(
+        tx.inputs
+            .iter()
+            .map(|input| input.previous_output.clone())
+            .collect(),
+    )
+    .await?;
+    let output_cells: Vec<(CellOutput, JsonBytes)> = tx
+        .outputs
+        .iter()
+        .cloned()
+        .zip(tx.outputs_data.iter().cloned())
+        .collect();
+
+    let mut changes: Vec<CotaCellChange> = vec![];
+    for (index, (output, data)) in input_cells.iter().enumerate() {
+        if !is_cota_cell(output) {
+            continue;
+        }
+        let lock_script = packed::Script::from(output.lock.clone())
+            .as_slice()
+            .to_vec();
+        let (version, old_root) = parse_cota_cell_data(data.as_bytes())?;
+        changes.push(CotaCellChange {
+            lock_script,
+            input_index: Some(index as u32),
+            old_root,
+            version: Some(version),
+            ..Default::default()
+        });
+    }
+    for (index, (output, data)) in output_cells.iter().enumerate() {
+        if !is_cota_cell(output) {
+            continue;
+        }
+        let lock_script = packed::Script::from(output.lock.clone())
+            .as_slice()
+            .to_vec();
+        let (version, new_root) = parse_cota_cell_data(data.as_bytes())?;
+        let position = changes
+            .iter()
+            .position(|change| change.output_index.is_none() && change.lock_script == lock_script);
+        match position {
+            Some(position) => {
+                let change = &mut changes[position];
+                change.output_index = Some(index as u32);
+                change.new_root = new_root;
+                change.version = Some(version);
+            }
+            None => changes.push(CotaCellChange {
+                lock_script,
+                output_index: Some(index as u32),
+                new_root,
+                version: Some(version),
+                ..Default::default()
+            }),
+        }
+    }
+    for change in changes.iter_mut() {
+        let witness_index = change.input_index.or(change.output_index).unwrap() as usize;
+        change.witness = tx
+            .witnesses
+            .get(witness_index)
+            .map(|witness| witness.as_bytes().to_vec())
+            .filter(|witness| !witness.is_empty());
+    }
+    Ok(changes)
+}
+
+pub async fn verify_cota_transaction(
+    req: CotaTransactionReq,
+) -> Result<
+    (
+        [u8; 32],
+        Vec<(CotaCellChange, Result<WitnessVerification, Error>)>,
+    ),
+    Error,
+> {
+    let (tx_hash, transaction) = get_cota_transaction(req).await?;
+    let changes = get_cota_cell_changes(&transaction).await?;
+    let verifications = changes
+        .into_iter()
+        .map(|change| {
+            let verification = match &change.witness {
+                Some(witness) => {
+                    verify_cota_witness(witness, change.version, change.old_root, change.new_root)
+                }
+                None => Err(Error::WitnessParseInvalid(
+                    "CoTA witness not found".to_string(),
+                )),
+            };
+            (change, verification)
+        })
+        .collect();
+    Ok((tx_hash, verifications))
+}
+
+// The CoTA cell data is the version byte, followed by the SMT root once the SMT is not empty
+pub fn parse_cota_cell_data(data: &[u8]) -> Result<(u8, Option<[u8; 32]>), Error> {
+    match data.len() {
+        1 => Ok((data[0], None)),
+        33 => {
+            let mut root = [0u8; 32];
+            root.copy_from_slice(&data[1..]);
+            Ok((data[0], Some(root)))
+        }
+        _ => Err(Error::CKBRPCInvalid(
+            "CoTA cell data length error".to_owned(),
+        )),
+    }
+}
+
+fn is_cota_cell(output: &CellOutput) -> bool {
+    let cota_code_hash = get_cota_code_hash();
+    output
+        .type_
+        .as_ref()
+        .map_or(false, |type_| type_.code_hash.as_bytes() == cota_code_hash)
+}
@@ -0,0 +1,143 @@
+use crate::business::tx_builder::cota_witness_size;
+use crate::utils::error::Error;
+
+// The default limits of a transaction in the transaction pool of CKB node
+pub const MAX_TX_SIZE: usize = 512_000;
+pub const MAX_TX_CYCLES: u64 = 70_000_000;
+
+// The cycles are rough estimates of the CoTA type script and the secp256k1 lock. The SMT leaves are
+// hashed and merged along their paths when the old and new SMT roots are verified, the proof
+// bytes are parsed into the merges, and the witness bytes are loaded and hashed for the signature.
+const BASE_CYCLES: u64 = 3_000_000;
+const LEAF_CYCLES: u64 = 100_000;
+const PROOF_BYTE_CYCLES: u64 = 100;
+const WITNESS_BYTE_CYCLES: u64 = 20;
+// The bytes of a CoTA transaction out of the witness, e.g. the inputs, outputs and cell deps
+const TX_BASE_SIZE: usize = 1_000;
+// The proof is only known after the SMT is updated, so the batches are split with the compiled
+// proof of every leaf estimated as 16 sibling hashes with their operation bytes
+const PROOF_SIZE_PER_LEAF: usize = 33 * 16;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TxEstimate {
+    pub witness_size: usize,
+    pub cycles:       u64,
+}
+
+impl TxEstimate {
+    pub fn new(entries_size: usize, leaf_count: usize, proof_size: usize) -> Self {
+        let witness_size = cota_witness_size(entries_size);
+        let cycles = BASE_CYCLES
+            + leaf_count as u64 * LEAF_CYCLES
+            + proof_size as u64 * PROOF_BYTE_CYCLES
+            + witness_size as u64 * WITNESS_BYTE_CYCLES;
+        TxEstimate {
+            witness_size,
+            cycles,
+        }
+    }
+
+    pub fn fits(&self) -> bool {
+        self.witness_size + TX_BASE_SIZE <= MAX_TX_SIZE && self.cycles <= MAX_TX_CYCLES
+    }
+
+    pub fn check(&self) -> Result<(), Error> {
+        if self.fits() {
+            return Ok(());
+        }
+        Err(Error::TxTooLarge {
+            witness_size: self.witness_size,
+            cycles:       self.cycles,
+        })
+    }
+}
+
+// Splits the items of a batch into the batches which fit in one transaction each and returns the
+// lengths of them in order. The items are (entries_size, leaf_count) of every item, and the base
+// is the one of the entries shared by every batch. An item which does not fit in a transaction
+// alone is rejected, since its batch would be rejected by CKB node.
+pub fn split_batch(items: &[(usize, usize)], base: (usize, usize)) -> Result<Vec<usize>, Error> {
+    let (base_size, base_leaves) = base;
+    let mut batch_lens: Vec<usize> = Vec::new();
+    let (mut size, mut leaves, mut len) = (base_size, base_leaves, 0usize);
+    for (item_size, item_leaves) in items.iter() {
+        let proof_size = (leaves + item_leaves) * PROOF_SIZE_PER_LEAF;
+        let estimate = TxEstimate::new(
+            size + item_size + proof_size,
+            leaves + item_leaves,
+            proof_size,
+        );
+        if len > 0 && !estimate.fits() {
+            batch_lens.push(len);
+            size = base_size;
+            leaves = base_leaves;
+            len = 0;
+        }
+        size += item_size;
+        leaves += item_leaves;
+        len += 1;
+    }
+    if len > 0 {
+        batch_lens.push(len);
+    }
+    let mut start = 0;
+    for batch_len in batch_lens.iter() {
+        check_batch(&items[start..start + batch_len], base)?;
+        start += batch_len;
+    }
+    Ok(batch_lens)
+}
+
+// The generating methods which cannot split their items into several transactions reject them
+// before the SMT is updated when they do not fit in one transaction, with the same estimate as
+// split_batch.
+pub fn check_batch(
+    items: &[(usize, usize)],
+    (base_size, base_leaves): (usize, usize),
+) -> Result<(), Error> {
+    let size = base_size + items.iter().map(|(item_size, _)| item_size).sum::<usize>();
+    let leaves = base_leaves
+        + items
+            .iter()
+            .map(|(_, item_leaves)| item_leaves)
+            .sum::<usize>();
+    let proof_size = leaves * PROOF_SIZE_PER_LEAF;
+    TxEstimate::new(size + proof_size, leaves, proof_size).check()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_batch() {
+        assert_eq!(split_batch(&[], (500, 1)), Ok(Vec::<usize>::new()));
+        assert_eq!(split_batch(&[(200, 1); 10], (500, 1)), Ok(vec![10]));
+
+        let batch_lens = split_batch(&[(200, 1); 2000], (500, 1)).unwrap();
+        assert!(batch_lens.len() > 1);
+        assert_eq!(batch_lens.iter().sum::<usize>(), 2000);
+        let first_len = batch_lens[0];
+        let proof_size = (first_len + 1) * PROOF_SIZE_PER_LEAF;
+        assert!(TxEstimate::new(
+            500 + first_len * 200 + proof_size,
+            first_len + 1,
+            proof_size
+        )
+        .fits());
+
+        assert!(matches!(
+            split_batch(&[(200, 1), (MAX_TX_SIZE, 1)], (500, 1)),
+            Err(Error::TxTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_check_batch() {
+        assert_eq!(check_batch(&[(200, 1); 10], (500, 1)), Ok(()));
+        assert!(matches!(
+            check_batch(&[(200, 1); 2000], (500, 1)),
+            Err(Error::TxTooLarge { .. })
+        ));
+    }
+}
@@ -1,2 +1,5 @@
+pub mod cota_transaction;
+pub mod estimate;
 pub mod helper;
 pub mod transaction;
+pub mod tx_builder;
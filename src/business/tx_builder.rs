@@ -0,0 +1,275 @@
+use crate::ckb::indexer::{get_cota_cell, CotaCell};
+use crate::ckb::rpc::{get_cota_cell_dep, get_live_cell, get_lock_cell_dep};
+use crate::utils::error::Error;
+use ckb_jsonrpc_types::{CellInput, CellOutput, JsonBytes, OutPoint, Transaction};
+use ckb_types::bytes::Bytes;
+use ckb_types::core::Capacity;
+use ckb_types::packed::{self, WitnessArgs};
+use ckb_types::prelude::{Builder, Entity, Pack, Unpack};
+use cota_smt::smt::H256;
+
+pub const CREATE: u8 = 1;
+pub const MINT: u8 = 2;
+pub const WITHDRAW: u8 = 3;
+pub const CLAIM: u8 = 4;
+pub const UPDATE: u8 = 5;
+pub const TRANSFER: u8 = 6;
+pub const CLAIM_UPDATE: u8 = 7;
+pub const TRANSFER_UPDATE: u8 = 8;
+
+// The transaction size is estimated with the secp256k1 signature placeholder, and every
+// transaction takes 4 more bytes as its offset in the serialized block
+const SIGNATURE_PLACEHOLDER_SIZE: usize = 65;
+const TX_OFFSET_SIZE: usize = 4;
+// The serialized WitnessArgs is the header of the table with three field offsets, the lock and
+// input_type bytes with their lengths and no output_type
+const WITNESS_ARGS_HEADER_SIZE: usize = 16;
+const BYTES_HEADER_SIZE: usize = 4;
+
+// The plain capacity cell of the CoTA cell's lock which pays the fee, and the change goes back
+// to the lock
+#[derive(Clone, Debug)]
+pub struct FeeCell {
+    pub out_point: OutPoint,
+    pub output:    CellOutput,
+}
+
+#[derive(Clone, Debug)]
+pub struct CotaTxParams {
+    pub cota_cell:   CotaCell,
+    pub fee_cell:    FeeCell,
+    pub action:      u8,
+    pub entries:     Vec<u8>,
+    pub smt_root:    H256,
+    pub header_deps: Vec<H256>,
+    pub fee_rate:    u64,
+}
+
+pub async fn get_live_cota_cell(lock_script: &[u8]) -> Result<CotaCell, Error> {
+    get_cota_cell(lock_script)
+        .await?
+        .ok_or(Error::CKBIndexerInvalid(
+            "The CoTA cell of the lock script does not exist".to_owned(),
+        ))
+}
+
+// The fee cell must be a live cell of the CoTA cell's lock without type script and data, so the
+// signature of the CoTA cell covers it as well
+pub async fn get_fee_cell(out_point: OutPoint, cota_cell: &CotaCell) -> Result<FeeCell, Error> {
+    let (output, output_data) = get_live_cell(out_point.clone())
+        .await?
+        .ok_or_else(|| Error::FeeCellInvalid("The fee cell is not live".to_owned()))?;
+    if output.lock != cota_cell.output.lock {
+        return Err(Error::FeeCellInvalid(
+            "The lock script of the fee cell must be the same as the CoTA cell".to_owned(),
+        ));
+    }
+    if output.type_.is_some() || !output_data.is_empty() {
+        return Err(Error::FeeCellInvalid(
+            "The fee cell must not have type script or data".to_owned(),
+        ));
+    }
+    Ok(FeeCell { out_point, output })
+}
+
+// The unsigned transaction consumes the CoTA cell and creates it again with the new SMT root,
+// and the fee is paid by the fee cell whose change goes back to the lock, so the capacity of
+// the CoTA cell is kept. The cell deps of the CoTA type and the lock are added, and the
+// signature is left to the wallet of the lock script
+pub fn build_cota_tx(params: CotaTxParams) -> Result<(Transaction, u64), Error> {
+    let CotaTxParams {
+        cota_cell,
+        fee_cell,
+        action,
+        entries,
+        smt_root,
+        header_deps,
+        fee_rate,
+    } = params;
+    // The new CoTA cell keeps the version of the consumed one
+    let version = *cota_cell
+        .output_data
+        .as_bytes()
+        .first()
+        .ok_or_else(|| Error::CKBIndexerInvalid("CoTA cell data is empty".to_owned()))?;
+    let mut output_data = vec![version];
+    output_data.extend_from_slice(smt_root.as_slice());
+    let mut input_type = vec![action];
+    input_type.extend(entries);
+    let witness_builder =
+        WitnessArgs::new_builder().input_type(Some(Bytes::from(input_type)).pack());
+    let placeholder_witness = witness_builder
+        .clone()
+        .lock(Some(Bytes::from(vec![0u8; SIGNATURE_PLACEHOLDER_SIZE])).pack())
+        .build();
+
+    let fee_capacity = fee_cell.output.capacity.value();
+    let mut tx = Transaction {
+        version:      0u32.into(),
+        cell_deps:    vec![
+            get_cota_cell_dep()?,
+            get_lock_cell_dep(&cota_cell.output.lock)?,
+        ],
+        header_deps:  header_deps
+            .into_iter()
+            .map(|header_dep| ckb_types::H256(header_dep.into()))
+            .collect(),
+        inputs:       vec![
+            CellInput {
+                previous_output: cota_cell.out_point,
+                since:           0u64.into(),
+            },
+            CellInput {
+                previous_output: fee_cell.out_point,
+                since:           0u64.into(),
+            },
+        ],
+        outputs:      vec![cota_cell.output, fee_cell.output],
+        outputs_data: vec![JsonBytes::from_vec(output_data), JsonBytes::default()],
+        witnesses:    vec![
+            JsonBytes::from_vec(placeholder_witness.as_slice().to_vec()),
+            JsonBytes::default(),
+        ],
+    };
+
+    let tx_size = packed::Transaction::from(tx.clone()).as_slice().len() + TX_OFFSET_SIZE;
+    let fee = calc_fee(tx_size, fee_rate);
+    let occupied_capacity = packed::CellOutput::from(tx.outputs[1].clone())
+        .occupied_capacity(Capacity::zero())
+        .map_err(|e| Error::Other(format!("Fee cell capacity error: {}", e)))?
+        .as_u64();
+    if fee_capacity < occupied_capacity + fee {
+        return Err(Error::FeeCellInvalid(format!(
+            "The fee cell capacity is not enough and {} shannons are required",
+            occupied_capacity + fee
+        )));
+    }
+    tx.outputs[1].capacity = (fee_capacity - fee).into();
+    tx.witnesses[0] = JsonBytes::from_vec(witness_builder.build().as_slice().to_vec());
+    Ok((tx, fee))
+}
+
+// The CoTA cell created by the unsigned transaction, which the next transaction of the same lock
+// consumes before the transaction is committed. The witnesses are not in the transaction hash,
+// so the out point stays the same after the transaction is signed
+pub fn next_cota_cell(tx: &Transaction) -> CotaCell {
+    let tx_hash = packed::Transaction::from(tx.clone()).calc_tx_hash();
+    CotaCell {
+        out_point:   OutPoint {
+            tx_hash: tx_hash.unpack(),
+            index:   0u32.into(),
+        },
+        output:      tx.outputs[0].clone(),
+        output_data: tx.outputs_data[0].clone(),
+    }
+}
+
+// The change cell created by the unsigned transaction, which pays the fee of the next transaction
+pub fn next_fee_cell(tx: &Transaction) -> FeeCell {
+    let tx_hash = packed::Transaction::from(tx.clone()).calc_tx_hash();
+    FeeCell {
+        out_point: OutPoint {
+            tx_hash: tx_hash.unpack(),
+            index:   1u32.into(),
+        },
+        output:    tx.outputs[1].clone(),
+    }
+}
+
+// The out point([12..]) of the CoTA cell, which the mint and withdrawal keys of the transaction
+// consuming it contain
+pub fn cota_out_point(cota_cell: &CotaCell) -> [u8; 24] {
+    let mut out_point = [0u8; 24];
+    out_point
+        .copy_from_slice(&packed::OutPoint::from(cota_cell.out_point.clone()).as_slice()[12..]);
+    out_point
+}
+
+// The size of the signed witness whose input_type is the action and the entries
+pub fn cota_witness_size(entries_size: usize) -> usize {
+    WITNESS_ARGS_HEADER_SIZE
+        + BYTES_HEADER_SIZE
+        + SIGNATURE_PLACEHOLDER_SIZE
+        + BYTES_HEADER_SIZE
+        + 1
+        + entries_size
+}
+
+// The fee rate is in shannons per 1000 bytes and the fee is rounded up
+fn calc_fee(tx_size: usize, fee_rate: u64) -> u64 {
+    (tx_size as u64 * fee_rate + 999) / 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ckb::constants::SECP256K1_CODE_HASH;
+    use crate::utils::helper::parse_bytes_n;
+    use ckb_jsonrpc_types::{Script, ScriptHashType};
+
+    #[test]
+    fn test_calc_fee() {
+        assert_eq!(calc_fee(0, 1000), 0);
+        assert_eq!(calc_fee(1000, 1000), 1000);
+        assert_eq!(calc_fee(333, 1500), 500);
+        assert_eq!(calc_fee(333, 1000), 333);
+        assert_eq!(calc_fee(1, 1), 1);
+    }
+
+    #[test]
+    fn test_build_cota_tx() {
+        let lock = Script {
+            code_hash: ckb_types::H256(
+                parse_bytes_n::<32>(SECP256K1_CODE_HASH.to_owned()).unwrap(),
+            ),
+            hash_type: ScriptHashType::Type,
+            args:      JsonBytes::from_vec(vec![1u8; 20]),
+        };
+        let out_point = |index: u32| OutPoint {
+            tx_hash: ckb_types::H256([index as u8; 32]),
+            index:   index.into(),
+        };
+        let cota_cell = CotaCell {
+            out_point:   out_point(0),
+            output:      CellOutput {
+                capacity: 150_0000_0000u64.into(),
+                lock:     lock.clone(),
+                type_:    None,
+            },
+            output_data: JsonBytes::from_vec(vec![2u8; 33]),
+        };
+        let fee_cell = FeeCell {
+            out_point: out_point(1),
+            output:    CellOutput {
+                capacity: 100_0000_0000u64.into(),
+                lock,
+                type_: None,
+            },
+        };
+        let (tx, fee) = build_cota_tx(CotaTxParams {
+            cota_cell,
+            fee_cell,
+            action: UPDATE,
+            entries: vec![0u8; 100],
+            smt_root: H256::from([3u8; 32]),
+            header_deps: vec![],
+            fee_rate: 1000,
+        })
+        .unwrap();
+        assert_eq!(tx.cell_deps.len(), 2);
+        assert_eq!(tx.inputs.len(), 2);
+        assert_eq!(tx.outputs[0].capacity.value(), 150_0000_0000);
+        assert_eq!(tx.outputs[1].capacity.value(), 100_0000_0000 - fee);
+        assert_eq!(tx.outputs_data[0].as_bytes()[0], 2);
+        assert_eq!(&tx.outputs_data[0].as_bytes()[1..], &[3u8; 32]);
+    }
+
+    #[test]
+    fn test_cota_witness_size() {
+        let witness = WitnessArgs::new_builder()
+            .lock(Some(Bytes::from(vec![0u8; SIGNATURE_PLACEHOLDER_SIZE])).pack())
+            .input_type(Some(Bytes::from(vec![MINT; 101])).pack())
+            .build();
+        assert_eq!(cota_witness_size(100), witness.as_slice().len());
+    }
+}
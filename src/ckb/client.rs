@@ -0,0 +1,233 @@
+use crate::utils::error::Error;
+use ckb_jsonrpc_types::{
+    BlockNumber, BlockView, CellWithStatus, HeaderView, OutPoint, TransactionProof,
+    TransactionWithStatusResponse,
+};
+use ckb_types::H256;
+use cota_smt::smt::blake2b_256;
+use lazy_static::lazy_static;
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde_json::{from_str, json, Map, Value};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref CKB_CLIENT: Box<dyn CkbClient> = init_ckb_client();
+}
+
+pub fn ckb_client() -> &'static dyn CkbClient {
+    CKB_CLIENT.as_ref()
+}
+
+// The calls of the CKB node and its indexer module which the aggregator makes. The clients
+// only need to serve the JSON-RPC requests, and the typed calls are built on top of them.
+// The requests are blocking, so they must run in tokio::task::spawn_blocking.
+pub trait CkbClient: Send + Sync {
+    fn request(&self, method: &str, params: Value) -> Result<Value, Error>;
+
+    fn get_cells(&self, search_key: Value, order: &str, limit: u32) -> Result<Value, Error> {
+        self.request(
+            "get_cells",
+            json!([search_key, order, format!("{:#x}", limit)]),
+        )
+    }
+
+    fn get_indexer_tip(&self) -> Result<Value, Error> {
+        self.request("get_indexer_tip", json!([]))
+    }
+
+    fn get_tip_block_number(&self) -> Result<BlockNumber, Error> {
+        parse_result(
+            "get_tip_block_number",
+            self.request("get_tip_block_number", json!([]))?,
+        )
+    }
+
+    fn get_block_by_number(&self, block_number: BlockNumber) -> Result<Option<BlockView>, Error> {
+        let result = self.request("get_block_by_number", json!([block_number]))?;
+        parse_result("get_block_by_number", result)
+    }
+
+    fn get_header_by_number(&self, block_number: BlockNumber) -> Result<Option<HeaderView>, Error> {
+        let result = self.request("get_header_by_number", json!([block_number]))?;
+        parse_result("get_header_by_number", result)
+    }
+
+    fn get_transaction(
+        &self,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionWithStatusResponse>, Error> {
+        let result = self.request("get_transaction", json!([tx_hash]))?;
+        parse_result("get_transaction", result)
+    }
+
+    fn get_live_cell(&self, out_point: OutPoint, with_data: bool) -> Result<CellWithStatus, Error> {
+        let result = self.request("get_live_cell", json!([out_point, with_data]))?;
+        parse_result("get_live_cell", result)
+    }
+
+    fn get_transaction_proof(
+        &self,
+        tx_hashes: Vec<H256>,
+        block_hash: Option<H256>,
+    ) -> Result<TransactionProof, Error> {
+        let result = self.request("get_transaction_proof", json!([tx_hashes, block_hash]))?;
+        parse_result("get_transaction_proof", result)
+    }
+}
+
+fn parse_result<T: DeserializeOwned>(method: &str, result: Value) -> Result<T, Error> {
+    serde_json::from_value::<T>(result)
+        .map_err(|e| Error::CKBRPCInvalid(format!("Parse {} response error: {}", method, e)))
+}
+
+// CKB_FIXTURE_DIR switches the client to the fixtures, and with CKB_FIXTURE_RECORD=true the
+// responses of CKB_NODE are recorded into it instead
+fn init_ckb_client() -> Box<dyn CkbClient> {
+    let http_client = HttpCkbClient {
+        url: env::var("CKB_NODE").ok(),
+    };
+    let fixture_dir = match env::var("CKB_FIXTURE_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => return Box::new(http_client),
+    };
+    let record: bool = match env::var("CKB_FIXTURE_RECORD") {
+        Ok(record) => from_str::<bool>(&record).unwrap(),
+        Err(_e) => false,
+    };
+    if record {
+        warn!(
+            "The responses of CKB node are recorded into {:?}",
+            fixture_dir
+        );
+        Box::new(RecordingCkbClient {
+            inner: http_client,
+            fixture_dir,
+        })
+    } else {
+        warn!(
+            "The responses of CKB node are served from {:?}",
+            fixture_dir
+        );
+        Box::new(FixtureCkbClient { fixture_dir })
+    }
+}
+
+pub struct HttpCkbClient {
+    url: Option<String>,
+}
+
+impl CkbClient for HttpCkbClient {
+    fn request(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let url = self
+            .url
+            .as_ref()
+            .ok_or_else(|| Error::Other("CKB_NODE must be set".to_owned()))?;
+        let mut req_json = Map::new();
+        req_json.insert("id".to_owned(), json!("1"));
+        req_json.insert("jsonrpc".to_owned(), json!("2.0"));
+        req_json.insert("method".to_owned(), json!(method));
+        req_json.insert("params".to_owned(), params);
+
+        let output = reqwest::blocking::Client::new()
+            .post(url)
+            .json(&req_json)
+            .send()
+            .and_then(|resp| resp.json::<jsonrpc_core::response::Output>())
+            .map_err(|e| Error::CKBRPCInvalid(format!("{} error: {}", method, e)))?;
+        match output {
+            jsonrpc_core::response::Output::Success(success) => Ok(success.result),
+            jsonrpc_core::response::Output::Failure(failure) => Err(Error::CKBRPCInvalid(format!(
+                "{} error: {}",
+                method, failure.error.message
+            ))),
+        }
+    }
+}
+
+// The fixture of a request is the JSON result saved as {method}-{hash}.json, where the hash is
+// the first 8 bytes of the blake2b hash of the params in hex. {method}.json serves the requests
+// of the method whose params have no fixture, e.g. the tip block number.
+pub struct FixtureCkbClient {
+    fixture_dir: PathBuf,
+}
+
+impl FixtureCkbClient {
+    fn fixture_path(fixture_dir: &Path, method: &str, params: &Value) -> PathBuf {
+        let params_hash = blake2b_256(params.to_string().as_bytes());
+        fixture_dir.join(format!(
+            "{}-{}.json",
+            method,
+            hex::encode(&params_hash[..8])
+        ))
+    }
+}
+
+impl CkbClient for FixtureCkbClient {
+    fn request(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let path = Self::fixture_path(&self.fixture_dir, method, &params);
+        let fallback_path = self.fixture_dir.join(format!("{}.json", method));
+        let content = fs::read_to_string(&path)
+            .or_else(|_| fs::read_to_string(&fallback_path))
+            .map_err(|_e| {
+                Error::CKBRPCInvalid(format!(
+                    "{} fixture {:?} of params {} does not exist",
+                    method, path, params
+                ))
+            })?;
+        debug!("Serve {} from the fixture {:?}", method, path);
+        from_str::<Value>(&content)
+            .map_err(|e| Error::CKBRPCInvalid(format!("{} fixture error: {}", method, e)))
+    }
+}
+
+pub struct RecordingCkbClient {
+    inner:       HttpCkbClient,
+    fixture_dir: PathBuf,
+}
+
+impl CkbClient for RecordingCkbClient {
+    fn request(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let result = self.inner.request(method, params.clone())?;
+        let path = FixtureCkbClient::fixture_path(&self.fixture_dir, method, &params);
+        let content = serde_json::to_string_pretty(&result)
+            .map_err(|e| Error::Other(format!("Serialize fixture error: {}", e)))?;
+        if let Err(e) =
+            fs::create_dir_all(&self.fixture_dir).and_then(|_| fs::write(&path, content))
+        {
+            warn!("Record fixture {:?} error: {}", path, e);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_fixture_ckb_client() {
+        let fixture_dir =
+            env::temp_dir().join(format!("ckb-fixtures-{}", thread_rng().gen::<u32>()));
+        fs::create_dir_all(&fixture_dir).unwrap();
+        let client = FixtureCkbClient {
+            fixture_dir: fixture_dir.clone(),
+        };
+        let params = json!([BlockNumber::from(100u64)]);
+        let path = FixtureCkbClient::fixture_path(&fixture_dir, "get_block_by_number", &params);
+        fs::write(&path, "null").unwrap();
+        fs::write(fixture_dir.join("get_tip_block_number.json"), "\"0x64\"").unwrap();
+
+        assert!(matches!(
+            client.get_block_by_number(100u64.into()),
+            Ok(None)
+        ));
+        assert!(client.get_block_by_number(101u64.into()).is_err());
+        assert_eq!(client.get_tip_block_number(), Ok(100u64.into()));
+
+        fs::remove_dir_all(fixture_dir).unwrap();
+    }
+}
@@ -2,3 +2,27 @@ pub const TESTNET_COTA_CODE_HASH: &str =
     "89cd8003a0eaf8e65e0c31525b7d1d5c1becefd2ea75bb4cff87810ae37764d8";
 pub const MAINNET_COTA_CODE_HASH: &str =
     "1122a4fb54697cf2e6e3a96c9d80fd398a936559b90954c6e88eb7ba0cf652df";
+pub const TESTNET_COTA_CELL_DEP_TX_HASH: &str =
+    "636a786001f87cb615acfcf408be0f9a1f077001f0bbc75ca54eadfe7e221713";
+pub const MAINNET_COTA_CELL_DEP_TX_HASH: &str =
+    "875db3381ebe7a730676c110e1c0d78ed1ef1fc5ed1b8efee7f4ea3a2a8c0d11";
+pub const TESTNET_REGISTRY_CODE_HASH: &str =
+    "9302db6cc1344b81a5efee06962abcb40427ecfcbe69d471b01b2658ed948075";
+pub const TESTNET_REGISTRY_ARGS: &str = "f9910364e0ca81a0e074f3aa42fe78cfcc880da6";
+pub const MAINNET_REGISTRY_CODE_HASH: &str =
+    "90ca618be6c15f5857d3cbd09f9f24ca6770af047ba9ee70989ec3b229419ac7";
+pub const MAINNET_REGISTRY_ARGS: &str = "563631b49cee549f3585ab4dde5f9d590f507f1f";
+pub const SECP256K1_CODE_HASH: &str =
+    "9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8";
+pub const TESTNET_SECP256K1_CELL_DEP_TX_HASH: &str =
+    "f8de3bb47d055cdf460d93a2a6e1b05f7432f9777c8c474abf4eec1d4aee5d37";
+pub const MAINNET_SECP256K1_CELL_DEP_TX_HASH: &str =
+    "71a7ba8fc96349fea0ed3a5c47992e3b4084b031a42264a018e0072e8172e46c";
+pub const TESTNET_JOYID_CODE_HASH: &str =
+    "d23761b364210735c19c60561d213fb3beae2fd6172743719eff6920e020baac";
+pub const MAINNET_JOYID_CODE_HASH: &str =
+    "d00c84f0ec8fd441c38bc3f87a371f547190f2fcff88e642bc5bf54b9e318323";
+pub const TESTNET_JOYID_CELL_DEP_TX_HASH: &str =
+    "4dcf3f3b09efac8995d6cbee87c5345e812d310094651e0c3d9a730f32dc9263";
+pub const MAINNET_JOYID_CELL_DEP_TX_HASH: &str =
+    "f05188e5f3a6767fc4687faf45ba5f1a6e25d3ada6129dae8722cb282f262493";
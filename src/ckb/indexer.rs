@@ -1,19 +1,50 @@
-use crate::ckb::constants::{MAINNET_COTA_CODE_HASH, TESTNET_COTA_CODE_HASH};
+use crate::audit::record_previous_root;
+use crate::ckb::client::ckb_client;
+use crate::ckb::constants::{
+    MAINNET_COTA_CODE_HASH, MAINNET_REGISTRY_ARGS, MAINNET_REGISTRY_CODE_HASH,
+    TESTNET_COTA_CODE_HASH, TESTNET_REGISTRY_ARGS, TESTNET_REGISTRY_CODE_HASH,
+};
 use crate::utils::error::Error;
 use ckb_jsonrpc_types::{BlockNumber, CellOutput, JsonBytes, OutPoint, Uint32};
 use ckb_types::packed::Script;
 use ckb_types::prelude::Entity;
 use ckb_types::H256;
-use serde::de::DeserializeOwned;
 use serde::Deserialize;
-use serde_json::{from_str, json, Map, Value};
+use serde_json::{from_str, json, Value};
 use std::env;
 
 pub async fn get_cota_smt_root(lock_script: &[u8]) -> Result<Option<[u8; 32]>, Error> {
-    let result =
-        build_rpc::<CellPagination>("get_cells", Some(generate_params(lock_script)?)).await?;
+    let result = get_cells(generate_params(lock_script)?).await?;
+    let root = if result.objects.is_empty() {
+        None
+    } else {
+        let cell_data = result.objects.first().unwrap().output_data.as_bytes();
+        match cell_data.len() {
+            1 => None,
+            33 => {
+                let mut ret = [0u8; 32];
+                ret.copy_from_slice(&cell_data[1..]);
+                Some(ret)
+            }
+            _ => {
+                return Err(Error::CKBIndexerInvalid(
+                    "CoTA cell data length error".to_owned(),
+                ))
+            }
+        }
+    };
+    record_previous_root(lock_script, root);
+    Ok(root)
+}
+
+// The registry cell data is the version byte, followed by the SMT root once the registry SMT
+// is not empty
+pub async fn get_registry_smt_root() -> Result<Option<[u8; 32]>, Error> {
+    let result = get_cells(generate_registry_params()).await?;
     if result.objects.is_empty() {
-        return Ok(None);
+        return Err(Error::CKBIndexerInvalid(
+            "The registry cell does not exist".to_owned(),
+        ));
     }
     let cell_data = result.objects.first().unwrap().output_data.as_bytes();
     match cell_data.len() {
@@ -24,49 +55,36 @@ pub async fn get_cota_smt_root(lock_script: &[u8]) -> Result<Option<[u8; 32]>, E
             Ok(Some(ret))
         }
         _ => Err(Error::CKBIndexerInvalid(
-            "CoTA cell data length error".to_owned(),
+            "Registry cell data length error".to_owned(),
         )),
     }
 }
 
-pub async fn get_indexer_tip_block_number() -> Result<u64, Error> {
-    let result = build_rpc::<Tip>("get_indexer_tip", None).await?;
-    Ok(u64::from(result.block_number))
+pub async fn get_cota_cell(lock_script: &[u8]) -> Result<Option<CotaCell>, Error> {
+    let result = get_cells(generate_params(lock_script)?).await?;
+    Ok(result.objects.into_iter().next().map(|cell| CotaCell {
+        out_point:   cell.out_point,
+        output:      cell.output,
+        output_data: cell.output_data,
+    }))
 }
 
-async fn build_rpc<T: DeserializeOwned>(method: &str, params: Option<Value>) -> Result<T, Error> {
-    let ckb_node_url =
-        env::var("CKB_NODE").map_err(|_e| Error::Other("CKB_NODE must be set".to_owned()))?;
-
-    let mut req_json = Map::new();
-    req_json.insert("id".to_owned(), json!("1"));
-    req_json.insert("jsonrpc".to_owned(), json!("2.0"));
-    req_json.insert("method".to_owned(), json!(method));
-    if let Some(param) = params {
-        req_json.insert("params".to_owned(), param);
-    }
-
-    let client = reqwest::Client::new();
-
-    let resp = client
-        .post(ckb_node_url)
-        .json(&req_json)
-        .send()
+pub async fn get_indexer_tip_block_number() -> Result<u64, Error> {
+    let result = tokio::task::spawn_blocking(|| ckb_client().get_indexer_tip())
         .await
-        .map_err(|e| Error::Other(format!("CKB node indexer rpc error: {:?}", e.to_string())))?;
-    let output = resp
-        .json::<jsonrpc_core::response::Output>()
+        .unwrap()?;
+    let tip = serde_json::from_value::<Tip>(result)
+        .map_err(|_e| Error::CKBIndexerInvalid("Parse response error".to_owned()))?;
+    Ok(u64::from(tip.block_number))
+}
+
+// The first live cell of the search key is enough for the CoTA and registry cells
+async fn get_cells(search_key: Value) -> Result<CellPagination, Error> {
+    let result = tokio::task::spawn_blocking(move || ckb_client().get_cells(search_key, "asc", 1))
         .await
-        .map_err(|e| Error::Other(format!("CKB node indexer rpc error: {:?}", e.to_string())))?;
-    match output {
-        jsonrpc_core::response::Output::Success(success) => {
-            serde_json::from_value::<T>(success.result)
-                .map_err(|_e| Error::CKBIndexerInvalid("Parse response error".to_owned()))
-        }
-        jsonrpc_core::response::Output::Failure(failure) => {
-            Err(Error::CKBIndexerInvalid(failure.error.message))
-        }
-    }
+        .unwrap()?;
+    serde_json::from_value::<CellPagination>(result)
+        .map_err(|_e| Error::CKBIndexerInvalid("Parse response error".to_owned()))
 }
 
 fn generate_params(lock_script: &[u8]) -> Result<Value, Error> {
@@ -88,34 +106,55 @@ fn generate_params(lock_script: &[u8]) -> Result<Value, Error> {
         format!("0x{}", TESTNET_COTA_CODE_HASH)
     };
 
-    Ok(json!([
-        {
+    Ok(json!({
+        "script": {
+            "code_hash": format!("0x{}", hex::encode(lock.code_hash().as_slice())),
+            "hash_type": hash_type,
+            "args": format!("0x{}", hex::encode(lock.args().raw_data())),
+        },
+        "script_type": "lock",
+        "filter": {
             "script": {
-                "code_hash": format!("0x{}", hex::encode(lock.code_hash().as_slice())),
-                "hash_type": hash_type,
-                "args": format!("0x{}", hex::encode(lock.args().raw_data())),
+                "code_hash": code_hash,
+                "hash_type": "type",
+                "args": "0x",
             },
-            "script_type": "lock",
-            "filter": {
-                "script": {
-                    "code_hash": code_hash,
-                    "hash_type": "type",
-                    "args": "0x",
-                },
-            }
+        }
+    }))
+}
+
+fn generate_registry_params() -> Value {
+    let is_mainnet: bool = match env::var("IS_MAINNET") {
+        Ok(mainnet) => from_str::<bool>(&mainnet).unwrap(),
+        Err(_e) => false,
+    };
+    let (code_hash, args) = if is_mainnet {
+        (MAINNET_REGISTRY_CODE_HASH, MAINNET_REGISTRY_ARGS)
+    } else {
+        (TESTNET_REGISTRY_CODE_HASH, TESTNET_REGISTRY_ARGS)
+    };
+    json!({
+        "script": {
+            "code_hash": format!("0x{}", code_hash),
+            "hash_type": "type",
+            "args": format!("0x{}", args),
         },
-        "asc",
-        "0x1"
-    ]))
+        "script_type": "type",
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct CotaCell {
+    pub out_point:   OutPoint,
+    pub output:      CellOutput,
+    pub output_data: JsonBytes,
 }
 
 #[derive(Deserialize)]
 struct Cell {
-    #[serde(skip_deserializing)]
-    _output:       CellOutput,
+    output:        CellOutput,
     output_data:   JsonBytes,
-    #[serde(skip_deserializing)]
-    _out_point:    OutPoint,
+    out_point:     OutPoint,
     #[serde(skip_deserializing)]
     _block_number: BlockNumber,
     #[serde(skip_deserializing)]
@@ -1,3 +1,4 @@
+pub mod client;
 mod constants;
 pub mod indexer;
 pub mod rpc;
@@ -1,10 +1,17 @@
-use crate::ckb::constants::{MAINNET_COTA_CODE_HASH, TESTNET_COTA_CODE_HASH};
+use crate::ckb::client::{ckb_client, CkbClient};
+use crate::ckb::constants::{
+    MAINNET_COTA_CELL_DEP_TX_HASH, MAINNET_COTA_CODE_HASH, MAINNET_JOYID_CELL_DEP_TX_HASH,
+    MAINNET_JOYID_CODE_HASH, MAINNET_SECP256K1_CELL_DEP_TX_HASH, SECP256K1_CODE_HASH,
+    TESTNET_COTA_CELL_DEP_TX_HASH, TESTNET_COTA_CODE_HASH, TESTNET_JOYID_CELL_DEP_TX_HASH,
+    TESTNET_JOYID_CODE_HASH, TESTNET_SECP256K1_CELL_DEP_TX_HASH,
+};
 use crate::utils::error::Error;
-use crate::utils::helper::parse_bytes_n;
+use crate::utils::helper::{parse_bytes_n, remove_0x};
 use ckb_jsonrpc_types::{
-    BlockNumber, Script as RPCScript, TransactionProof as JSONRPCTxProof, TransactionView, Uint64,
+    BlockNumber, CellDep, CellOutput, DepType, Either, JsonBytes, OutPoint, ResponseFormat,
+    Script as RPCScript, Transaction as RPCTransaction, TransactionProof as JSONRPCTxProof,
+    TransactionView, Uint64,
 };
-use ckb_sdk::CkbRpcClient;
 use ckb_types::packed::{BytesVec, Script, Transaction};
 use cota_smt::common::{
     Byte32, Byte32VecBuilder, Bytes, BytesBuilder, MerkleProofBuilder, TransactionProof,
@@ -13,6 +20,7 @@ use cota_smt::common::{
 use cota_smt::smt::{blake2b_256, H256};
 use molecule::prelude::{Builder, Byte, Entity};
 use serde_json::from_str;
+use std::collections::HashMap;
 use std::env;
 
 #[derive(Clone, Debug, Default)]
@@ -30,21 +38,12 @@ pub async fn get_withdraw_info(
     withdrawal_lock_hash: [u8; 32],
     withdrawal_tx_hash: [u8; 32],
 ) -> Result<WithdrawRawTx, Error> {
-    let is_mainnet: bool = match env::var("IS_MAINNET") {
-        Ok(mainnet) => from_str::<bool>(&mainnet).unwrap(),
-        Err(_e) => false,
-    };
-    let cota_code_hash = if is_mainnet {
-        parse_bytes_n::<32>(MAINNET_COTA_CODE_HASH.to_owned()).unwrap()
-    } else {
-        parse_bytes_n::<32>(TESTNET_COTA_CODE_HASH.to_owned()).unwrap()
-    };
+    let cota_code_hash = get_cota_code_hash();
 
     tokio::task::spawn_blocking(move || {
-        let mut client = ckb_node_client()?;
+        let client = ckb_client();
         let block = client
-            .get_block_by_number(Uint64::from(block_number))
-            .map_err(|_e| Error::CKBRPCInvalid("get_block_by_number".to_string()))?
+            .get_block_by_number(Uint64::from(block_number))?
             .ok_or(Error::CKBRPCInvalid("get_block error".to_string()))?;
         let block_hash = block.header.hash;
         let block_number = block.header.inner.number.value();
@@ -86,9 +85,8 @@ pub async fn get_withdraw_info(
             )
             .build();
 
-        let transaction_proof = client
-            .get_transaction_proof(vec![tx_view.hash], Some(block_hash.clone()))
-            .map_err(|_e| Error::CKBRPCInvalid("get_transaction_proof".to_string()))?;
+        let transaction_proof =
+            client.get_transaction_proof(vec![tx_view.hash], Some(block_hash.clone()))?;
         let tx_proof = get_tx_proof(transaction_proof);
 
         let withdraw_info = WithdrawRawTx {
@@ -108,10 +106,7 @@ pub async fn get_withdraw_info(
 
 pub async fn get_node_tip_block_number() -> Result<u64, Error> {
     tokio::task::spawn_blocking(move || {
-        let mut client = ckb_node_client()?;
-        let block_number = client
-            .get_tip_block_number()
-            .map_err(|_e| Error::CKBRPCInvalid("get_tip_block_number".to_string()))?;
+        let block_number = ckb_client().get_tip_block_number()?;
         Ok(u64::from(block_number))
     })
     .await
@@ -120,10 +115,8 @@ pub async fn get_node_tip_block_number() -> Result<u64, Error> {
 
 pub async fn get_block_timestamp(block_number: u64) -> Result<u64, Error> {
     tokio::task::spawn_blocking(move || {
-        let mut client = ckb_node_client()?;
-        let header = client
-            .get_header_by_number(BlockNumber::from(block_number))
-            .map_err(|_e| Error::CKBRPCInvalid("get_header_by_number".to_string()))?
+        let header = ckb_client()
+            .get_header_by_number(BlockNumber::from(block_number))?
             .ok_or(Error::CKBRPCInvalid("get_header_by_number".to_string()))?;
         Ok(header.inner.timestamp.value())
     })
@@ -131,6 +124,147 @@ pub async fn get_block_timestamp(block_number: u64) -> Result<u64, Error> {
     .unwrap()
 }
 
+pub async fn get_transaction(tx_hash: [u8; 32]) -> Result<RPCTransaction, Error> {
+    tokio::task::spawn_blocking(move || fetch_transaction(ckb_client(), tx_hash))
+        .await
+        .unwrap()
+}
+
+// The input cells are fetched from the transactions which created them, so the spent cells
+// can be resolved as well
+pub async fn get_input_cells(
+    out_points: Vec<OutPoint>,
+) -> Result<Vec<(CellOutput, JsonBytes)>, Error> {
+    tokio::task::spawn_blocking(move || {
+        let client = ckb_client();
+        let mut txs: HashMap<[u8; 32], RPCTransaction> = HashMap::new();
+        let mut cells = Vec::with_capacity(out_points.len());
+        for out_point in out_points {
+            let tx_hash = out_point.tx_hash.0;
+            if !txs.contains_key(&tx_hash) {
+                txs.insert(tx_hash, fetch_transaction(client, tx_hash)?);
+            }
+            let tx = txs.get(&tx_hash).unwrap();
+            let index = out_point.index.value() as usize;
+            let output = tx.outputs.get(index).cloned().ok_or(Error::CKBRPCInvalid(
+                "Input cell does not exist".to_string(),
+            ))?;
+            let output_data = tx.outputs_data.get(index).cloned().unwrap_or_default();
+            cells.push((output, output_data));
+        }
+        Ok(cells)
+    })
+    .await
+    .unwrap()
+}
+
+pub fn get_cota_code_hash() -> [u8; 32] {
+    let is_mainnet: bool = match env::var("IS_MAINNET") {
+        Ok(mainnet) => from_str::<bool>(&mainnet).unwrap(),
+        Err(_e) => false,
+    };
+    if is_mainnet {
+        parse_bytes_n::<32>(MAINNET_COTA_CODE_HASH.to_owned()).unwrap()
+    } else {
+        parse_bytes_n::<32>(TESTNET_COTA_CODE_HASH.to_owned()).unwrap()
+    }
+}
+
+// The CoTA type script is deployed as a dep group, and COTA_CELL_DEP_TX_HASH overrides the
+// deployment of the configured network
+pub fn get_cota_cell_dep() -> Result<CellDep, Error> {
+    let tx_hash = match env::var("COTA_CELL_DEP_TX_HASH") {
+        Ok(tx_hash) if !tx_hash.is_empty() => remove_0x(&tx_hash).to_owned(),
+        _ => {
+            let is_mainnet: bool = match env::var("IS_MAINNET") {
+                Ok(mainnet) => from_str::<bool>(&mainnet).unwrap(),
+                Err(_e) => false,
+            };
+            if is_mainnet {
+                MAINNET_COTA_CELL_DEP_TX_HASH.to_owned()
+            } else {
+                TESTNET_COTA_CELL_DEP_TX_HASH.to_owned()
+            }
+        }
+    };
+    Ok(CellDep {
+        out_point: OutPoint {
+            tx_hash: ckb_types::H256(parse_bytes_n::<32>(tx_hash)?),
+            index:   0u32.into(),
+        },
+        dep_type:  DepType::DepGroup,
+    })
+}
+
+// The secp256k1 and JoyID locks of the configured network are deployed as dep groups, and the
+// CoTA cells with other locks are not supported
+pub fn get_lock_cell_dep(lock: &RPCScript) -> Result<CellDep, Error> {
+    let is_mainnet: bool = match env::var("IS_MAINNET") {
+        Ok(mainnet) => from_str::<bool>(&mainnet).unwrap(),
+        Err(_e) => false,
+    };
+    let (joyid_code_hash, joyid_tx_hash, secp256k1_tx_hash) = if is_mainnet {
+        (
+            MAINNET_JOYID_CODE_HASH,
+            MAINNET_JOYID_CELL_DEP_TX_HASH,
+            MAINNET_SECP256K1_CELL_DEP_TX_HASH,
+        )
+    } else {
+        (
+            TESTNET_JOYID_CODE_HASH,
+            TESTNET_JOYID_CELL_DEP_TX_HASH,
+            TESTNET_SECP256K1_CELL_DEP_TX_HASH,
+        )
+    };
+    let code_hash = hex::encode(lock.code_hash.as_bytes());
+    let tx_hash = if code_hash == SECP256K1_CODE_HASH {
+        secp256k1_tx_hash
+    } else if code_hash == joyid_code_hash {
+        joyid_tx_hash
+    } else {
+        return Err(Error::LockScriptUnsupported(code_hash));
+    };
+    Ok(CellDep {
+        out_point: OutPoint {
+            tx_hash: ckb_types::H256(parse_bytes_n::<32>(tx_hash.to_owned())?),
+            index:   0u32.into(),
+        },
+        dep_type:  DepType::DepGroup,
+    })
+}
+
+pub async fn get_live_cell(out_point: OutPoint) -> Result<Option<(CellOutput, JsonBytes)>, Error> {
+    tokio::task::spawn_blocking(move || {
+        let cell_with_status = ckb_client().get_live_cell(out_point, true)?;
+        Ok(cell_with_status.cell.map(|cell| {
+            let output_data = cell.data.map(|data| data.content).unwrap_or_default();
+            (cell.output, output_data)
+        }))
+    })
+    .await
+    .unwrap()
+}
+
+fn fetch_transaction(client: &dyn CkbClient, tx_hash: [u8; 32]) -> Result<RPCTransaction, Error> {
+    let tx = client
+        .get_transaction(ckb_types::H256(tx_hash))?
+        .and_then(|tx_with_status| tx_with_status.transaction)
+        .ok_or(Error::CKBRPCInvalid(format!(
+            "The tx 0x{} does not exist",
+            hex::encode(tx_hash)
+        )))?;
+    parse_tx_response(tx)
+}
+
+fn parse_tx_response(tx: ResponseFormat<TransactionView>) -> Result<RPCTransaction, Error> {
+    match tx.inner {
+        Either::Left(tx_view) => Ok(tx_view.inner),
+        Either::Right(bytes) => Transaction::from_slice(bytes.as_bytes())
+            .map(RPCTransaction::from)
+            .map_err(|_e| Error::CKBRPCInvalid("Parse transaction error".to_string())),
+    }
+}
+
 fn get_tx_proof(transaction_proof: JSONRPCTxProof) -> TransactionProof {
     let indices = Uint32VecBuilder::default()
         .set(
@@ -162,9 +296,3 @@ fn get_tx_proof(transaction_proof: JSONRPCTxProof) -> TransactionProof {
         )
         .build()
 }
-
-fn ckb_node_client() -> Result<CkbRpcClient, Error> {
-    let ckb_node_url =
-        env::var("CKB_NODE").map_err(|_e| Error::Other("CKB_NODE must be set".to_owned()))?;
-    Ok(CkbRpcClient::new(&ckb_node_url))
-}
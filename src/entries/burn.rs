@@ -0,0 +1,47 @@
+use crate::entries::constants::STATE_BURNED;
+use crate::entries::policy::check_burn;
+use crate::entries::update::{generate_update_batch, update_smt_batches};
+use crate::models::hold::{get_hold_cota_by_lock_hash, HoldDb};
+use crate::request::burn::BurnReq;
+use crate::utils::error::Error;
+use cota_smt::smt::{blake2b_256, H256};
+use cota_smt::update::UpdateCotaNFTEntries;
+
+const BURN_ACTION: &str = "Burn NFTs";
+
+// The CoTA type script has no action removing the hold leaves, so the NFTs are burned by the
+// update entries which set the burned bit of their states, and the burned NFTs cannot be
+// updated, withdrawn or transferred any more
+pub async fn generate_burn_smt(burn_req: BurnReq) -> Result<(H256, UpdateCotaNFTEntries), Error> {
+    let nfts = burn_req.nfts;
+    if nfts.is_empty() {
+        return Err(Error::RequestParamNotFound("nfts".to_string()));
+    }
+    let cota_id_index_pairs: Vec<([u8; 20], [u8; 4])> = nfts
+        .iter()
+        .map(|nft| (nft.cota_id, nft.token_index))
+        .collect();
+    let db_holds =
+        get_hold_cota_by_lock_hash(blake2b_256(&burn_req.lock_script), &cota_id_index_pairs)?.0;
+    if db_holds.is_empty() || db_holds.len() != nfts.len() {
+        return Err(Error::CotaIdAndTokenIndexHasNotHeld);
+    }
+    let mut updates: Vec<(HoldDb, u8, [u8; 20])> = Vec::with_capacity(db_holds.len());
+    for hold_db in db_holds {
+        check_burn(hold_db.cota_id, hold_db.token_index, hold_db.state)?;
+        updates.push((
+            hold_db,
+            hold_db.state | STATE_BURNED,
+            hold_db.characteristic,
+        ));
+    }
+    let batch = generate_update_batch(&updates);
+    let mut burn_smts = update_smt_batches(
+        &burn_req.lock_script,
+        vec![batch],
+        BURN_ACTION,
+        burn_req.dry_run,
+    )
+    .await?;
+    Ok(burn_smts.remove(0))
+}
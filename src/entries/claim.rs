@@ -1,181 +1,333 @@
-use crate::ckb::indexer::get_cota_smt_root;
-use crate::ckb::rpc::get_withdraw_info;
+use crate::business::estimate::split_batch;
+use crate::ckb::rpc::{get_withdraw_info, WithdrawRawTx};
 use crate::entries::helper::{
-    generate_claim_key, generate_claim_value, generate_hold_key, generate_hold_value, with_lock,
+    generate_claim_key, generate_claim_value, generate_hold_key, generate_hold_value,
 };
-use crate::entries::smt::{generate_history_smt, init_smt};
+use crate::entries::policy::check_claim;
+use crate::entries::smt::apply_smt_batches;
 use crate::entries::witness::parse_witness_withdraw_proof;
 use crate::models::claim::is_exist_in_claim;
-use crate::models::withdrawal::nft::{get_withdrawal_cota_by_lock_hash, WithdrawDb};
-use crate::request::claim::ClaimReq;
-use crate::smt::transaction::store_transaction::StoreTransaction;
-use crate::smt::RootSaver;
+use crate::models::scripts::get_script_id_by_lock_script;
+use crate::models::withdrawal::nft::{
+    get_unclaimed_withdrawals_by_script_id, get_withdrawal_cota_by_lock_hash, ReceivedWithdrawDb,
+    WithdrawDb,
+};
+use crate::request::claim::{ClaimAllReq, ClaimReq};
 use crate::utils::error::Error;
-use crate::ROCKS_DB;
 use cota_smt::common::*;
 use cota_smt::molecule::prelude::*;
 use cota_smt::smt::{blake2b_256, H256};
 use cota_smt::transfer::{ClaimCotaNFTV2Entries, ClaimCotaNFTV2EntriesBuilder};
-use log::error;
+use std::collections::HashMap;
+
+// The entry bytes of a claim and its SMT leaves, which are the hold and claim leaves of the
+// receiver and the withdrawal leaf of the sender proved by the withdrawal proof
+const CLAIM_ENTRY_SIZE: usize = 160;
+const CLAIM_LEAF_COUNT: usize = 3;
+// The withdrawal transaction and its block proof which every claim batch carries
+pub(crate) const CLAIM_BASE_SIZE: usize = 4_000;
+// The NFTs claimed by one generate_claim_all_cota_smt call, the rest are left to the next call
+const CLAIM_ALL_LIMIT: i64 = 500;
+
+// The claimed NFTs withdrawn in the same transaction, which share the withdrawal proof of it.
+// The claim entries only carry the withdrawal proof of one transaction, so every batch is
+// claimed by a transaction of its own.
+pub(crate) struct ClaimBatch {
+    pub withdrawal_lock_hash: [u8; 32],
+    // The withdrawals with the indexes of their claims in the request
+    pub withdrawals:          Vec<(usize, WithdrawDb)>,
+}
+
+impl ClaimBatch {
+    pub fn cota_id_index_pairs(&self) -> Vec<([u8; 20], [u8; 4])> {
+        self.withdrawals
+            .iter()
+            .map(|(_, withdrawal)| (withdrawal.cota_id, withdrawal.token_index))
+            .collect()
+    }
+
+    // A batch which does not fit in one claim transaction is split into several ones, and every
+    // one of them carries the withdrawal proof of its own NFTs
+    pub fn split(self) -> Result<Vec<ClaimBatch>, Error> {
+        let items = vec![(CLAIM_ENTRY_SIZE, CLAIM_LEAF_COUNT); self.withdrawals.len()];
+        let withdrawal_lock_hash = self.withdrawal_lock_hash;
+        let mut withdrawals = self.withdrawals.into_iter();
+        let batches = split_batch(&items, (CLAIM_BASE_SIZE, 0))?
+            .into_iter()
+            .map(|batch_len| ClaimBatch {
+                withdrawal_lock_hash,
+                withdrawals: withdrawals.by_ref().take(batch_len).collect(),
+            })
+            .collect();
+        Ok(batches)
+    }
+
+    pub async fn withdraw_info(&self) -> Result<(WithdrawRawTx, Bytes), Error> {
+        let (_, withdrawal) = &self.withdrawals[0];
+        let withdraw_info = get_withdraw_info(
+            withdrawal.block_number,
+            self.withdrawal_lock_hash,
+            withdrawal.tx_hash,
+        )
+        .await?;
+        let withdraw_proof = parse_witness_withdraw_proof(
+            withdraw_info.witnesses.clone(),
+            &self.cota_id_index_pairs(),
+            withdraw_info.block_number,
+        )?;
+        Ok((withdraw_info, withdraw_proof))
+    }
+}
+
+// The claims are (withdrawal_lock_hash, cota_id, token_index), and the batches keep the order
+// in which their withdrawal transactions first appear in the claims. The batches too large for
+// one claim transaction are split.
+pub(crate) fn group_claim_withdrawals(
+    claim_lock_script: &[u8],
+    claims: &[([u8; 32], [u8; 20], [u8; 4])],
+) -> Result<Vec<ClaimBatch>, Error> {
+    let mut sender_claims: Vec<([u8; 32], Vec<usize>)> = Vec::new();
+    for (index, (withdrawal_lock_hash, ..)) in claims.iter().enumerate() {
+        match sender_claims
+            .iter_mut()
+            .find(|(lock_hash, _)| lock_hash == withdrawal_lock_hash)
+        {
+            Some((_, indexes)) => indexes.push(index),
+            None => sender_claims.push((*withdrawal_lock_hash, vec![index])),
+        }
+    }
+
+    let claim_lock_hash = blake2b_256(claim_lock_script);
+    let mut batches: Vec<ClaimBatch> = Vec::new();
+    for (withdrawal_lock_hash, indexes) in sender_claims {
+        let cota_id_index_pairs: Vec<([u8; 20], [u8; 4])> = indexes
+            .iter()
+            .map(|index| (claims[*index].1, claims[*index].2))
+            .collect();
+        let sender_withdrawals =
+            get_withdrawal_cota_by_lock_hash(withdrawal_lock_hash, &cota_id_index_pairs)?.0;
+        for (index, (cota_id, token_index)) in indexes.into_iter().zip(cota_id_index_pairs) {
+            let withdrawal = sender_withdrawals
+                .iter()
+                .find(|withdrawal| {
+                    withdrawal.cota_id == cota_id && withdrawal.token_index == token_index
+                })
+                .cloned()
+                .ok_or(Error::CotaIdAndTokenIndexHasNotWithdrawn)?;
+            if withdrawal.receiver_lock_script != claim_lock_script {
+                return Err(Error::CotaIdAndTokenIndexHasNotWithdrawn);
+            }
+            let is_claimed = is_exist_in_claim(
+                claim_lock_hash,
+                withdrawal.cota_id,
+                withdrawal.token_index,
+                withdrawal.out_point,
+            );
+            check_claim(
+                withdrawal.cota_id,
+                withdrawal.token_index,
+                withdrawal.configure,
+                is_claimed,
+            )?;
+            push_claim_withdrawal(&mut batches, withdrawal_lock_hash, index, withdrawal);
+        }
+    }
+    split_claim_batches(batches)
+}
+
+fn split_claim_batches(batches: Vec<ClaimBatch>) -> Result<Vec<ClaimBatch>, Error> {
+    let mut split_batches = Vec::with_capacity(batches.len());
+    for batch in batches {
+        split_batches.extend(batch.split()?);
+    }
+    Ok(split_batches)
+}
+
+// The withdrawal joins the batch of its withdrawal transaction, or starts a new one
+fn push_claim_withdrawal(
+    batches: &mut Vec<ClaimBatch>,
+    withdrawal_lock_hash: [u8; 32],
+    index: usize,
+    withdrawal: WithdrawDb,
+) {
+    match batches.iter_mut().find(|batch| {
+        batch.withdrawal_lock_hash == withdrawal_lock_hash
+            && batch.withdrawals[0].1.tx_hash == withdrawal.tx_hash
+    }) {
+        Some(batch) => batch.withdrawals.push((index, withdrawal)),
+        None => batches.push(ClaimBatch {
+            withdrawal_lock_hash,
+            withdrawals: vec![(index, withdrawal)],
+        }),
+    }
+}
 
 pub async fn generate_claim_smt(
     claim_req: ClaimReq,
-) -> Result<(H256, ClaimCotaNFTV2Entries, H256), Error> {
+) -> Result<Vec<(H256, ClaimCotaNFTV2Entries, H256)>, Error> {
     let claims = claim_req.claims;
     let claim_lock_script = claim_req.lock_script;
-    let claims_len = claims.len();
-    if claims_len == 0 {
+    if claims.is_empty() {
         return Err(Error::RequestParamNotFound("claims".to_string()));
     }
-    let cota_id_index_pairs: Vec<([u8; 20], [u8; 4])> = claims
+    let claims: Vec<([u8; 32], [u8; 20], [u8; 4])> = claims
         .iter()
-        .map(|claim| (claim.cota_id, claim.token_index))
+        .map(|claim| {
+            (
+                blake2b_256(&claim.withdrawal_lock_script),
+                claim.cota_id,
+                claim.token_index,
+            )
+        })
         .collect();
-    let withdrawal_lock_hash = blake2b_256(&claim_req.withdrawal_lock_script);
-    let sender_withdrawals =
-        get_withdrawal_cota_by_lock_hash(withdrawal_lock_hash, &cota_id_index_pairs)?.0;
-    if sender_withdrawals.is_empty() || sender_withdrawals.len() != claims_len {
-        return Err(Error::CotaIdAndTokenIndexHasNotWithdrawn);
-    }
-    let is_receiver = sender_withdrawals
-        .iter()
-        .any(|withdrawal| &withdrawal.receiver_lock_script == &claim_lock_script);
-    if !is_receiver {
-        return Err(Error::CotaIdAndTokenIndexHasNotWithdrawn);
+    let batches = group_claim_withdrawals(&claim_lock_script, &claims)?;
+    generate_claim_batches_smt(&claim_lock_script, batches, claim_req.dry_run).await
+}
+
+// Claims the unclaimed NFTs withdrawn to the lock script in the order of the withdrawals, except
+// the ones whose configure forbids claiming, and at most CLAIM_ALL_LIMIT NFTs are claimed by one
+// call. The withdrawals are loaded with everything the claims need, so no more queries are made
+// per NFT.
+pub async fn generate_claim_all_smt(
+    claim_all_req: ClaimAllReq,
+) -> Result<Vec<(H256, ClaimCotaNFTV2Entries, H256)>, Error> {
+    let claim_lock_script = claim_all_req.lock_script;
+    let script_id = match get_script_id_by_lock_script(&claim_lock_script)? {
+        Some(script_id) => script_id,
+        None => return Ok(vec![]),
+    };
+    let withdrawals =
+        get_unclaimed_withdrawals_by_script_id(script_id, &claim_lock_script, CLAIM_ALL_LIMIT)?;
+    // The SMT of the sender keeps the latest withdrawal of an NFT
+    let mut claims: Vec<ReceivedWithdrawDb> = Vec::with_capacity(withdrawals.len());
+    let mut claim_indexes: HashMap<([u8; 32], [u8; 20], [u8; 4]), usize> = HashMap::new();
+    for received in withdrawals {
+        let claim = (
+            received.lock_hash,
+            received.withdrawal.cota_id,
+            received.withdrawal.token_index,
+        );
+        match claim_indexes.get(&claim) {
+            Some(index) => claims[*index] = received,
+            None => {
+                claim_indexes.insert(claim, claims.len());
+                claims.push(received);
+            }
+        }
     }
-    let claim_lock_hash = blake2b_256(&claim_lock_script);
-    let is_claimed = sender_withdrawals.iter().any(|withdrawal| {
-        is_exist_in_claim(
-            claim_lock_hash,
-            withdrawal.cota_id,
-            withdrawal.token_index,
-            withdrawal.out_point,
-        )
-    });
-    if is_claimed {
-        return Err(Error::CotaIdAndTokenIndexHasNotWithdrawn);
+    if claims.is_empty() {
+        return Ok(vec![]);
     }
-    let withdrawal_block_number = sender_withdrawals.first().unwrap().block_number;
-    let withdrawal_tx_hash = sender_withdrawals.first().unwrap().tx_hash;
-    if sender_withdrawals[1..]
-        .iter()
-        .any(|withdrawal| withdrawal.block_number != withdrawal_block_number)
-    {
-        return Err(Error::WithdrawCotaNFTsNotInOneTx);
+    let mut batches: Vec<ClaimBatch> = Vec::new();
+    for (index, received) in claims.into_iter().enumerate() {
+        push_claim_withdrawal(&mut batches, received.lock_hash, index, received.withdrawal);
     }
+    let batches = split_claim_batches(batches)?;
+    generate_claim_batches_smt(&claim_lock_script, batches, claim_all_req.dry_run).await
+}
 
-    let mut hold_keys: Vec<CotaNFTId> = Vec::new();
-    let mut hold_values: Vec<CotaNFTInfo> = Vec::new();
-    let mut claim_keys: Vec<ClaimCotaNFTKey> = Vec::new();
-    let mut key_vec: Vec<(H256, u8)> = Vec::new();
-    let mut claim_values: Vec<Byte32> = Vec::new();
-    let mut claim_update_leaves: Vec<(H256, H256)> = Vec::with_capacity(claims_len * 2);
-    let mut previous_leaves: Vec<(H256, H256)> = Vec::with_capacity(claims_len * 2);
-
-    for withdrawal in sender_withdrawals {
-        let WithdrawDb {
-            cota_id,
-            token_index,
-            characteristic,
-            state,
-            configure,
-            out_point,
-            version,
-            ..
-        } = withdrawal;
-
-        let (hold_key, key) = generate_hold_key(cota_id, token_index);
-        let (hold_value, value) = generate_hold_value(configure, state, characteristic);
-        hold_keys.push(hold_key);
-        hold_values.push(hold_value);
-        claim_update_leaves.push((key, value));
-        previous_leaves.push((key, H256::zero()));
-
-        let (claim_key, key) = generate_claim_key(cota_id, token_index, out_point);
-        claim_keys.push(claim_key);
-        key_vec.push((key, version));
+async fn generate_claim_batches_smt(
+    claim_lock_script: &[u8],
+    batches: Vec<ClaimBatch>,
+    dry_run: bool,
+) -> Result<Vec<(H256, ClaimCotaNFTV2Entries, H256)>, Error> {
+    let mut batch_values = Vec::with_capacity(batches.len());
+    let mut batch_leaves = Vec::with_capacity(batches.len());
+    for batch in batches.iter() {
+        let claims_len = batch.withdrawals.len();
+        let mut hold_keys: Vec<CotaNFTId> = Vec::new();
+        let mut hold_values: Vec<CotaNFTInfo> = Vec::new();
+        let mut claim_keys: Vec<ClaimCotaNFTKey> = Vec::new();
+        let mut key_vec: Vec<(H256, u8)> = Vec::new();
+        let mut claim_values: Vec<Byte32> = Vec::new();
+        let mut claim_update_leaves: Vec<(H256, H256)> = Vec::with_capacity(claims_len * 2);
+        let mut previous_leaves: Vec<(H256, H256)> = Vec::with_capacity(claims_len * 2);
+
+        for (_, withdrawal) in batch.withdrawals.iter() {
+            let WithdrawDb {
+                cota_id,
+                token_index,
+                characteristic,
+                state,
+                configure,
+                out_point,
+                version,
+                ..
+            } = *withdrawal;
+
+            let (hold_key, key) = generate_hold_key(cota_id, token_index);
+            let (hold_value, value) = generate_hold_value(configure, state, characteristic);
+            hold_keys.push(hold_key);
+            hold_values.push(hold_value);
+            claim_update_leaves.push((key, value));
+            previous_leaves.push((key, H256::zero()));
+
+            let (claim_key, key) = generate_claim_key(cota_id, token_index, out_point);
+            claim_keys.push(claim_key);
+            key_vec.push((key, version));
+        }
+
+        for (key, version) in key_vec {
+            let (claim_value, value) = generate_claim_value(version);
+            claim_values.push(claim_value);
+            claim_update_leaves.push((key, value));
+            previous_leaves.push((key, H256::zero()));
+        }
+
+        // The withdrawal transactions are fetched before the SMT is updated, so no pending
+        // update is left for the batches which cannot be claimed
+        let withdraw_info = batch.withdraw_info().await?;
+        batch_values.push((
+            hold_keys,
+            hold_values,
+            claim_keys,
+            claim_values,
+            withdraw_info,
+        ));
+        batch_leaves.push((claim_update_leaves, previous_leaves));
     }
 
-    for (key, version) in key_vec {
-        let (claim_value, value) = generate_claim_value(version);
-        claim_values.push(claim_value);
-        claim_update_leaves.push((key, value));
-        previous_leaves.push((key, H256::zero()));
+    let roots_and_proofs =
+        apply_smt_batches(claim_lock_script, &batch_leaves, dry_run, "Claim").await?;
+
+    let mut claim_smts = Vec::with_capacity(batches.len());
+    for (
+        (hold_keys, hold_values, claim_keys, claim_values, (withdraw_info, withdraw_proof)),
+        (claim_root, claim_proof),
+    ) in batch_values.into_iter().zip(roots_and_proofs)
+    {
+        let mut action_vec: Vec<u8> = Vec::new();
+        action_vec.extend("Claim ".as_bytes());
+        action_vec.extend(hold_keys.len().to_string().as_bytes());
+        action_vec.extend(" NFTs".as_bytes());
+        let action_bytes = BytesBuilder::default()
+            .set(action_vec.iter().map(|v| Byte::from(*v)).collect())
+            .build();
+
+        let claim_entries = ClaimCotaNFTV2EntriesBuilder::default()
+            .hold_keys(HoldCotaNFTKeyVecBuilder::default().set(hold_keys).build())
+            .hold_values(
+                HoldCotaNFTValueVecBuilder::default()
+                    .set(hold_values)
+                    .build(),
+            )
+            .claim_keys(ClaimCotaNFTKeyVecBuilder::default().set(claim_keys).build())
+            .claim_values(
+                ClaimCotaNFTValueVecBuilder::default()
+                    .set(claim_values)
+                    .build(),
+            )
+            .proof(claim_proof)
+            .action(action_bytes)
+            .withdrawal_proof(withdraw_proof)
+            .leaf_keys(Byte32Vec::default())
+            .leaf_values(Byte32Vec::default())
+            .raw_tx(withdraw_info.raw_tx)
+            .output_index(withdraw_info.output_index)
+            .tx_proof(withdraw_info.tx_proof)
+            .build();
+        claim_smts.push((claim_root, claim_entries, withdraw_info.block_hash));
     }
 
-    let claim_smt_root = get_cota_smt_root(&claim_lock_script).await?;
-
-    let claim_lock_hash = blake2b_256(&claim_lock_script);
-    let transaction = &StoreTransaction::new(ROCKS_DB.transaction());
-    let mut claim_smt = init_smt(transaction, claim_lock_hash)?;
-
-    // Add lock to claim smt
-    with_lock(claim_lock_hash, || {
-        generate_history_smt(&mut claim_smt, claim_lock_hash, claim_smt_root)?;
-        claim_smt
-            .update_all(claim_update_leaves.clone())
-            .map_err(|e| Error::SMTInvalid(e.to_string()))?;
-        claim_smt.save_root_and_leaves(previous_leaves.clone())?;
-        claim_smt.commit()
-    })?;
-
-    let leaf_keys: Vec<H256> = claim_update_leaves.iter().map(|leave| leave.0).collect();
-    let claim_merkle_proof = claim_smt.merkle_proof(leaf_keys.clone()).map_err(|e| {
-        error!("Claim SMT proof error: {:?}", e.to_string());
-        Error::SMTProofInvalid("Claim".to_string())
-    })?;
-    let claim_merkle_proof_compiled = claim_merkle_proof.compile(leaf_keys).map_err(|e| {
-        error!("Claim SMT proof error: {:?}", e.to_string());
-        Error::SMTProofInvalid("Claim".to_string())
-    })?;
-
-    let merkel_proof_vec: Vec<u8> = claim_merkle_proof_compiled.into();
-    let claim_proof = BytesBuilder::default()
-        .extend(merkel_proof_vec.iter().map(|v| Byte::from(*v)))
-        .build();
-
-    let withdraw_info = get_withdraw_info(
-        withdrawal_block_number,
-        withdrawal_lock_hash,
-        withdrawal_tx_hash,
-    )
-    .await?;
-    let withdraw_proof = parse_witness_withdraw_proof(
-        withdraw_info.witnesses,
-        &cota_id_index_pairs,
-        withdraw_info.block_number,
-    )?;
-
-    let mut action_vec: Vec<u8> = Vec::new();
-    action_vec.extend("Claim ".as_bytes());
-    action_vec.extend(claims_len.to_string().as_bytes());
-    action_vec.extend(" NFTs".as_bytes());
-    let action_bytes = BytesBuilder::default()
-        .set(action_vec.iter().map(|v| Byte::from(*v)).collect())
-        .build();
-
-    let claim_entries = ClaimCotaNFTV2EntriesBuilder::default()
-        .hold_keys(HoldCotaNFTKeyVecBuilder::default().set(hold_keys).build())
-        .hold_values(
-            HoldCotaNFTValueVecBuilder::default()
-                .set(hold_values)
-                .build(),
-        )
-        .claim_keys(ClaimCotaNFTKeyVecBuilder::default().set(claim_keys).build())
-        .claim_values(
-            ClaimCotaNFTValueVecBuilder::default()
-                .set(claim_values)
-                .build(),
-        )
-        .proof(claim_proof)
-        .action(action_bytes)
-        .withdrawal_proof(withdraw_proof)
-        .leaf_keys(Byte32Vec::default())
-        .leaf_values(Byte32Vec::default())
-        .raw_tx(withdraw_info.raw_tx)
-        .output_index(withdraw_info.output_index)
-        .tx_proof(withdraw_info.tx_proof)
-        .build();
-
-    Ok((*claim_smt.root(), claim_entries, withdraw_info.block_hash))
+    Ok(claim_smts)
 }
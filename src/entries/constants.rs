@@ -4,9 +4,24 @@ pub const HOLD_NFT_SMT_TYPE: [u8; 2] = [129u8, 1u8]; // 0x8101
 pub const WITHDRAWAL_NFT_SMT_TYPE: [u8; 2] = [129u8, 2u8]; // 0x8102
 pub const CLAIM_NFT_SMT_TYPE: [u8; 2] = [129u8, 3u8]; // 0x8103
 
+// configure bits, and the behavior of the NFTs is forbidden when its bit is set
+pub const CONFIGURE_CLAIM_DISABLED: u8 = 0x01;
+pub const CONFIGURE_LOCK_DISABLED: u8 = 0x02;
+pub const CONFIGURE_UPDATE_DISABLED: u8 = 0x04;
+pub const CONFIGURE_TRANSFER_DISABLED: u8 = 0x08;
+pub const CONFIGURE_RESERVED_BITS: u8 = 0xF0;
+
+// state bits
+pub const STATE_LOCKED: u8 = 0x01;
+pub const STATE_CLAIMED: u8 = 0x02;
+pub const STATE_BURNED: u8 = 0x04;
+
 // block height
 pub const BLOCK_HEIGHT_VALUE_PADDING_MAINNET: u64 = 7233113;
 pub const BLOCK_HEIGHT_VALUE_PADDING_TESTNET: u64 = 5476282;
 
 // extension action
 pub const EXT_ACTION_ADD: u8 = 0xF0;
+
+// The registry SMT is stored in rocksdb with the zero lock hash which no lock script hashes to
+pub const REGISTRY_SMT_LOCK_HASH: [u8; 32] = [0u8; 32];
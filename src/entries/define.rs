@@ -1,7 +1,11 @@
 use crate::ckb::indexer::get_cota_smt_root;
-use crate::entries::helper::{generate_define_key, generate_define_value, with_lock};
+use crate::entries::helper::{
+    generate_cota_id, generate_define_key, generate_define_value, with_lock,
+};
+use crate::entries::policy::check_define_configure;
 use crate::entries::smt::{generate_history_smt, init_smt};
 use crate::models::block::get_syncer_tip_block_number;
+use crate::models::define::get_define_cota_by_cota_id;
 use crate::request::define::DefineReq;
 use crate::smt::transaction::store_transaction::StoreTransaction;
 use crate::smt::RootSaver;
@@ -23,8 +27,19 @@ pub async fn generate_define_smt(
         total,
         issued,
         configure,
+        first_input_out_point,
         ..
     } = define_req;
+    check_define_configure(configure)?;
+    // The aggregator generates one define entry for a transaction, so its index is zero
+    if let Some(out_point) = first_input_out_point {
+        if generate_cota_id(out_point, 0) != cota_id {
+            return Err(Error::CotaIdNotDerived(hex::encode(cota_id)));
+        }
+    }
+    if get_define_cota_by_cota_id(cota_id)?.is_some() {
+        return Err(Error::CotaIdHasDefined(hex::encode(cota_id)));
+    }
     let (define_key, key) = generate_define_key(cota_id);
     let block_number = get_syncer_tip_block_number()?;
     let (define_value, value) = generate_define_value(total, issued, configure, block_number);
@@ -38,8 +53,18 @@ pub async fn generate_define_smt(
     // Add lock to smt
     with_lock(lock_hash, || {
         generate_history_smt(&mut smt, lock_hash, smt_root)?;
+        // The define of a pending transaction has not been synced to mysql yet
+        let define_leaf = smt
+            .get(&key)
+            .map_err(|e| Error::SMTInvalid(e.to_string()))?;
+        if define_leaf != H256::zero() {
+            return Err(Error::CotaIdHasDefined(hex::encode(cota_id)));
+        }
         smt.update(key, value)
             .map_err(|e| Error::SMTInvalid(e.to_string()))?;
+        if define_req.dry_run {
+            return Ok(());
+        }
         smt.save_root_and_leaves(previous_leaves.clone())?;
         smt.commit()
     })?;
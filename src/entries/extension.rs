@@ -29,6 +29,7 @@ pub async fn generate_ext_subkey_smt(
         lock_script,
         subkeys,
         ext_action,
+        dry_run,
     } = ext_subkey_req;
     let subkey_len = subkeys.len();
     let mut update_leaves: Vec<(H256, H256)> = Vec::with_capacity(subkey_len);
@@ -68,6 +69,9 @@ pub async fn generate_ext_subkey_smt(
         generate_history_smt(&mut smt, lock_hash, smt_root)?;
         smt.update_all(update_leaves.clone())
             .map_err(|e| Error::SMTInvalid(e.to_string()))?;
+        if dry_run {
+            return Ok(());
+        }
         smt.save_root_and_leaves(previous_leaves.clone())?;
         smt.commit()
     })?;
@@ -161,6 +165,9 @@ pub async fn generate_ext_social_smt(
         generate_history_smt(&mut smt, lock_hash, smt_root)?;
         smt.update_all(update_leaves.clone())
             .map_err(|e| Error::SMTInvalid(e.to_string()))?;
+        if ext_social_req.dry_run {
+            return Ok(());
+        }
         smt.save_root_and_leaves(previous_leaves.clone())?;
         smt.commit()
     })?;
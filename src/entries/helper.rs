@@ -2,7 +2,7 @@ use crate::entries::constants::{
     BLOCK_HEIGHT_VALUE_PADDING_MAINNET, BLOCK_HEIGHT_VALUE_PADDING_TESTNET, CLAIM_NFT_SMT_TYPE,
     DEFINE_NFT_SMT_TYPE, HOLD_NFT_SMT_TYPE, WITHDRAWAL_NFT_SMT_TYPE,
 };
-use crate::entries::SMT_LOCK;
+use crate::entries::{SHUTTING_DOWN, SMT_LOCK};
 use crate::models::extension::social::SocialRecoveryDb;
 use crate::request::extension::{ExtSocialReq, ExtSubkey};
 use crate::utils::error::Error;
@@ -15,7 +15,14 @@ use joyid_smt::joyid::{
 };
 use serde_json::from_str;
 use std::env;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// The registry leaf key is the lock hash and the leaf value is 0xFF...FF once registered
+pub fn generate_registry_leaf(lock_hash: [u8; 32]) -> (H256, H256) {
+    (H256::from(lock_hash), H256::from([255u8; 32]))
+}
 
 pub fn generate_define_key(cota_id: [u8; 20]) -> (DefineCotaNFTId, H256) {
     let cota_id = CotaId::from_slice(&cota_id).unwrap();
@@ -52,6 +59,18 @@ pub fn generate_define_value(
     (define_value, value)
 }
 
+// The cota_id is the first 20 bytes of blake2b(first_input | define_index) where the first input
+// is the molecule CellInput with zero since, the same as the CoTA type script derives it
+pub fn generate_cota_id(first_input_out_point: [u8; 36], define_index: u8) -> [u8; 20] {
+    let mut preimage = Vec::with_capacity(45);
+    preimage.extend(&0u64.to_le_bytes());
+    preimage.extend(&first_input_out_point);
+    preimage.push(define_index);
+    let mut cota_id = [0u8; 20];
+    cota_id.copy_from_slice(&blake2b_256(&preimage)[0..20]);
+    cota_id
+}
+
 pub fn generate_hold_key(cota_id: [u8; 20], token_index: [u8; 4]) -> (CotaNFTId, H256) {
     let hold_key = CotaNFTIdBuilder::default()
         .cota_id(CotaId::from_slice(&cota_id).unwrap())
@@ -271,7 +290,13 @@ where
     let (lock, cond) = &*Arc::clone(&SMT_LOCK);
     {
         let mut set = lock.lock();
-        while !set.insert(lock_hash) {
+        loop {
+            if SHUTTING_DOWN.load(Ordering::SeqCst) {
+                return Err(Error::ShuttingDown);
+            }
+            if set.insert(lock_hash) {
+                break;
+            }
             cond.wait(&mut set);
         }
     }
@@ -289,6 +314,23 @@ where
     Ok(())
 }
 
+// Reject the SMT operations which have not acquired the lock yet and wait for the in-flight
+// ones to finish, the lock hashes still held after the timeout are returned
+pub fn wait_for_smt_operations(timeout: Duration) -> Vec<[u8; 32]> {
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+    let (lock, cond) = &*Arc::clone(&SMT_LOCK);
+    let deadline = Instant::now() + timeout;
+    let mut set = lock.lock();
+    // Wake the operations waiting for the same lock hash so they can be rejected
+    cond.notify_all();
+    while !set.is_empty() {
+        if cond.wait_until(&mut set, deadline).timed_out() {
+            break;
+        }
+    }
+    set.iter().cloned().collect()
+}
+
 pub fn get_value_padding_block_height() -> u64 {
     let is_mainnet: bool = match env::var("IS_MAINNET") {
         Ok(mainnet) => from_str::<bool>(&mainnet).unwrap(),
@@ -0,0 +1,166 @@
+use crate::entries::define::generate_define_smt;
+use crate::request::define::DefineReq;
+use crate::response::witness::info::{ClassInfo, InfoData, IssuerInfo, Metadata};
+use crate::utils::error::Error;
+use cota_smt::define::DefineCotaNFTEntries;
+use cota_smt::smt::H256;
+use serde::Serialize;
+
+// The CoTA metadata standard puts the JSON of the issuer or class information into the
+// output_type of the witness, and the metadata describes the CoTA cell of the first output
+const METADATA_ID: &str = "CTMeta";
+const METADATA_VER: &str = "1.0";
+const METADATA_TARGET: &str = "output#0";
+const INFO_VERSION: &str = "0";
+const ISSUER_TYPE: &str = "issuer";
+const CLASS_TYPE: &str = "cota";
+
+pub fn generate_issuer_metadata(issuer: IssuerInfo) -> Result<Vec<u8>, Error> {
+    check_info_version(&issuer.version)?;
+    check_required("name", &issuer.name)?;
+    check_optional_url("avatar", &issuer.avatar)?;
+    generate_metadata(ISSUER_TYPE, issuer)
+}
+
+// The class metadata is attached to the define transaction, so the define entries are generated
+// only after the metadata is valid
+pub async fn generate_class_metadata(
+    define_req: DefineReq,
+    class: ClassInfo,
+) -> Result<((H256, DefineCotaNFTEntries), Vec<u8>), Error> {
+    check_class_info(&class, define_req.cota_id)?;
+    let output_type = generate_metadata(CLASS_TYPE, class)?;
+    let define_smt = generate_define_smt(define_req).await?;
+    Ok((define_smt, output_type))
+}
+
+fn check_class_info(class: &ClassInfo, cota_id: [u8; 20]) -> Result<(), Error> {
+    check_info_version(&class.version)?;
+    check_required("name", &class.name)?;
+    check_url("image", &class.image)?;
+    if class.cota_id.trim_start_matches("0x") != hex::encode(cota_id) {
+        return Err(Error::MetadataFieldInvalid("cota_id".to_owned()));
+    }
+    check_optional_url("audio", &class.audio)?;
+    check_optional_url("video", &class.video)?;
+    check_optional_url("model", &class.model)?;
+    for audio in class.audios.iter().flatten() {
+        check_optional_url("audios.url", &audio.url)?;
+    }
+    Ok(())
+}
+
+fn generate_metadata<T: Serialize>(type_: &str, data: T) -> Result<Vec<u8>, Error> {
+    let metadata = Metadata {
+        id:       METADATA_ID.to_owned(),
+        ver:      METADATA_VER.to_owned(),
+        metadata: InfoData {
+            target: METADATA_TARGET.to_owned(),
+            type_: type_.to_owned(),
+            data,
+        },
+    };
+    serde_json::to_vec(&metadata).map_err(|e| Error::Other(format!("Metadata error: {}", e)))
+}
+
+fn check_info_version(version: &str) -> Result<(), Error> {
+    if version != INFO_VERSION {
+        return Err(Error::MetadataFieldInvalid("version".to_owned()));
+    }
+    Ok(())
+}
+
+fn check_required(field: &str, value: &str) -> Result<(), Error> {
+    if value.trim().is_empty() {
+        return Err(Error::MetadataFieldInvalid(field.to_owned()));
+    }
+    Ok(())
+}
+
+// The media are referred by the URLs of any scheme, e.g. https:// and ipfs://
+fn check_url(field: &str, url: &str) -> Result<(), Error> {
+    match url.split_once("://") {
+        Some((scheme, path)) if !scheme.is_empty() && !path.is_empty() => Ok(()),
+        _ => Err(Error::MetadataFieldInvalid(field.to_owned())),
+    }
+}
+
+fn check_optional_url(field: &str, url: &Option<String>) -> Result<(), Error> {
+    match url {
+        Some(url) => check_url(field, url),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COTA_ID: [u8; 20] = [0x0a; 20];
+
+    fn class_info() -> ClassInfo {
+        ClassInfo {
+            version:        "0".to_owned(),
+            name:           "First Step".to_owned(),
+            image:          "https://i.loli.net/2021/04/29/IigbpOWP8fw9qDn.png".to_owned(),
+            cota_id:        format!("0x{}", hex::encode(COTA_ID)),
+            symbol:         None,
+            description:    Some("Just the first step".to_owned()),
+            audio:          None,
+            audios:         None,
+            video:          None,
+            model:          None,
+            characteristic: None,
+            properties:     None,
+        }
+    }
+
+    #[test]
+    fn test_generate_issuer_metadata() {
+        let issuer = IssuerInfo {
+            version:     "0".to_owned(),
+            name:        "Rebase".to_owned(),
+            avatar:      Some("ipfs://QmTkGnXw2gX9xD1oWKMjgFuUgkUJfgRwm8ZBmTz7UmJQd6".to_owned()),
+            description: None,
+        };
+        let output_type = generate_issuer_metadata(issuer.clone()).unwrap();
+        let metadata = serde_json::from_slice::<Metadata<IssuerInfo>>(&output_type).unwrap();
+        assert_eq!(metadata.id, METADATA_ID);
+        assert_eq!(metadata.metadata.type_, ISSUER_TYPE);
+        assert_eq!(metadata.metadata.data, issuer);
+
+        let issuer = IssuerInfo {
+            avatar: Some("avatar.png".to_owned()),
+            ..issuer
+        };
+        assert_eq!(
+            generate_issuer_metadata(issuer),
+            Err(Error::MetadataFieldInvalid("avatar".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_check_class_info() {
+        assert!(check_class_info(&class_info(), COTA_ID).is_ok());
+        assert_eq!(
+            check_class_info(&class_info(), [0x0b; 20]),
+            Err(Error::MetadataFieldInvalid("cota_id".to_owned()))
+        );
+        let class = ClassInfo {
+            name: " ".to_owned(),
+            ..class_info()
+        };
+        assert_eq!(
+            check_class_info(&class, COTA_ID),
+            Err(Error::MetadataFieldInvalid("name".to_owned()))
+        );
+        let class = ClassInfo {
+            version: "1".to_owned(),
+            ..class_info()
+        };
+        assert_eq!(
+            check_class_info(&class, COTA_ID),
+            Err(Error::MetadataFieldInvalid("version".to_owned()))
+        );
+    }
+}
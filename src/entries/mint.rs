@@ -1,9 +1,9 @@
+use crate::business::estimate::{check_batch, split_batch};
 use crate::ckb::indexer::get_cota_smt_root;
 use crate::entries::helper::{generate_define_key, generate_define_value, with_lock};
 use crate::entries::helper::{generate_withdrawal_key_v1, generate_withdrawal_value_v1};
-use crate::entries::smt::{generate_history_smt, init_smt};
+use crate::entries::smt::{apply_smt_batch, generate_history_smt, init_smt};
 use crate::models::block::get_syncer_tip_block_number;
-use crate::models::define::{get_define_cota_by_lock_hash_and_cota_id, DefineDb};
 use crate::request::mint::{MintReq, MintWithdrawal};
 use crate::smt::transaction::store_transaction::StoreTransaction;
 use crate::smt::RootSaver;
@@ -15,157 +15,279 @@ use cota_smt::common::*;
 use cota_smt::mint::{MintCotaNFTV1Entries, MintCotaNFTV1EntriesBuilder};
 use cota_smt::molecule::prelude::*;
 use cota_smt::smt::{blake2b_256, H256};
-use log::error;
 use molecule::hex_string;
+use std::collections::HashSet;
+use std::convert::TryInto;
+
+// The entry bytes of a mint withdrawal out of its lock script, and the ones of the define
+// entries and the action which every mint batch carries
+const MINT_WITHDRAWAL_SIZE: usize = 128;
+const MINT_BASE_SIZE: usize = 512;
 
 pub async fn generate_mint_smt(mint_req: MintReq) -> Result<(H256, MintCotaNFTV1Entries), Error> {
-    let withdrawals = mint_req.withdrawals;
-    let withdrawals_len = withdrawals.len();
-    if withdrawals_len == 0 {
+    let out_point = mint_req.out_point;
+    let batch_lens = [mint_req.withdrawals.len()];
+    let mut mint_smts =
+        generate_mint_batches_smt(mint_req, &batch_lens, |_, _| Ok(out_point)).await?;
+    Ok(mint_smts.remove(0))
+}
+
+// The lengths of the mint batches which fit in one transaction each
+pub fn mint_batch_lens(withdrawals: &[MintWithdrawal]) -> Result<Vec<usize>, Error> {
+    split_batch(&mint_items(withdrawals), (MINT_BASE_SIZE, 1))
+}
+
+fn mint_items(withdrawals: &[MintWithdrawal]) -> Vec<(usize, usize)> {
+    withdrawals
+        .iter()
+        .map(|withdrawal| (MINT_WITHDRAWAL_SIZE + withdrawal.to_lock_script.len(), 1))
+        .collect()
+}
+
+// The withdrawals are minted in the batches of batch_lens, and every batch is built on top of the
+// previous one with the issued count and the define leaf updated by it. The withdrawal keys contain
+// the out point of the consumed CoTA cell, so the out point of the request is the one of the first
+// batch, and next_out_point returns the one of the CoTA cell created by the transaction of a batch
+// from its SMT root and entries.
+pub async fn generate_mint_batches_smt<F>(
+    mint_req: MintReq,
+    batch_lens: &[usize],
+    mut next_out_point: F,
+) -> Result<Vec<(H256, MintCotaNFTV1Entries)>, Error>
+where
+    F: FnMut(H256, &MintCotaNFTV1Entries) -> Result<[u8; 24], Error>,
+{
+    let MintReq {
+        lock_script,
+        cota_id,
+        out_point,
+        withdrawals,
+        dry_run,
+    } = mint_req;
+    if withdrawals.is_empty() {
         return Err(Error::RequestParamNotFound("withdrawals".to_string()));
     }
-    let first_withdrawal = withdrawals.first().unwrap().clone();
-    let db_define = get_define_cota_by_lock_hash_and_cota_id(
-        blake2b_256(&mint_req.lock_script),
-        mint_req.cota_id,
-    )?;
-    if db_define.is_none() {
-        let cota_id_hex = hex::encode(mint_req.cota_id);
-        return Err(Error::CotaIdHasNotDefined(cota_id_hex));
+    // Every batch must fit in one transaction, and generate_mint_smt has only one batch
+    let mut items = mint_items(&withdrawals).into_iter();
+    for batch_len in batch_lens.iter() {
+        let batch_items: Vec<(usize, usize)> = items.by_ref().take(*batch_len).collect();
+        check_batch(&batch_items, (MINT_BASE_SIZE, 1))?;
     }
-    let mut define_keys: Vec<DefineCotaNFTId> = Vec::new();
-    let mut define_old_values: Vec<DefineCotaNFTValue> = Vec::new();
-    let mut define_new_values: Vec<DefineCotaNFTValue> = Vec::new();
-    let mut withdrawal_keys: Vec<WithdrawalCotaNFTKeyV1> = Vec::new();
-    let mut withdrawal_values: Vec<WithdrawalCotaNFTValueV1> = Vec::new();
-
-    let mut update_leaves: Vec<(H256, H256)> = Vec::with_capacity(withdrawals_len + 1);
-    let mut previous_leaves: Vec<(H256, H256)> = Vec::with_capacity(withdrawals_len + 1);
-    let DefineDb {
-        cota_id,
-        total,
-        issued,
-        configure,
-        block_number,
-    } = db_define.unwrap();
-    let (define_key, key) = generate_define_key(cota_id);
-    define_keys.push(define_key);
-
-    let (define_old_value, old_value) = generate_define_value(
-        total.to_be_bytes(),
-        issued.to_be_bytes(),
-        configure,
-        block_number,
-    );
-    define_old_values.push(define_old_value);
-
-    let new_issued = issued + withdrawals_len as u32;
+    let lock_hash = blake2b_256(&lock_script);
     let latest_block_number = get_syncer_tip_block_number()?;
-    let (define_new_value, value) = generate_define_value(
-        total.to_be_bytes(),
-        new_issued.to_be_bytes(),
-        configure,
-        latest_block_number,
-    );
-    define_new_values.push(define_new_value);
-
-    previous_leaves.push((key, old_value));
-    update_leaves.push((key, value));
-
-    let start_time = Local::now().timestamp_millis();
-    for MintWithdrawal {
-        token_index,
-        state,
-        characteristic,
-        to_lock_script,
-    } in withdrawals
-    {
-        let (withdrawal_key, key) =
-            generate_withdrawal_key_v1(cota_id, token_index, mint_req.out_point);
-        withdrawal_keys.push(withdrawal_key);
-
-        let (withdrawal_value, value) =
-            generate_withdrawal_value_v1(configure, state, characteristic, &to_lock_script);
-        withdrawal_values.push(withdrawal_value);
-
-        previous_leaves.push((key, H256::zero()));
-        update_leaves.push((key, value));
-    }
-    diff_time(start_time, "Generate mint smt object with update leaves");
 
-    let mut action_vec: Vec<u8> = Vec::new();
-    if withdrawals_len == 1 {
-        action_vec.extend("Mint the NFT ".as_bytes());
-        action_vec.extend(
-            hex_string(&withdrawal_keys.first().unwrap().nft_id().as_slice()[2..]).as_bytes(),
-        );
-        action_vec.extend(" to ".as_bytes());
-        action_vec.extend(hex_string(&first_withdrawal.to_lock_script).as_bytes());
-    }
-
-    let smt_root = get_cota_smt_root(&mint_req.lock_script).await?;
-    let lock_hash = blake2b_256(&mint_req.lock_script);
+    let smt_root = get_cota_smt_root(&lock_script).await?;
     let transaction = &StoreTransaction::new(ROCKS_DB.transaction());
     let mut smt = init_smt(transaction, lock_hash)?;
+    let mut mint_smts: Vec<(H256, MintCotaNFTV1Entries)> = Vec::with_capacity(batch_lens.len());
     // Add lock to smt
     with_lock(lock_hash, || {
         generate_history_smt(&mut smt, lock_hash, smt_root)?;
-        smt.update_all(update_leaves.clone())
+        // The define leaf of the SMT contains the define and the mints of the pending
+        // transactions which have not been synced to mysql yet
+        let (_, key) = generate_define_key(cota_id);
+        let mut define_leaf = smt
+            .get(&key)
             .map_err(|e| Error::SMTInvalid(e.to_string()))?;
-        smt.save_root_and_leaves(previous_leaves.clone())?;
+        if define_leaf == H256::zero() {
+            return Err(Error::CotaIdHasNotDefined(hex::encode(cota_id)));
+        }
+        let (total, mut issued, configure) = parse_define_leaf(&define_leaf);
+        check_mint_token_indexes(total, issued, &withdrawals)?;
+        let mut out_point = out_point;
+        let mut batch_withdrawals = withdrawals.iter();
+        for batch_len in batch_lens.iter() {
+            let batch: Vec<&MintWithdrawal> = batch_withdrawals.by_ref().take(*batch_len).collect();
+            let new_issued = issued + batch.len() as u32;
+            let mut define_keys: Vec<DefineCotaNFTId> = Vec::new();
+            let mut define_old_values: Vec<DefineCotaNFTValue> = Vec::new();
+            let mut define_new_values: Vec<DefineCotaNFTValue> = Vec::new();
+            let mut withdrawal_keys: Vec<WithdrawalCotaNFTKeyV1> = Vec::new();
+            let mut withdrawal_values: Vec<WithdrawalCotaNFTValueV1> = Vec::new();
+            let mut update_leaves: Vec<(H256, H256)> = Vec::with_capacity(batch.len() + 1);
+            let mut previous_leaves: Vec<(H256, H256)> = Vec::with_capacity(batch.len() + 1);
+
+            let (define_key, key) = generate_define_key(cota_id);
+            define_keys.push(define_key);
+            let define_old_value = DefineCotaNFTValue::from_slice(&define_leaf.as_slice()[0..9])
+                .map_err(|_e| Error::SMTInvalid("Define leaf parse error".to_owned()))?;
+            define_old_values.push(define_old_value);
+            let (define_new_value, value) = generate_define_value(
+                total.to_be_bytes(),
+                new_issued.to_be_bytes(),
+                configure,
+                latest_block_number,
+            );
+            define_new_values.push(define_new_value);
+            previous_leaves.push((key, define_leaf));
+            update_leaves.push((key, value));
+
+            let start_time = Local::now().timestamp_millis();
+            for withdrawal in batch.iter() {
+                let (withdrawal_key, key) =
+                    generate_withdrawal_key_v1(cota_id, withdrawal.token_index, out_point);
+                withdrawal_keys.push(withdrawal_key);
+
+                let (withdrawal_value, value) = generate_withdrawal_value_v1(
+                    configure,
+                    withdrawal.state,
+                    withdrawal.characteristic,
+                    &withdrawal.to_lock_script,
+                );
+                withdrawal_values.push(withdrawal_value);
+
+                previous_leaves.push((key, H256::zero()));
+                update_leaves.push((key, value));
+            }
+            diff_time(start_time, "Generate mint smt object with update leaves");
+
+            let mut action_vec: Vec<u8> = Vec::new();
+            if batch.len() == 1 {
+                action_vec.extend("Mint the NFT ".as_bytes());
+                action_vec.extend(
+                    hex_string(&withdrawal_keys.first().unwrap().nft_id().as_slice()[2..])
+                        .as_bytes(),
+                );
+                action_vec.extend(" to ".as_bytes());
+                action_vec.extend(hex_string(&batch[0].to_lock_script).as_bytes());
+            }
+
+            let start_time = Local::now().timestamp_millis();
+            let merkel_proof_bytes =
+                apply_smt_batch(&mut smt, &update_leaves, &previous_leaves, dry_run, "Mint")?;
+            diff_time(start_time, "Generate mint smt proof");
+
+            let action_bytes = BytesBuilder::default()
+                .set(action_vec.iter().map(|v| Byte::from(*v)).collect())
+                .build();
+
+            let mint_entries = MintCotaNFTV1EntriesBuilder::default()
+                .define_keys(
+                    DefineCotaNFTKeyVecBuilder::default()
+                        .set(define_keys)
+                        .build(),
+                )
+                .define_old_values(
+                    DefineCotaNFTValueVecBuilder::default()
+                        .set(define_old_values)
+                        .build(),
+                )
+                .define_new_values(
+                    DefineCotaNFTValueVecBuilder::default()
+                        .set(define_new_values)
+                        .build(),
+                )
+                .withdrawal_keys(
+                    WithdrawalCotaNFTKeyV1VecBuilder::default()
+                        .set(withdrawal_keys)
+                        .build(),
+                )
+                .withdrawal_values(
+                    WithdrawalCotaNFTValueV1VecBuilder::default()
+                        .set(withdrawal_values)
+                        .build(),
+                )
+                .proof(merkel_proof_bytes)
+                .action(action_bytes)
+                .build();
+
+            out_point = next_out_point(*smt.root(), &mint_entries)?;
+            mint_smts.push((*smt.root(), mint_entries));
+            issued = new_issued;
+            define_leaf = value;
+        }
+        if dry_run {
+            return Ok(());
+        }
         smt.commit()
     })?;
 
-    let start_time = Local::now().timestamp_millis();
-    let leaf_keys: Vec<H256> = update_leaves.iter().map(|leave| leave.0).collect();
-    let mint_merkle_proof = smt.merkle_proof(leaf_keys.clone()).map_err(|e| {
-        error!("Mint SMT proof error: {:?}", e.to_string());
-        Error::SMTProofInvalid("Mint".to_string())
-    })?;
-    let mint_merkle_proof_compiled = mint_merkle_proof.compile(leaf_keys).map_err(|e| {
-        error!("Mint SMT proof error: {:?}", e.to_string());
-        Error::SMTProofInvalid("Mint".to_string())
-    })?;
-    diff_time(start_time, "Generate mint smt proof");
-
-    let merkel_proof_vec: Vec<u8> = mint_merkle_proof_compiled.into();
-    let merkel_proof_bytes = BytesBuilder::default()
-        .extend(merkel_proof_vec.iter().map(|v| Byte::from(*v)))
-        .build();
-
-    let action_bytes = BytesBuilder::default()
-        .set(action_vec.iter().map(|v| Byte::from(*v)).collect())
-        .build();
-
-    let mint_entries = MintCotaNFTV1EntriesBuilder::default()
-        .define_keys(
-            DefineCotaNFTKeyVecBuilder::default()
-                .set(define_keys)
-                .build(),
-        )
-        .define_old_values(
-            DefineCotaNFTValueVecBuilder::default()
-                .set(define_old_values)
-                .build(),
-        )
-        .define_new_values(
-            DefineCotaNFTValueVecBuilder::default()
-                .set(define_new_values)
-                .build(),
-        )
-        .withdrawal_keys(
-            WithdrawalCotaNFTKeyV1VecBuilder::default()
-                .set(withdrawal_keys)
-                .build(),
-        )
-        .withdrawal_values(
-            WithdrawalCotaNFTValueV1VecBuilder::default()
-                .set(withdrawal_values)
-                .build(),
-        )
-        .proof(merkel_proof_bytes)
-        .action(action_bytes)
-        .build();
-
-    Ok((*smt.root(), mint_entries))
+    Ok(mint_smts)
+}
+
+// The define leaf value is the molecule DefineCotaNFTValue(total, issued and configure) with
+// the padding of the last byte
+fn parse_define_leaf(define_leaf: &H256) -> (u32, u32, u8) {
+    let bytes = define_leaf.as_slice();
+    let total = u32::from_be_bytes(bytes[0..4].try_into().unwrap());
+    let issued = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+    (total, issued, bytes[8])
+}
+
+// The token indexes of the withdrawals must continue from the issued count without gaps, and
+// the new issued count must not exceed the total unless the total is zero(unlimited)
+fn check_mint_token_indexes(
+    total: u32,
+    issued: u32,
+    withdrawals: &[MintWithdrawal],
+) -> Result<u32, Error> {
+    let mut token_indexes: HashSet<u32> = HashSet::with_capacity(withdrawals.len());
+    for withdrawal in withdrawals {
+        let token_index = u32::from_be_bytes(withdrawal.token_index);
+        if !token_indexes.insert(token_index) {
+            return Err(Error::TokenIndexDuplicated(token_index));
+        }
+    }
+    for (index, withdrawal) in withdrawals.iter().enumerate() {
+        let expected = issued as u64 + index as u64;
+        let got = u32::from_be_bytes(withdrawal.token_index);
+        if got as u64 != expected {
+            return Err(Error::TokenIndexNotSequential {
+                got,
+                expected: expected as u32,
+            });
+        }
+    }
+    let new_issued = issued as u64 + withdrawals.len() as u64;
+    if (total != 0 && new_issued > total as u64) || new_issued > u32::MAX as u64 {
+        return Err(Error::CotaIssuedExceedTotal {
+            issued: new_issued,
+            total,
+        });
+    }
+    Ok(new_issued as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn withdrawals(token_indexes: &[u32]) -> Vec<MintWithdrawal> {
+        token_indexes
+            .iter()
+            .map(|token_index| MintWithdrawal {
+                token_index:    token_index.to_be_bytes(),
+                state:          0,
+                characteristic: [0u8; 20],
+                to_lock_script: vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_check_mint_token_indexes() {
+        assert_eq!(
+            check_mint_token_indexes(10, 2, &withdrawals(&[2, 3])),
+            Ok(4)
+        );
+        assert_eq!(check_mint_token_indexes(0, 2, &withdrawals(&[2, 3])), Ok(4));
+        assert_eq!(
+            check_mint_token_indexes(3, 2, &withdrawals(&[2, 3])),
+            Err(Error::CotaIssuedExceedTotal {
+                issued: 4,
+                total:  3,
+            })
+        );
+        assert_eq!(
+            check_mint_token_indexes(10, 2, &withdrawals(&[2, 2])),
+            Err(Error::TokenIndexDuplicated(2))
+        );
+        assert_eq!(
+            check_mint_token_indexes(10, 2, &withdrawals(&[3, 4])),
+            Err(Error::TokenIndexNotSequential {
+                got:      3,
+                expected: 2,
+            })
+        );
+    }
 }
@@ -1,15 +1,20 @@
 use lazy_static::lazy_static;
 use parking_lot::{Condvar, Mutex};
 use std::collections::HashSet;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
+pub(crate) mod burn;
 pub(crate) mod claim;
 pub(crate) mod claim_update;
-mod constants;
+pub(crate) mod constants;
 pub(crate) mod define;
 pub(crate) mod extension;
 pub mod helper;
+pub(crate) mod metadata;
 pub(crate) mod mint;
+mod policy;
+pub(crate) mod registry;
 pub(crate) mod sequential_transfer;
 pub mod smt;
 pub(crate) mod social;
@@ -17,10 +22,12 @@ pub(crate) mod subkey;
 pub(crate) mod transfer;
 pub(crate) mod transfer_update;
 pub(crate) mod update;
+pub(crate) mod verify;
 pub(crate) mod withdrawal;
 mod witness;
 
 lazy_static! {
     static ref SMT_LOCK: Arc<(Mutex<HashSet<[u8; 32]>>, Condvar)> =
         Arc::new((Mutex::new(HashSet::new()), Condvar::new()));
+    static ref SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
 }
@@ -0,0 +1,253 @@
+use crate::entries::constants::{
+    CONFIGURE_CLAIM_DISABLED, CONFIGURE_LOCK_DISABLED, CONFIGURE_RESERVED_BITS,
+    CONFIGURE_TRANSFER_DISABLED, CONFIGURE_UPDATE_DISABLED, STATE_BURNED, STATE_CLAIMED,
+    STATE_LOCKED,
+};
+use crate::entries::helper::generate_cota_index;
+use crate::utils::error::Error;
+
+// The configure of the collection is copied into every NFT of it and constrains what the
+// holders can do with the NFT, the same as the CoTA type script checks it on chain
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Configure(u8);
+
+impl Configure {
+    pub fn claimable(&self) -> bool {
+        self.0 & CONFIGURE_CLAIM_DISABLED == 0
+    }
+
+    pub fn lockable(&self) -> bool {
+        self.0 & CONFIGURE_LOCK_DISABLED == 0
+    }
+
+    pub fn updatable(&self) -> bool {
+        self.0 & CONFIGURE_UPDATE_DISABLED == 0
+    }
+
+    pub fn transferable(&self) -> bool {
+        self.0 & CONFIGURE_TRANSFER_DISABLED == 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct State(u8);
+
+impl State {
+    pub fn locked(&self) -> bool {
+        self.0 & STATE_LOCKED != 0
+    }
+
+    pub fn claimed(&self) -> bool {
+        self.0 & STATE_CLAIMED != 0
+    }
+
+    pub fn burned(&self) -> bool {
+        self.0 & STATE_BURNED != 0
+    }
+}
+
+fn nft_id(cota_id: [u8; 20], token_index: [u8; 4]) -> String {
+    hex::encode(generate_cota_index(cota_id, token_index))
+}
+
+pub fn check_define_configure(configure: u8) -> Result<(), Error> {
+    if configure & CONFIGURE_RESERVED_BITS != 0 {
+        return Err(Error::CotaConfigureInvalid(configure));
+    }
+    Ok(())
+}
+
+// is_claimed is whether the claim leaf of the withdrawal exists for the receiver
+pub fn check_claim(
+    cota_id: [u8; 20],
+    token_index: [u8; 4],
+    configure: u8,
+    is_claimed: bool,
+) -> Result<(), Error> {
+    if is_claimed {
+        return Err(Error::NFTHasClaimed(nft_id(cota_id, token_index)));
+    }
+    if !Configure(configure).claimable() {
+        return Err(Error::NFTNotClaimable(nft_id(cota_id, token_index)));
+    }
+    Ok(())
+}
+
+pub fn check_transfer(
+    cota_id: [u8; 20],
+    token_index: [u8; 4],
+    configure: u8,
+    state: u8,
+) -> Result<(), Error> {
+    let state = State(state);
+    if state.burned() {
+        return Err(Error::NFTBurned(nft_id(cota_id, token_index)));
+    }
+    if state.locked() {
+        return Err(Error::NFTLocked(nft_id(cota_id, token_index)));
+    }
+    if !Configure(configure).transferable() {
+        return Err(Error::NFTNotTransferable(nft_id(cota_id, token_index)));
+    }
+    Ok(())
+}
+
+// The characteristic can only be changed when the NFT is updatable and the state can only be
+// locked when it is lockable. The claimed and burned bits are kept by the claims and burns rather
+// than the updates, and a burned NFT cannot be updated at all.
+pub fn check_update(
+    cota_id: [u8; 20],
+    token_index: [u8; 4],
+    configure: u8,
+    (old_state, old_characteristic): (u8, [u8; 20]),
+    (new_state, new_characteristic): (u8, [u8; 20]),
+) -> Result<(), Error> {
+    let configure = Configure(configure);
+    let (old_state, new_state) = (State(old_state), State(new_state));
+    if old_state.burned() {
+        return Err(Error::NFTBurned(nft_id(cota_id, token_index)));
+    }
+    if old_characteristic != new_characteristic && !configure.updatable() {
+        return Err(Error::NFTNotUpdatable(nft_id(cota_id, token_index)));
+    }
+    if !old_state.locked() && new_state.locked() && !configure.lockable() {
+        return Err(Error::NFTNotLockable(nft_id(cota_id, token_index)));
+    }
+    if old_state.claimed() != new_state.claimed() || old_state.burned() != new_state.burned() {
+        return Err(Error::NFTStateInvalid(nft_id(cota_id, token_index)));
+    }
+    Ok(())
+}
+
+// A burned NFT stays in the SMT with the burned bit set in its state, so a locked NFT is kept
+// locked rather than being burned
+pub fn check_burn(cota_id: [u8; 20], token_index: [u8; 4], state: u8) -> Result<(), Error> {
+    let state = State(state);
+    if state.burned() {
+        return Err(Error::NFTBurned(nft_id(cota_id, token_index)));
+    }
+    if state.locked() {
+        return Err(Error::NFTLocked(nft_id(cota_id, token_index)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COTA_ID: [u8; 20] = [1u8; 20];
+    const TOKEN_INDEX: [u8; 4] = [0u8; 4];
+
+    #[test]
+    fn test_check_update() {
+        let characteristic = [0u8; 20];
+        let new_characteristic = [1u8; 20];
+        assert!(check_update(
+            COTA_ID,
+            TOKEN_INDEX,
+            0x00,
+            (0x00, characteristic),
+            (STATE_LOCKED, new_characteristic)
+        )
+        .is_ok());
+        assert!(matches!(
+            check_update(
+                COTA_ID,
+                TOKEN_INDEX,
+                CONFIGURE_UPDATE_DISABLED,
+                (0x00, characteristic),
+                (0x00, new_characteristic)
+            ),
+            Err(Error::NFTNotUpdatable(_))
+        ));
+        assert!(check_update(
+            COTA_ID,
+            TOKEN_INDEX,
+            CONFIGURE_UPDATE_DISABLED,
+            (0x00, characteristic),
+            (STATE_LOCKED, characteristic)
+        )
+        .is_ok());
+        assert!(matches!(
+            check_update(
+                COTA_ID,
+                TOKEN_INDEX,
+                CONFIGURE_LOCK_DISABLED,
+                (0x00, characteristic),
+                (STATE_LOCKED, characteristic)
+            ),
+            Err(Error::NFTNotLockable(_))
+        ));
+        assert!(matches!(
+            check_update(
+                COTA_ID,
+                TOKEN_INDEX,
+                0x00,
+                (STATE_CLAIMED, characteristic),
+                (0x00, characteristic)
+            ),
+            Err(Error::NFTStateInvalid(_))
+        ));
+        assert!(matches!(
+            check_update(
+                COTA_ID,
+                TOKEN_INDEX,
+                0x00,
+                (0x00, characteristic),
+                (STATE_BURNED, characteristic)
+            ),
+            Err(Error::NFTStateInvalid(_))
+        ));
+        assert!(matches!(
+            check_update(
+                COTA_ID,
+                TOKEN_INDEX,
+                0x00,
+                (STATE_BURNED, characteristic),
+                (STATE_BURNED, new_characteristic)
+            ),
+            Err(Error::NFTBurned(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_burn() {
+        assert!(check_burn(COTA_ID, TOKEN_INDEX, STATE_CLAIMED).is_ok());
+        assert!(matches!(
+            check_burn(COTA_ID, TOKEN_INDEX, STATE_LOCKED),
+            Err(Error::NFTLocked(_))
+        ));
+        assert!(matches!(
+            check_burn(COTA_ID, TOKEN_INDEX, STATE_BURNED),
+            Err(Error::NFTBurned(_))
+        ));
+        assert!(matches!(
+            check_transfer(COTA_ID, TOKEN_INDEX, 0x00, STATE_BURNED),
+            Err(Error::NFTBurned(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_transfer_and_claim() {
+        assert!(check_transfer(COTA_ID, TOKEN_INDEX, 0x00, STATE_CLAIMED).is_ok());
+        assert!(matches!(
+            check_transfer(COTA_ID, TOKEN_INDEX, 0x00, STATE_LOCKED),
+            Err(Error::NFTLocked(_))
+        ));
+        assert!(matches!(
+            check_transfer(COTA_ID, TOKEN_INDEX, CONFIGURE_TRANSFER_DISABLED, 0x00),
+            Err(Error::NFTNotTransferable(_))
+        ));
+
+        assert!(check_claim(COTA_ID, TOKEN_INDEX, 0x00, false).is_ok());
+        assert!(matches!(
+            check_claim(COTA_ID, TOKEN_INDEX, 0x00, true),
+            Err(Error::NFTHasClaimed(_))
+        ));
+        assert!(matches!(
+            check_claim(COTA_ID, TOKEN_INDEX, CONFIGURE_CLAIM_DISABLED, false),
+            Err(Error::NFTNotClaimable(_))
+        ));
+    }
+}
@@ -0,0 +1,77 @@
+use super::constants::REGISTRY_SMT_LOCK_HASH;
+use crate::ckb::indexer::get_registry_smt_root;
+use crate::entries::helper::{generate_registry_leaf, with_lock};
+use crate::entries::smt::{apply_smt_batch, generate_registry_history_smt, init_smt};
+use crate::models::registry::check_registered;
+use crate::request::registry::RegistryReq;
+use crate::smt::transaction::store_transaction::StoreTransaction;
+use crate::smt::RootSaver;
+use crate::utils::error::Error;
+use crate::ROCKS_DB;
+use cota_smt::common::*;
+use cota_smt::molecule::prelude::*;
+use cota_smt::registry::{
+    CotaNFTRegistryEntries, CotaNFTRegistryEntriesBuilder, Registry, RegistryBuilder,
+    RegistryVecBuilder,
+};
+use cota_smt::smt::H256;
+
+pub async fn generate_registry_smt(
+    registry_req: RegistryReq,
+) -> Result<(H256, CotaNFTRegistryEntries), Error> {
+    let lock_hashes = registry_req.lock_hashes;
+    if lock_hashes.is_empty() {
+        return Err(Error::RequestParamNotFound("lock_hashes".to_string()));
+    }
+    let mut registries: Vec<Registry> = Vec::with_capacity(lock_hashes.len());
+    let mut update_leaves: Vec<(H256, H256)> = Vec::with_capacity(lock_hashes.len());
+    let mut previous_leaves: Vec<(H256, H256)> = Vec::with_capacity(lock_hashes.len());
+    for lock_hash in lock_hashes {
+        if check_registered(lock_hash)?.0 {
+            return Err(Error::LockHashHasRegistered(hex::encode(lock_hash)));
+        }
+        let registry = RegistryBuilder::default()
+            .lock_hash(Byte32::from_slice(&lock_hash).unwrap())
+            .state(Byte32::from_slice(&[0u8; 32]).unwrap())
+            .build();
+        let (key, value) = generate_registry_leaf(lock_hash);
+        registries.push(registry);
+        update_leaves.push((key, value));
+        previous_leaves.push((key, H256::zero()));
+    }
+
+    let smt_root = get_registry_smt_root().await?;
+    let transaction = &StoreTransaction::new(ROCKS_DB.transaction());
+    let mut smt = init_smt(transaction, REGISTRY_SMT_LOCK_HASH)?;
+    let mut merkel_proof_bytes = Bytes::default();
+    // Add lock to smt, the proof is generated with the lock since the registry SMT is shared by
+    // all the accounts
+    with_lock(REGISTRY_SMT_LOCK_HASH, || {
+        generate_registry_history_smt(&mut smt, smt_root)?;
+        // The registrations which have not been synced to mysql are only in the registry SMT
+        for (key, _) in update_leaves.iter() {
+            let value = smt.get(key).map_err(|e| Error::SMTInvalid(e.to_string()))?;
+            if value != H256::zero() {
+                return Err(Error::LockHashHasRegistered(hex::encode(key.as_slice())));
+            }
+        }
+        merkel_proof_bytes = apply_smt_batch(
+            &mut smt,
+            &update_leaves,
+            &previous_leaves,
+            registry_req.dry_run,
+            "Registry",
+        )?;
+        if registry_req.dry_run {
+            return Ok(());
+        }
+        smt.commit()
+    })?;
+
+    let registry_entries = CotaNFTRegistryEntriesBuilder::default()
+        .registries(RegistryVecBuilder::default().set(registries).build())
+        .proof(merkel_proof_bytes)
+        .build();
+
+    Ok((*smt.root(), registry_entries))
+}
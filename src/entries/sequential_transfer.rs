@@ -5,6 +5,7 @@ use crate::entries::helper::{
     generate_claim_key, generate_claim_value, generate_withdrawal_key_v1,
     generate_withdrawal_value_v1, with_lock,
 };
+use crate::entries::policy::{check_claim, check_transfer};
 use crate::entries::smt::{generate_history_smt, init_smt};
 use crate::entries::witness::parse_witness_withdraw_proof;
 use crate::models::claim::is_exist_in_claim;
@@ -67,9 +68,18 @@ pub async fn generate_sequential_transfer_smt(
                 current_withdrawal.token_index,
                 current_withdrawal.out_point,
             );
-            if is_claimed {
-                return Err(Error::CotaIdAndTokenIndexHasNotWithdrawn);
-            }
+            check_claim(
+                current_withdrawal.cota_id,
+                current_withdrawal.token_index,
+                current_withdrawal.configure,
+                is_claimed,
+            )?;
+            check_transfer(
+                current_withdrawal.cota_id,
+                current_withdrawal.token_index,
+                current_withdrawal.configure,
+                current_withdrawal.state,
+            )?;
         }
     }
 
@@ -152,6 +162,9 @@ pub async fn generate_sequential_transfer_smt(
             .update_all(transfer_update_leaves.clone())
             .map_err(|e| Error::SMTInvalid(e.to_string()))?;
         next_subkey_entries = generate_subkey_smt(transfer_lock_hash, &subkey_opt, &transfer_smt)?;
+        if transfer_req.dry_run {
+            return Ok(());
+        }
         transfer_smt.save_root_and_leaves(previous_leaves.clone())?;
         transfer_smt.commit()
     })?;
@@ -1,25 +1,30 @@
+use crate::ckb::indexer::get_cota_smt_root;
 use crate::entries::helper::{
     generate_claim_key, generate_claim_value, generate_cota_index, generate_define_key,
-    generate_define_value, generate_hold_key, generate_hold_value, generate_withdrawal_key,
-    generate_withdrawal_key_v1, generate_withdrawal_value, generate_withdrawal_value_v1,
+    generate_define_value, generate_hold_key, generate_hold_value, generate_registry_leaf,
+    generate_withdrawal_key, generate_withdrawal_key_v1, generate_withdrawal_value,
+    generate_withdrawal_value_v1, with_lock,
 };
 use crate::models::claim::ClaimDb;
 use crate::models::common::get_all_cota_by_lock_hash;
 use crate::models::define::DefineDb;
 use crate::models::hold::HoldDb;
+use crate::models::registry::get_registered_lock_hashes;
 use crate::models::withdrawal::nft::WithdrawDb;
 use crate::smt::db::schema::{
     COLUMN_SMT_BRANCH, COLUMN_SMT_LEAF, COLUMN_SMT_ROOT, COLUMN_SMT_TEMP_LEAVES,
 };
 use crate::smt::store::smt_store::SMTStore;
 use crate::smt::transaction::store_transaction::StoreTransaction;
-use crate::smt::CotaSMT;
+use crate::smt::{CotaSMT, RootSaver};
 use crate::utils::error::Error;
 use crate::utils::helper::diff_time;
+use crate::ROCKS_DB;
 use chrono::prelude::*;
 use cota_smt::common::*;
-use cota_smt::smt::H256;
-use log::debug;
+use cota_smt::molecule::prelude::*;
+use cota_smt::smt::{blake2b_256, H256};
+use log::{debug, error};
 use std::collections::HashMap;
 
 pub fn init_smt(transaction: &StoreTransaction, lock_hash: [u8; 32]) -> Result<CotaSMT, Error> {
@@ -48,29 +53,83 @@ pub fn generate_history_smt(
     lock_hash: [u8; 32],
     smt_root_opt: Option<[u8; 32]>,
 ) -> Result<(), Error> {
-    let root = *smt.root();
-    if root == H256::zero() {
-        return generate_mysql_smt(smt, lock_hash);
+    if *smt.root() == H256::zero() {
+        generate_mysql_smt(smt, lock_hash)?;
+        return smt.store().save_root(smt.root());
     }
     debug!(
         "cota cell smt root: {:?} of {:?}",
         smt_root_opt,
         hex::encode(lock_hash)
     );
-    if let Some(smt_root) = smt_root_opt {
-        if smt_root.as_slice() == root.as_slice() {
-            debug!("The smt leaves and root in rocksdb are right");
+    if smt.reconcile_pending(smt_root_opt.map(H256::from))? {
+        debug!("The smt leaves and root in rocksdb are right");
+        return Ok(());
+    }
+    generate_mysql_smt(smt, lock_hash)?;
+    smt.store().save_root(smt.root())
+}
+
+// Updates the SMT with the leaves of a batch and returns the compiled proof of them. The batch
+// is saved as a pending update of its own unless it is a dry run, since the transactions of the
+// batches are committed one by one.
+pub fn apply_smt_batch(
+    smt: &mut CotaSMT,
+    update_leaves: &[(H256, H256)],
+    previous_leaves: &[(H256, H256)],
+    dry_run: bool,
+    name: &str,
+) -> Result<Bytes, Error> {
+    smt.update_all(update_leaves.to_vec())
+        .map_err(|e| Error::SMTInvalid(e.to_string()))?;
+
+    let leaf_keys: Vec<H256> = update_leaves.iter().map(|leave| leave.0).collect();
+    let merkle_proof = smt.merkle_proof(leaf_keys.clone()).map_err(|e| {
+        error!("{} SMT proof error: {:?}", name, e.to_string());
+        Error::SMTProofInvalid(name.to_string())
+    })?;
+    let merkle_proof_compiled = merkle_proof.compile(leaf_keys).map_err(|e| {
+        error!("{} SMT proof error: {:?}", name, e.to_string());
+        Error::SMTProofInvalid(name.to_string())
+    })?;
+    let merkel_proof_vec: Vec<u8> = merkle_proof_compiled.into();
+    let merkel_proof_bytes = BytesBuilder::default()
+        .extend(merkel_proof_vec.iter().map(|v| Byte::from(*v)))
+        .build();
+
+    if !dry_run {
+        smt.save_root_and_leaves(previous_leaves.to_vec())?;
+    }
+    Ok(merkel_proof_bytes)
+}
+
+// The leaves of the batches are (update_leaves, previous_leaves), and they are applied one after
+// another on top of the SMT of the lock script, so that every batch gets the SMT root and proof
+// on top of the previous one.
+pub async fn apply_smt_batches(
+    lock_script: &[u8],
+    batch_leaves: &[(Vec<(H256, H256)>, Vec<(H256, H256)>)],
+    dry_run: bool,
+    name: &str,
+) -> Result<Vec<(H256, Bytes)>, Error> {
+    let smt_root = get_cota_smt_root(lock_script).await?;
+    let transaction = &StoreTransaction::new(ROCKS_DB.transaction());
+    let lock_hash = blake2b_256(lock_script);
+    let mut smt = init_smt(transaction, lock_hash)?;
+    let mut roots_and_proofs: Vec<(H256, Bytes)> = Vec::with_capacity(batch_leaves.len());
+    // Add lock to smt
+    with_lock(lock_hash, || {
+        generate_history_smt(&mut smt, lock_hash, smt_root)?;
+        for (update_leaves, previous_leaves) in batch_leaves.iter() {
+            let proof = apply_smt_batch(&mut smt, update_leaves, previous_leaves, dry_run, name)?;
+            roots_and_proofs.push((*smt.root(), proof));
+        }
+        if dry_run {
             return Ok(());
-        } else {
-            reset_smt_temp_leaves(smt)?;
-            if smt_root.as_slice() == smt.root().as_slice() {
-                debug!("The smt leaves and root in rocksdb are right after reset");
-                return Ok(());
-            }
         }
-    }
-    reset_smt_temp_leaves(smt)?;
-    generate_mysql_smt(smt, lock_hash)
+        smt.commit()
+    })?;
+    Ok(roots_and_proofs)
 }
 
 pub fn generate_mysql_smt(smt: &mut CotaSMT, lock_hash: [u8; 32]) -> Result<(), Error> {
@@ -180,12 +239,38 @@ pub fn generate_mysql_smt(smt: &mut CotaSMT, lock_hash: [u8; 32]) -> Result<(),
     Ok(())
 }
 
-fn reset_smt_temp_leaves(smt: &mut CotaSMT) -> Result<(), Error> {
-    let leaves_opt = smt.store().get_leaves()?;
-    if let Some(leaves) = leaves_opt {
+// The registry SMT is shared by all the accounts and it is rebuilt from the registered lock
+// hashes of mysql when the root of rocksdb mismatches the root of the registry cell
+pub fn generate_registry_history_smt(
+    smt: &mut CotaSMT,
+    smt_root_opt: Option<[u8; 32]>,
+) -> Result<(), Error> {
+    if *smt.root() == H256::zero() {
+        generate_mysql_registry_smt(smt)?;
+        return smt.store().save_root(smt.root());
+    }
+    debug!("registry cell smt root: {:?}", smt_root_opt);
+    if smt.reconcile_pending(smt_root_opt.map(H256::from))? {
+        debug!("The registry smt leaves and root in rocksdb are right");
+        return Ok(());
+    }
+    generate_mysql_registry_smt(smt)?;
+    smt.store().save_root(smt.root())
+}
+
+fn generate_mysql_registry_smt(smt: &mut CotaSMT) -> Result<(), Error> {
+    let start_time = Local::now().timestamp_millis();
+    let leaves: Vec<(H256, H256)> = get_registered_lock_hashes()?
+        .into_iter()
+        .map(generate_registry_leaf)
+        .collect();
+    diff_time(
+        start_time,
+        "Load all registered lock hashes from mysql database",
+    );
+    if !leaves.is_empty() {
         smt.update_all(leaves)
-            .expect("SMT update temp leaves error");
+            .map_err(|e| Error::SMTInvalid(e.to_string()))?;
     }
-    debug!("Reset temp leaves successfully");
     Ok(())
 }
@@ -1,9 +1,12 @@
+use crate::business::estimate::check_batch;
 use crate::ckb::indexer::get_cota_smt_root;
 use crate::ckb::rpc::get_withdraw_info;
+use crate::entries::claim::CLAIM_BASE_SIZE;
 use crate::entries::helper::{
     generate_claim_key, generate_claim_value, generate_withdrawal_key_v1,
     generate_withdrawal_value_v1, with_lock,
 };
+use crate::entries::policy::{check_claim, check_transfer};
 use crate::entries::smt::{generate_history_smt, init_smt};
 use crate::entries::witness::parse_witness_withdraw_proof;
 use crate::models::claim::is_exist_in_claim;
@@ -23,6 +26,12 @@ use cota_smt::transfer::{TransferCotaNFTV2Entries, TransferCotaNFTV2EntriesBuild
 use log::error;
 use molecule::hex_string;
 
+// The entry bytes of a transfer out of its lock script, which are the claim and withdrawal keys
+// and values, and its SMT leaves, which are the claim leaf of the sender and the withdrawal leaf
+// of the receiver besides the withdrawal leaf proved by the withdrawal proof
+pub(crate) const TRANSFER_ENTRY_SIZE: usize = 160;
+pub(crate) const TRANSFER_LEAF_COUNT: usize = 3;
+
 pub async fn generate_transfer_smt(
     transfer_req: TransferReq,
 ) -> Result<(H256, TransferCotaNFTV2Entries, H256), Error> {
@@ -32,6 +41,16 @@ pub async fn generate_transfer_smt(
     if transfers_len == 0 {
         return Err(Error::RequestParamNotFound("transfers".to_string()));
     }
+    let items: Vec<(usize, usize)> = transfers
+        .iter()
+        .map(|transfer| {
+            (
+                TRANSFER_ENTRY_SIZE + transfer.to_lock_script.len(),
+                TRANSFER_LEAF_COUNT,
+            )
+        })
+        .collect();
+    check_batch(&items, (CLAIM_BASE_SIZE, 0))?;
     let cota_id_index_pairs: Vec<([u8; 20], [u8; 4])> = transfers
         .iter()
         .map(|transfer| (transfer.cota_id, transfer.token_index))
@@ -49,16 +68,25 @@ pub async fn generate_transfer_smt(
         return Err(Error::CotaIdAndTokenIndexHasNotWithdrawn);
     }
     let transfer_lock_hash = blake2b_256(&transfer_lock_script);
-    let is_claimed = sender_withdrawals.iter().any(|withdrawal| {
-        is_exist_in_claim(
+    for withdrawal in sender_withdrawals.iter() {
+        let is_claimed = is_exist_in_claim(
             transfer_lock_hash,
             withdrawal.cota_id,
             withdrawal.token_index,
             withdrawal.out_point,
-        )
-    });
-    if is_claimed {
-        return Err(Error::CotaIdAndTokenIndexHasNotWithdrawn);
+        );
+        check_claim(
+            withdrawal.cota_id,
+            withdrawal.token_index,
+            withdrawal.configure,
+            is_claimed,
+        )?;
+        check_transfer(
+            withdrawal.cota_id,
+            withdrawal.token_index,
+            withdrawal.configure,
+            withdrawal.state,
+        )?;
     }
     let withdrawal_block_number = sender_withdrawals.first().unwrap().block_number;
     let withdrawal_tx_hash = sender_withdrawals.first().unwrap().tx_hash;
@@ -133,6 +161,9 @@ pub async fn generate_transfer_smt(
         transfer_smt
             .update_all(transfer_update_leaves.clone())
             .map_err(|e| Error::SMTInvalid(e.to_string()))?;
+        if transfer_req.dry_run {
+            return Ok(());
+        }
         transfer_smt.save_root_and_leaves(previous_leaves.clone())?;
         transfer_smt.commit()
     })?;
@@ -1,10 +1,14 @@
+use crate::business::estimate::check_batch;
 use crate::ckb::indexer::get_cota_smt_root;
 use crate::ckb::rpc::get_withdraw_info;
+use crate::entries::claim::CLAIM_BASE_SIZE;
 use crate::entries::helper::{
     generate_claim_key, generate_claim_value, generate_withdrawal_key_v1,
     generate_withdrawal_value_v1, with_lock,
 };
+use crate::entries::policy::{check_claim, check_transfer, check_update};
 use crate::entries::smt::{generate_history_smt, init_smt};
+use crate::entries::transfer::{TRANSFER_ENTRY_SIZE, TRANSFER_LEAF_COUNT};
 use crate::entries::witness::parse_witness_withdraw_proof;
 use crate::models::claim::is_exist_in_claim;
 use crate::models::withdrawal::nft::{get_withdrawal_cota_by_lock_hash, WithdrawDb};
@@ -31,6 +35,16 @@ pub async fn generate_transfer_update_smt(
     if transfers_len == 0 {
         return Err(Error::RequestParamNotFound("transfers".to_string()));
     }
+    let items: Vec<(usize, usize)> = transfers
+        .iter()
+        .map(|transfer| {
+            (
+                TRANSFER_ENTRY_SIZE + transfer.to_lock_script.len(),
+                TRANSFER_LEAF_COUNT,
+            )
+        })
+        .collect();
+    check_batch(&items, (CLAIM_BASE_SIZE, 0))?;
     let cota_id_index_pairs: Vec<([u8; 20], [u8; 4])> = transfers
         .iter()
         .map(|transfer| (transfer.cota_id, transfer.token_index))
@@ -48,16 +62,25 @@ pub async fn generate_transfer_update_smt(
         return Err(Error::CotaIdAndTokenIndexHasNotWithdrawn);
     }
     let transfer_lock_hash = blake2b_256(&transfer_lock_script);
-    let is_claimed = sender_withdrawals.iter().any(|withdrawal| {
-        is_exist_in_claim(
+    for withdrawal in sender_withdrawals.iter() {
+        let is_claimed = is_exist_in_claim(
             transfer_lock_hash,
             withdrawal.cota_id,
             withdrawal.token_index,
             withdrawal.out_point,
-        )
-    });
-    if is_claimed {
-        return Err(Error::CotaIdAndTokenIndexHasNotWithdrawn);
+        );
+        check_claim(
+            withdrawal.cota_id,
+            withdrawal.token_index,
+            withdrawal.configure,
+            is_claimed,
+        )?;
+        check_transfer(
+            withdrawal.cota_id,
+            withdrawal.token_index,
+            withdrawal.configure,
+            withdrawal.state,
+        )?;
     }
     let withdrawal_block_number = sender_withdrawals.first().unwrap().block_number;
     let withdrawal_tx_hash = sender_withdrawals.first().unwrap().tx_hash;
@@ -111,6 +134,13 @@ pub async fn generate_transfer_update_smt(
         claimed_infos.push(claimed_info);
 
         let TransferUpdate { to_lock_script, .. } = transfer;
+        check_update(
+            cota_id,
+            token_index,
+            configure,
+            (state, characteristic),
+            (transfer.state, transfer.characteristic),
+        )?;
 
         let (withdrawal_key, key) = generate_withdrawal_key_v1(
             cota_id,
@@ -152,6 +182,9 @@ pub async fn generate_transfer_update_smt(
         transfer_update_smt
             .update_all(transfer_update_leaves.clone())
             .map_err(|e| Error::SMTInvalid(e.to_string()))?;
+        if transfer_update_req.dry_run {
+            return Ok(());
+        }
         transfer_update_smt.save_root_and_leaves(previous_leaves.clone())?;
         transfer_update_smt.commit()
     })?;
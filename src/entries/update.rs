@@ -1,17 +1,30 @@
-use crate::ckb::indexer::get_cota_smt_root;
-use crate::entries::helper::{generate_hold_key, generate_hold_value, with_lock};
-use crate::entries::smt::{generate_history_smt, init_smt};
-use crate::models::hold::get_hold_cota_by_lock_hash;
-use crate::request::update::UpdateReq;
-use crate::smt::transaction::store_transaction::StoreTransaction;
-use crate::smt::RootSaver;
+use crate::business::estimate::{check_batch, split_batch};
+use crate::entries::helper::{generate_hold_key, generate_hold_value};
+use crate::entries::policy::check_update;
+use crate::entries::smt::apply_smt_batches;
+use crate::models::hold::{
+    get_hold_cota_by_lock_hash, get_hold_cota_by_lock_hash_and_token_range, HoldDb,
+};
+use crate::request::update::{NftTransform, UpdateByFilterReq, UpdateReq};
 use crate::utils::error::Error;
-use crate::ROCKS_DB;
 use cota_smt::common::*;
 use cota_smt::molecule::prelude::*;
 use cota_smt::smt::{blake2b_256, H256};
 use cota_smt::update::{UpdateCotaNFTEntries, UpdateCotaNFTEntriesBuilder};
-use log::error;
+
+// The entry bytes of an updated NFT, which are its hold key with the old and new hold values,
+// and the ones of the action and the molecule headers which every update batch carries
+const UPDATE_ENTRY_SIZE: usize = 70;
+const UPDATE_BASE_SIZE: usize = 128;
+const UPDATE_ACTION: &str = "Update NFT information";
+
+pub(crate) struct UpdateBatch {
+    hold_keys:       Vec<CotaNFTId>,
+    hold_old_values: Vec<CotaNFTInfo>,
+    hold_values:     Vec<CotaNFTInfo>,
+    update_leaves:   Vec<(H256, H256)>,
+    previous_leaves: Vec<(H256, H256)>,
+}
 
 pub async fn generate_update_smt(
     update_req: UpdateReq,
@@ -29,73 +42,194 @@ pub async fn generate_update_smt(
     if db_holds.is_empty() || db_holds.len() != nfts.len() {
         return Err(Error::CotaIdAndTokenIndexHasNotHeld);
     }
-    let mut hold_keys: Vec<CotaNFTId> = Vec::new();
-    let mut hold_old_values: Vec<CotaNFTInfo> = Vec::new();
-    let mut hold_values: Vec<CotaNFTInfo> = Vec::new();
-    let mut update_leaves: Vec<(H256, H256)> = Vec::with_capacity(nfts.len());
-    let mut previous_leaves: Vec<(H256, H256)> = Vec::with_capacity(nfts.len());
-    for (hold_db, nft) in db_holds.iter().zip(nfts.iter()) {
+    let updates: Vec<(HoldDb, u8, [u8; 20])> = db_holds
+        .into_iter()
+        .zip(nfts.iter())
+        .map(|(hold_db, nft)| (hold_db, nft.state, nft.characteristic))
+        .collect();
+    check_updates(&updates)?;
+    let batch = generate_update_batch(&updates);
+    let mut update_smts = update_smt_batches(
+        &update_req.lock_script,
+        vec![batch],
+        UPDATE_ACTION,
+        update_req.dry_run,
+    )
+    .await?;
+    Ok(update_smts.remove(0))
+}
+
+// Updates the held NFTs selected by the filter, and the NFTs which the transform does not change
+// are skipped. The batches are built on top of each other and must be committed in order.
+pub async fn generate_update_by_filter_smt(
+    update_req: UpdateByFilterReq,
+) -> Result<Vec<(H256, UpdateCotaNFTEntries)>, Error> {
+    let holds = get_hold_cota_by_lock_hash_and_token_range(
+        blake2b_256(&update_req.lock_script),
+        update_req.cota_id,
+        (update_req.token_index_start, update_req.token_index_end),
+    )?;
+    let updates: Vec<(HoldDb, u8, [u8; 20])> = holds
+        .into_iter()
+        .filter(|hold| hold.state & update_req.match_state_mask == update_req.match_state)
+        .map(|hold| {
+            let (state, characteristic) =
+                transform_nft(&update_req.transform, hold.state, hold.characteristic);
+            (hold, state, characteristic)
+        })
+        .filter(|(hold, state, characteristic)| {
+            hold.state != *state || hold.characteristic != *characteristic
+        })
+        .collect();
+    if updates.is_empty() {
+        return Ok(vec![]);
+    }
+    check_updates(&updates)?;
+    let items = vec![(UPDATE_ENTRY_SIZE, 1); updates.len()];
+    let mut remaining = updates.as_slice();
+    let mut batches = Vec::new();
+    for batch_len in split_batch(&items, (UPDATE_BASE_SIZE, 0))? {
+        let (batch, rest) = remaining.split_at(batch_len);
+        batches.push(generate_update_batch(batch));
+        remaining = rest;
+    }
+    update_smt_batches(
+        &update_req.lock_script,
+        batches,
+        UPDATE_ACTION,
+        update_req.dry_run,
+    )
+    .await
+}
+
+fn transform_nft(transform: &NftTransform, state: u8, characteristic: [u8; 20]) -> (u8, [u8; 20]) {
+    let mut characteristic = transform.characteristic.unwrap_or(characteristic);
+    for (index, byte) in characteristic.iter_mut().enumerate() {
+        *byte =
+            (*byte | transform.characteristic_set[index]) & !transform.characteristic_clear[index];
+    }
+    (
+        (state | transform.state_set) & !transform.state_clear,
+        characteristic,
+    )
+}
+
+// The updates are (hold, new_state, new_characteristic)
+fn check_updates(updates: &[(HoldDb, u8, [u8; 20])]) -> Result<(), Error> {
+    for (hold_db, state, characteristic) in updates.iter() {
+        check_update(
+            hold_db.cota_id,
+            hold_db.token_index,
+            hold_db.configure,
+            (hold_db.state, hold_db.characteristic),
+            (*state, *characteristic),
+        )?;
+    }
+    Ok(())
+}
+
+// The updates are (hold, new_state, new_characteristic), which must have been checked
+pub(crate) fn generate_update_batch(updates: &[(HoldDb, u8, [u8; 20])]) -> UpdateBatch {
+    let mut batch = UpdateBatch {
+        hold_keys:       Vec::with_capacity(updates.len()),
+        hold_old_values: Vec::with_capacity(updates.len()),
+        hold_values:     Vec::with_capacity(updates.len()),
+        update_leaves:   Vec::with_capacity(updates.len()),
+        previous_leaves: Vec::with_capacity(updates.len()),
+    };
+    for (hold_db, state, characteristic) in updates.iter() {
         let (hold_key, key) = generate_hold_key(hold_db.cota_id, hold_db.token_index);
         let (hold_old_value, old_value) =
             generate_hold_value(hold_db.configure, hold_db.state, hold_db.characteristic);
-        let (hold_value, value) =
-            generate_hold_value(hold_db.configure, nft.state, nft.characteristic);
-        hold_keys.push(hold_key);
-        hold_old_values.push(hold_old_value);
-        hold_values.push(hold_value);
-        update_leaves.push((key, value));
-        previous_leaves.push((key, old_value));
+        let (hold_value, value) = generate_hold_value(hold_db.configure, *state, *characteristic);
+        batch.hold_keys.push(hold_key);
+        batch.hold_old_values.push(hold_old_value);
+        batch.hold_values.push(hold_value);
+        batch.update_leaves.push((key, value));
+        batch.previous_leaves.push((key, old_value));
+    }
+    batch
+}
+
+// Every batch is updated on top of the previous one and saved as a pending update of its own,
+// and the batches which do not fit in one transaction are rejected
+pub(crate) async fn update_smt_batches(
+    lock_script: &[u8],
+    batches: Vec<UpdateBatch>,
+    action: &str,
+    dry_run: bool,
+) -> Result<Vec<(H256, UpdateCotaNFTEntries)>, Error> {
+    for batch in batches.iter() {
+        check_batch(
+            &vec![(UPDATE_ENTRY_SIZE, 1); batch.hold_keys.len()],
+            (UPDATE_BASE_SIZE, 0),
+        )?;
     }
+    let batch_leaves: Vec<(Vec<(H256, H256)>, Vec<(H256, H256)>)> = batches
+        .iter()
+        .map(|batch| (batch.update_leaves.clone(), batch.previous_leaves.clone()))
+        .collect();
+    let roots_and_proofs = apply_smt_batches(lock_script, &batch_leaves, dry_run, "Update").await?;
 
-    let smt_root = get_cota_smt_root(&update_req.lock_script).await?;
-    let transaction = &StoreTransaction::new(ROCKS_DB.transaction());
-    let lock_hash = blake2b_256(&update_req.lock_script);
-    let mut smt = init_smt(transaction, lock_hash)?;
-    // Add lock to smt
-    with_lock(lock_hash, || {
-        generate_history_smt(&mut smt, lock_hash, smt_root)?;
-        smt.update_all(update_leaves.clone())
-            .map_err(|e| Error::SMTInvalid(e.to_string()))?;
-        smt.save_root_and_leaves(previous_leaves.clone())?;
-        smt.commit()
-    })?;
+    let mut update_smts = Vec::with_capacity(batches.len());
+    for (batch, (root, proof)) in batches.into_iter().zip(roots_and_proofs) {
+        let mut action_vec: Vec<u8> = Vec::new();
+        action_vec.extend(action.as_bytes());
+        let action_bytes = BytesBuilder::default()
+            .set(action_vec.iter().map(|v| Byte::from(*v)).collect())
+            .build();
 
-    let leaf_keys: Vec<H256> = update_leaves.iter().map(|leave| leave.0).collect();
-    let update_merkle_proof = smt.merkle_proof(leaf_keys.clone()).map_err(|e| {
-        error!("Update SMT proof error: {:?}", e.to_string());
-        Error::SMTProofInvalid("Update".to_string())
-    })?;
-    let update_merkle_proof_compiled = update_merkle_proof.compile(leaf_keys).map_err(|e| {
-        error!("Update SMT proof error: {:?}", e.to_string());
-        Error::SMTProofInvalid("Update".to_string())
-    })?;
+        let update_entries = UpdateCotaNFTEntriesBuilder::default()
+            .hold_keys(
+                HoldCotaNFTKeyVecBuilder::default()
+                    .set(batch.hold_keys)
+                    .build(),
+            )
+            .hold_old_values(
+                HoldCotaNFTValueVecBuilder::default()
+                    .set(batch.hold_old_values)
+                    .build(),
+            )
+            .hold_new_values(
+                HoldCotaNFTValueVecBuilder::default()
+                    .set(batch.hold_values)
+                    .build(),
+            )
+            .proof(proof)
+            .action(action_bytes)
+            .build();
+        update_smts.push((root, update_entries));
+    }
 
-    let merkel_proof_vec: Vec<u8> = update_merkle_proof_compiled.into();
-    let merkel_proof_bytes = BytesBuilder::default()
-        .extend(merkel_proof_vec.iter().map(|v| Byte::from(*v)))
-        .build();
+    Ok(update_smts)
+}
 
-    let mut action_vec: Vec<u8> = Vec::new();
-    action_vec.extend("Update NFT information".as_bytes());
-    let action_bytes = BytesBuilder::default()
-        .set(action_vec.iter().map(|v| Byte::from(*v)).collect())
-        .build();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let update_entries = UpdateCotaNFTEntriesBuilder::default()
-        .hold_keys(HoldCotaNFTKeyVecBuilder::default().set(hold_keys).build())
-        .hold_old_values(
-            HoldCotaNFTValueVecBuilder::default()
-                .set(hold_old_values)
-                .build(),
-        )
-        .hold_new_values(
-            HoldCotaNFTValueVecBuilder::default()
-                .set(hold_values)
-                .build(),
-        )
-        .proof(merkel_proof_bytes)
-        .action(action_bytes)
-        .build();
+    #[test]
+    fn test_transform_nft() {
+        let transform = NftTransform {
+            state_set: 0x01,
+            characteristic_set: [0x80; 20],
+            characteristic_clear: [0x01; 20],
+            ..Default::default()
+        };
+        assert_eq!(
+            transform_nft(&transform, 0x02, [0x03; 20]),
+            (0x03, [0x82; 20])
+        );
 
-    Ok((*smt.root(), update_entries))
+        let transform = NftTransform {
+            state_clear: 0x01,
+            characteristic: Some([0x05; 20]),
+            characteristic_clear: [0x04; 20],
+            ..Default::default()
+        };
+        assert_eq!(
+            transform_nft(&transform, 0x03, [0x03; 20]),
+            (0x02, [0x01; 20])
+        );
+    }
 }
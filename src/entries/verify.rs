@@ -0,0 +1,586 @@
+use crate::request::extension::{EXT_ACTION_ADD, EXT_ACTION_UPDATE};
+use crate::utils::error::Error;
+use ckb_types::packed::WitnessArgs;
+use cota_smt::define::DefineCotaNFTEntries;
+use cota_smt::extension::ExtensionEntries;
+use cota_smt::mint::{MintCotaNFTEntries, MintCotaNFTV1Entries};
+use cota_smt::smt::{blake2b_256, Blake2bHasher};
+use cota_smt::transfer::{
+    ClaimCotaNFTEntries, ClaimCotaNFTV2Entries, TransferCotaNFTEntries, TransferCotaNFTV1Entries,
+    TransferCotaNFTV2Entries, WithdrawalCotaNFTEntries, WithdrawalCotaNFTV1Entries,
+};
+use cota_smt::transfer_update::{
+    ClaimUpdateCotaNFTEntries, ClaimUpdateCotaNFTV2Entries, TransferUpdateCotaNFTEntries,
+    TransferUpdateCotaNFTV1Entries, TransferUpdateCotaNFTV2Entries,
+};
+use cota_smt::update::UpdateCotaNFTEntries;
+use molecule::prelude::Entity;
+use sparse_merkle_tree::{CompiledMerkleProof, H256};
+
+const CREATE: u8 = 1;
+const MINT: u8 = 2;
+const WITHDRAW: u8 = 3;
+const CLAIM: u8 = 4;
+const UPDATE: u8 = 5;
+const TRANSFER: u8 = 6;
+const CLAIM_UPDATE: u8 = 7;
+const TRANSFER_UPDATE: u8 = 8;
+
+// SMT key, old value and new value
+type Leaf = (H256, H256, H256);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WitnessVerification {
+    pub action:           String,
+    pub version:          Option<u8>,
+    pub leaves_count:     usize,
+    pub old_root_matched: bool,
+    pub new_root_matched: bool,
+}
+
+impl WitnessVerification {
+    pub fn is_valid(&self) -> bool {
+        self.old_root_matched && self.new_root_matched
+    }
+}
+
+// Recompute the SMT leaves from the witness entries and verify the compiled proof with the
+// old leaves against the old root and the new leaves against the new root, which is the same
+// check as the CoTA type script. The empty SMT is represented by the zero root.
+pub fn verify_cota_witness(
+    witness: &[u8],
+    version: Option<u8>,
+    old_root: Option<[u8; 32]>,
+    new_root: Option<[u8; 32]>,
+) -> Result<WitnessVerification, Error> {
+    let witness_args = WitnessArgs::from_slice(witness)
+        .map_err(|_| Error::WitnessParseInvalid("Parse witness args error".to_string()))?;
+    let input_type = witness_args
+        .input_type()
+        .to_opt()
+        .ok_or(Error::WitnessParseInvalid("Not cota witness".to_string()))?
+        .raw_data();
+    if input_type.is_empty() {
+        return Err(Error::WitnessParseInvalid("Not cota witness".to_string()));
+    }
+    let (tx_type, slice) = (input_type[0], &input_type[1..]);
+    let old_root = H256::from(old_root.unwrap_or_default());
+    let new_root = H256::from(new_root.unwrap_or_default());
+
+    if tx_type == EXT_ACTION_ADD || tx_type == EXT_ACTION_UPDATE {
+        let entries = ExtensionEntries::from_slice(slice).map_err(entries_error)?;
+        let (leaves, proof) = extension_leaves(entries);
+        return Ok(WitnessVerification {
+            action:           "extension".to_owned(),
+            version:          None,
+            leaves_count:     leaves.len(),
+            old_root_matched: verify_leaves(&leaves, &proof, &old_root, true)?,
+            new_root_matched: verify_leaves(&leaves, &proof, &new_root, false)?,
+        });
+    }
+    let action = action_name(tx_type)?;
+    let versions: Vec<u8> = match (version, tx_type) {
+        (Some(version), _) => vec![version],
+        (None, CREATE | UPDATE) => vec![0],
+        (None, TRANSFER | TRANSFER_UPDATE) => vec![2, 1, 0],
+        (None, _) => vec![1, 0],
+    };
+    for version in versions {
+        // The define value is padded after the padding block height, and the old value may be
+        // generated before it, so both paddings are accepted
+        let padded = cota_leaves(tx_type, version, slice, true)?;
+        let unpadded = cota_leaves(tx_type, version, slice, false)?;
+        if let (Some((padded, proof)), Some((unpadded, _))) = (padded, unpadded) {
+            let old_root_matched = verify_leaves(&padded, &proof, &old_root, true)?
+                || verify_leaves(&unpadded, &proof, &old_root, true)?;
+            let new_root_matched = verify_leaves(&padded, &proof, &new_root, false)?
+                || verify_leaves(&unpadded, &proof, &new_root, false)?;
+            return Ok(WitnessVerification {
+                action: action.to_owned(),
+                version: Some(version),
+                leaves_count: padded.len(),
+                old_root_matched,
+                new_root_matched,
+            });
+        }
+    }
+    Err(Error::WitnessParseInvalid(
+        "Parse cota entries error".to_string(),
+    ))
+}
+
+fn verify_leaves(leaves: &[Leaf], proof: &[u8], root: &H256, old: bool) -> Result<bool, Error> {
+    let leaves: Vec<(H256, H256)> = leaves
+        .iter()
+        .map(|(key, old_value, new_value)| (*key, if old { *old_value } else { *new_value }))
+        .collect();
+    CompiledMerkleProof(proof.to_vec())
+        .verify::<Blake2bHasher>(root, leaves)
+        .map_err(|e| Error::SMTProofInvalid(e.to_string()))
+}
+
+fn action_name(tx_type: u8) -> Result<&'static str, Error> {
+    match tx_type {
+        CREATE => Ok("define"),
+        MINT => Ok("mint"),
+        WITHDRAW => Ok("withdraw"),
+        CLAIM => Ok("claim"),
+        UPDATE => Ok("update"),
+        TRANSFER => Ok("transfer"),
+        CLAIM_UPDATE => Ok("claim_update"),
+        TRANSFER_UPDATE => Ok("transfer_update"),
+        _ => Err(Error::WitnessParseInvalid("Not cota witness".to_string())),
+    }
+}
+
+// Returns none when the entries can not be decoded with the layout of the version
+fn cota_leaves(
+    tx_type: u8,
+    version: u8,
+    slice: &[u8],
+    padding: bool,
+) -> Result<Option<(Vec<Leaf>, Vec<u8>)>, Error> {
+    let mut leaves: Vec<Leaf> = vec![];
+    let proof = match (tx_type, version) {
+        (CREATE, _) => {
+            let Ok(entries) = DefineCotaNFTEntries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for (key, value) in entries
+                .define_keys()
+                .into_iter()
+                .zip(entries.define_values())
+            {
+                leaves.push((
+                    padded(key.as_slice()),
+                    H256::zero(),
+                    define_value(value.as_slice(), padding),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (MINT, 0) => {
+            let Ok(entries) = MintCotaNFTEntries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for ((key, old_value), new_value) in entries
+                .define_keys()
+                .into_iter()
+                .zip(entries.define_old_values())
+                .zip(entries.define_new_values())
+            {
+                leaves.push((
+                    padded(key.as_slice()),
+                    define_value(old_value.as_slice(), padding),
+                    define_value(new_value.as_slice(), padding),
+                ));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    padded(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (MINT, _) => {
+            let Ok(entries) = MintCotaNFTV1Entries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for ((key, old_value), new_value) in entries
+                .define_keys()
+                .into_iter()
+                .zip(entries.define_old_values())
+                .zip(entries.define_new_values())
+            {
+                leaves.push((
+                    padded(key.as_slice()),
+                    define_value(old_value.as_slice(), padding),
+                    define_value(new_value.as_slice(), padding),
+                ));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    hashed(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (WITHDRAW, 0) => {
+            let Ok(entries) = WithdrawalCotaNFTEntries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for (key, value) in entries.hold_keys().into_iter().zip(entries.hold_values()) {
+                leaves.push((
+                    padded(key.as_slice()),
+                    hold_value(value.as_slice()),
+                    H256::zero(),
+                ));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    padded(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (WITHDRAW, _) => {
+            let Ok(entries) = WithdrawalCotaNFTV1Entries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for (key, value) in entries.hold_keys().into_iter().zip(entries.hold_values()) {
+                leaves.push((
+                    padded(key.as_slice()),
+                    hold_value(value.as_slice()),
+                    H256::zero(),
+                ));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    hashed(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (CLAIM, 0) => {
+            let Ok(entries) = ClaimCotaNFTEntries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for (key, value) in entries.hold_keys().into_iter().zip(entries.hold_values()) {
+                leaves.push((
+                    padded(key.as_slice()),
+                    H256::zero(),
+                    hold_value(value.as_slice()),
+                ));
+            }
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (CLAIM, _) => {
+            let Ok(entries) = ClaimCotaNFTV2Entries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for (key, value) in entries.hold_keys().into_iter().zip(entries.hold_values()) {
+                leaves.push((
+                    padded(key.as_slice()),
+                    H256::zero(),
+                    hold_value(value.as_slice()),
+                ));
+            }
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (UPDATE, _) => {
+            let Ok(entries) = UpdateCotaNFTEntries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for ((key, old_value), new_value) in entries
+                .hold_keys()
+                .into_iter()
+                .zip(entries.hold_old_values())
+                .zip(entries.hold_new_values())
+            {
+                leaves.push((
+                    padded(key.as_slice()),
+                    hold_value(old_value.as_slice()),
+                    hold_value(new_value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (TRANSFER, 0) => {
+            let Ok(entries) = TransferCotaNFTEntries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    padded(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (TRANSFER, 1) => {
+            let Ok(entries) = TransferCotaNFTV1Entries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    hashed(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (TRANSFER, _) => {
+            let Ok(entries) = TransferCotaNFTV2Entries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    hashed(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (CLAIM_UPDATE, 0) => {
+            let Ok(entries) = ClaimUpdateCotaNFTEntries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for (key, value) in entries.hold_keys().into_iter().zip(entries.hold_values()) {
+                leaves.push((
+                    padded(key.as_slice()),
+                    H256::zero(),
+                    hold_value(value.as_slice()),
+                ));
+            }
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (CLAIM_UPDATE, _) => {
+            let Ok(entries) = ClaimUpdateCotaNFTV2Entries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for (key, value) in entries.hold_keys().into_iter().zip(entries.hold_values()) {
+                leaves.push((
+                    padded(key.as_slice()),
+                    H256::zero(),
+                    hold_value(value.as_slice()),
+                ));
+            }
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (TRANSFER_UPDATE, 0) => {
+            let Ok(entries) = TransferUpdateCotaNFTEntries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    padded(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (TRANSFER_UPDATE, 1) => {
+            let Ok(entries) = TransferUpdateCotaNFTV1Entries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    hashed(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        (TRANSFER_UPDATE, _) => {
+            let Ok(entries) = TransferUpdateCotaNFTV2Entries::from_slice(slice) else {
+                return Ok(None);
+            };
+            for key in entries.claim_keys() {
+                leaves.push((hashed(key.as_slice()), H256::zero(), claimed_value()));
+            }
+            for (key, value) in entries
+                .withdrawal_keys()
+                .into_iter()
+                .zip(entries.withdrawal_values())
+            {
+                leaves.push((
+                    hashed(key.as_slice()),
+                    H256::zero(),
+                    hashed(value.as_slice()),
+                ));
+            }
+            entries.proof().raw_data().to_vec()
+        }
+        _ => return Err(Error::WitnessParseInvalid("Not cota witness".to_string())),
+    };
+    Ok(Some((leaves, proof)))
+}
+
+fn extension_leaves(entries: ExtensionEntries) -> (Vec<Leaf>, Vec<u8>) {
+    let ext_leaves = entries.leaves();
+    let leaves = ext_leaves
+        .keys()
+        .into_iter()
+        .zip(ext_leaves.old_values())
+        .zip(ext_leaves.values())
+        .map(|((key, old_value), value)| {
+            (
+                padded(key.as_slice()),
+                padded(old_value.as_slice()),
+                padded(value.as_slice()),
+            )
+        })
+        .collect();
+    (leaves, ext_leaves.proof().raw_data().to_vec())
+}
+
+fn padded(slice: &[u8]) -> H256 {
+    let mut bytes = [0u8; 32];
+    bytes[0..slice.len()].copy_from_slice(slice);
+    H256::from(bytes)
+}
+
+fn hashed(slice: &[u8]) -> H256 {
+    H256::from(blake2b_256(slice))
+}
+
+fn define_value(slice: &[u8], padding: bool) -> H256 {
+    let mut bytes = [0u8; 32];
+    bytes[0..9].copy_from_slice(slice);
+    if padding || bytes == [0u8; 32] {
+        bytes[31] = 255u8;
+    }
+    H256::from(bytes)
+}
+
+fn hold_value(slice: &[u8]) -> H256 {
+    let mut bytes = [0u8; 32];
+    bytes[0..22].copy_from_slice(slice);
+    bytes[31] = 255u8;
+    H256::from(bytes)
+}
+
+fn claimed_value() -> H256 {
+    H256::from([255u8; 32])
+}
+
+fn entries_error(_e: molecule::error::VerificationError) -> Error {
+    Error::WitnessParseInvalid("Parse extension entries error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entries::helper::{generate_define_key, generate_define_value};
+    use ckb_types::packed::WitnessArgsBuilder;
+    use ckb_types::prelude::Pack;
+    use cota_smt::common::{
+        BytesBuilder, DefineCotaNFTKeyVecBuilder, DefineCotaNFTValueVecBuilder,
+    };
+    use cota_smt::define::DefineCotaNFTEntriesBuilder;
+    use cota_smt::smt::SMT;
+    use molecule::prelude::{Builder, Byte};
+
+    #[test]
+    fn test_verify_define_witness() {
+        let (define_key, key) = generate_define_key([1u8; 20]);
+        let (define_value, value) = generate_define_value([0, 0, 0, 100], [0u8; 4], 0, u64::MAX);
+        let mut smt = SMT::default();
+        smt.update(key, value).unwrap();
+        let proof: Vec<u8> = smt
+            .merkle_proof(vec![key])
+            .unwrap()
+            .compile(vec![key])
+            .unwrap()
+            .into();
+        let entries = DefineCotaNFTEntriesBuilder::default()
+            .define_keys(
+                DefineCotaNFTKeyVecBuilder::default()
+                    .set(vec![define_key])
+                    .build(),
+            )
+            .define_values(
+                DefineCotaNFTValueVecBuilder::default()
+                    .set(vec![define_value])
+                    .build(),
+            )
+            .proof(
+                BytesBuilder::default()
+                    .set(proof.into_iter().map(Byte::from).collect())
+                    .build(),
+            )
+            .build();
+        let mut input_type = vec![CREATE];
+        input_type.extend(entries.as_slice());
+        let witness = WitnessArgsBuilder::default()
+            .input_type(Some(ckb_types::bytes::Bytes::from(input_type)).pack())
+            .build();
+        let root: [u8; 32] = (*smt.root()).into();
+
+        let verification = verify_cota_witness(witness.as_slice(), None, None, Some(root)).unwrap();
+        assert_eq!(verification.action, "define");
+        assert_eq!(verification.version, Some(0));
+        assert!(verification.is_valid());
+
+        let verification =
+            verify_cota_witness(witness.as_slice(), None, None, Some([1u8; 32])).unwrap();
+        assert!(verification.old_root_matched);
+        assert!(!verification.new_root_matched);
+    }
+}
@@ -1,8 +1,10 @@
+use crate::business::estimate::check_batch;
 use crate::ckb::indexer::get_cota_smt_root;
 use crate::entries::helper::{
     generate_empty_value, generate_hold_key, generate_hold_value, generate_withdrawal_key_v1,
     generate_withdrawal_value_v1, with_lock,
 };
+use crate::entries::policy::check_transfer;
 use crate::entries::smt::{generate_history_smt, init_smt};
 use crate::models::hold::get_hold_cota_by_lock_hash;
 use crate::request::withdrawal::WithdrawalReq;
@@ -17,6 +19,11 @@ use cota_smt::transfer::{WithdrawalCotaNFTV1Entries, WithdrawalCotaNFTV1EntriesB
 use log::error;
 use molecule::hex_string;
 
+// The entry bytes of a withdrawal out of its lock script, which are the hold key and value and
+// the withdrawal key and value, and the ones of the action which every withdrawal carries
+const WITHDRAWAL_ENTRY_SIZE: usize = 176;
+const WITHDRAWAL_BASE_SIZE: usize = 256;
+
 pub async fn generate_withdrawal_smt(
     withdrawal_req: WithdrawalReq,
 ) -> Result<(H256, WithdrawalCotaNFTV1Entries), Error> {
@@ -24,6 +31,11 @@ pub async fn generate_withdrawal_smt(
     if withdrawals.is_empty() {
         return Err(Error::RequestParamNotFound("withdrawals".to_string()));
     }
+    let items: Vec<(usize, usize)> = withdrawals
+        .iter()
+        .map(|withdrawal| (WITHDRAWAL_ENTRY_SIZE + withdrawal.to_lock_script.len(), 2))
+        .collect();
+    check_batch(&items, (WITHDRAWAL_BASE_SIZE, 0))?;
     let cota_id_index_pairs: Vec<([u8; 20], [u8; 4])> = withdrawals
         .iter()
         .map(|withdrawal| (withdrawal.cota_id, withdrawal.token_index))
@@ -43,6 +55,12 @@ pub async fn generate_withdrawal_smt(
     let mut update_leaves: Vec<(H256, H256)> = Vec::with_capacity(withdrawals.len() * 2);
     let mut previous_leaves: Vec<(H256, H256)> = Vec::with_capacity(withdrawals.len() * 2);
     for (hold_db, withdrawal) in db_holds.iter().zip(withdrawals.iter()) {
+        check_transfer(
+            hold_db.cota_id,
+            hold_db.token_index,
+            hold_db.configure,
+            hold_db.state,
+        )?;
         let (hold_key, key) = generate_hold_key(hold_db.cota_id, hold_db.token_index);
         let (hold_value, old_value) =
             generate_hold_value(hold_db.configure, hold_db.state, hold_db.characteristic);
@@ -79,6 +97,9 @@ pub async fn generate_withdrawal_smt(
         generate_history_smt(&mut smt, lock_hash, smt_root)?;
         smt.update_all(update_leaves.clone())
             .map_err(|e| Error::SMTInvalid(e.to_string()))?;
+        if withdrawal_req.dry_run {
+            return Ok(());
+        }
         smt.save_root_and_leaves(previous_leaves.clone())?;
         smt.commit()
     })?;
@@ -4,18 +4,33 @@ extern crate diesel;
 extern crate dotenv;
 
 use crate::api::*;
+use crate::auth::middleware::AuthMiddleware;
+use crate::auth::{check_handshake_api_key, ClientInfo, ClientMeta, WsMeta};
+use crate::entries::helper::wait_for_smt_operations;
 use crate::models::helper::init_connection_pool;
+use crate::server::HttpServer;
 use crate::smt::db::db::RocksDB;
+use crate::subscription::add_subscriptions;
+use crate::subscription::poller::start_tip_poller;
+use crate::webhook::poller::start_webhook_poller;
 use dotenv::dotenv;
+use jsonrpc_http_server::jsonrpc_core::middleware::Middleware;
 use jsonrpc_http_server::jsonrpc_core::serde_json::from_str;
-use jsonrpc_http_server::jsonrpc_core::IoHandler;
-use jsonrpc_http_server::ServerBuilder;
+use jsonrpc_http_server::jsonrpc_core::{MetaIoHandler, Metadata};
+use jsonrpc_pubsub::{PubSubHandler, Session};
+use jsonrpc_ws_server::{ws, RequestContext, ServerBuilder as WsServerBuilder};
 use lazy_static::lazy_static;
-use log::info;
+use log::{error, info, warn};
 use models::SqlConnectionPool;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
 
 pub mod api;
+mod audit;
+mod auth;
 mod business;
 mod ckb;
 mod entries;
@@ -23,8 +38,11 @@ mod models;
 mod request;
 mod response;
 pub mod schema;
+mod server;
 mod smt;
+mod subscription;
 mod utils;
+mod webhook;
 
 #[cfg(all(not(target_env = "msvc"), not(target_os = "macos")))]
 #[global_allocator]
@@ -48,13 +66,108 @@ fn main() {
         }));
     }
 
-    let mut io = IoHandler::default();
+    let mut io = MetaIoHandler::with_middleware(AuthMiddleware);
+    register_methods(&mut io);
+
+    let threads: usize = match env::var("THREADS") {
+        Ok(thread) => from_str::<usize>(&thread).unwrap(),
+        Err(_e) => 3,
+    };
+
+    let http_port: u16 = match env::var("HTTP_PORT") {
+        Ok(port) => from_str::<u16>(&port).unwrap(),
+        Err(_e) => 3030,
+    };
+
+    let server = HttpServer::start(
+        io,
+        &format!("0.0.0.0:{}", http_port).parse().unwrap(),
+        threads,
+    );
+
+    let ws_port: u16 = match env::var("WS_PORT") {
+        Ok(port) => from_str::<u16>(&port).unwrap(),
+        Err(_e) => 3031,
+    };
+
+    let mut pubsub_io = PubSubHandler::new(MetaIoHandler::with_middleware(AuthMiddleware));
+    register_methods(&mut pubsub_io);
+    add_subscriptions(&mut pubsub_io);
+
+    let ws_server =
+        WsServerBuilder::with_meta_extractor(pubsub_io, |context: &RequestContext| WsMeta {
+            session: Arc::new(Session::new(context.sender())),
+            client:  ClientMeta {
+                session_id: Some(context.session_id),
+                ..Default::default()
+            },
+        })
+        .request_middleware(|req: &ws::Request| {
+            let api_key = req.header("x-api-key").map(|key| key.as_slice());
+            if check_handshake_api_key(api_key, req.resource()) {
+                None
+            } else {
+                Some(ws::Response::new(
+                    401,
+                    "Unauthorized",
+                    b"Invalid API key".to_vec(),
+                ))
+            }
+        })
+        .start(&format!("0.0.0.0:{}", ws_port).parse().unwrap())
+        .unwrap();
+
+    start_tip_poller();
+    start_webhook_poller();
+
+    let version = env!("CARGO_PKG_VERSION");
+    info!("{}", format!("Cota aggregator v{} server start", version));
+
+    let mut signals = Signals::new([SIGTERM, SIGINT]).expect("Register signal handler error");
+    if let Some(signal) = signals.forever().next() {
+        info!(
+            "Received signal {}, cota aggregator is shutting down",
+            signal
+        );
+    }
+    shutdown(server, ws_server);
+}
+
+fn shutdown(server: HttpServer, ws_server: jsonrpc_ws_server::Server) {
+    server.close();
+    ws_server.close();
+
+    let timeout: u64 = match env::var("SHUTDOWN_TIMEOUT") {
+        Ok(timeout) => from_str::<u64>(&timeout).unwrap(),
+        Err(_e) => 30,
+    };
+    let interrupted = wait_for_smt_operations(Duration::from_secs(timeout));
+    for lock_hash in interrupted.iter() {
+        warn!(
+            "SMT operation of lock hash 0x{} is interrupted by shutdown",
+            hex::encode(lock_hash)
+        );
+    }
+
+    if let Err(err) = ROCKS_DB.flush() {
+        error!("Flush RocksDB error: {}", err.to_msg());
+    }
+    info!("Cota aggregator server stopped");
+}
+
+fn register_methods<T: Metadata + ClientInfo, S: Middleware<T>>(io: &mut MetaIoHandler<T, S>) {
+    io.add_method("generate_register_cota_smt", registry_rpc);
     io.add_method("generate_define_cota_smt", define_rpc);
     io.add_method("generate_mint_cota_smt", mint_rpc);
     io.add_method("generate_claim_cota_smt", claim_rpc);
+    io.add_method("generate_claim_all_cota_smt", claim_all_rpc);
     io.add_method("generate_update_cota_smt", update_rpc);
+    io.add_method("generate_update_by_filter_cota_smt", update_by_filter_rpc);
     io.add_method("generate_transfer_cota_smt", transfer_rpc);
     io.add_method("generate_withdrawal_cota_smt", withdrawal_rpc);
+    io.add_method("generate_burn_cota_smt", burn_rpc);
+    io.add_method("generate_issuer_metadata", issuer_metadata_rpc);
+    io.add_method("generate_class_metadata", class_metadata_rpc);
     io.add_method("generate_claim_update_cota_smt", claim_update_rpc);
     io.add_method("generate_transfer_update_cota_smt", transfer_update_rpc);
     io.add_method(
@@ -66,10 +179,22 @@ fn main() {
     io.add_method("generate_subkey_unlock_smt", subkey_unlock_rpc);
     io.add_method("generate_extension_social_smt", extension_social_rpc);
     io.add_method("generate_social_unlock_smt", social_unlock_rpc);
+    io.add_method("build_define_cota_tx", build_define_tx_rpc);
+    io.add_method("build_mint_cota_tx", build_mint_tx_rpc);
+    io.add_method("build_withdrawal_cota_tx", build_withdrawal_tx_rpc);
+    io.add_method("build_claim_cota_tx", build_claim_tx_rpc);
+    io.add_method("build_update_cota_tx", build_update_tx_rpc);
+    io.add_method("build_transfer_cota_tx", build_transfer_tx_rpc);
+    io.add_method("build_claim_update_cota_tx", build_claim_update_tx_rpc);
+    io.add_method(
+        "build_transfer_update_cota_tx",
+        build_transfer_update_tx_rpc,
+    );
     io.add_method("get_hold_cota_nft", fetch_hold_rpc);
     io.add_method("get_withdrawal_cota_nft", fetch_withdrawal_rpc);
     io.add_method("get_mint_cota_nft", fetch_mint_rpc);
     io.add_method("is_claimed", is_claimed_rpc);
+    io.add_method("is_registered", is_registered_rpc);
     io.add_method("get_cota_nft_sender", get_sender_account);
     io.add_method("get_cota_nft_owner", get_owner_account);
     io.add_method("get_define_info", get_define_info);
@@ -77,24 +202,15 @@ fn main() {
     io.add_method("get_cota_nft_info", get_cota_nft_info);
     io.add_method("get_joyid_info", get_joyid_info);
     io.add_method("parse_witness", parse_witness);
+    io.add_method("parse_cota_transaction", parse_cota_transaction_rpc);
+    io.add_method("verify_cota_witness", verify_cota_witness_rpc);
     io.add_method("get_cota_count", get_cota_count);
     io.add_method("get_history_transactions", get_cota_history_transactions);
     io.add_method("get_transactions_by_block_number", get_txs_by_block_number);
     io.add_method("get_issuer_info_by_cota_id", get_issuer_info_by_cota_id);
     io.add_method("get_aggregator_info", get_aggregator_info);
-
-    let threads: usize = match env::var("THREADS") {
-        Ok(thread) => from_str::<usize>(&thread).unwrap(),
-        Err(_e) => 3,
-    };
-
-    let server = ServerBuilder::new(io)
-        .threads(threads)
-        .start_http(&"0.0.0.0:3030".parse().unwrap())
-        .unwrap();
-
-    let version = env!("CARGO_PKG_VERSION");
-    info!("{}", format!("Cota aggregator v{} server start", version));
-
-    server.wait();
+    io.add_method_with_meta("register_webhook", register_webhook_rpc::<T>);
+    io.add_method_with_meta("remove_webhook", remove_webhook_rpc::<T>);
+    io.add_method_with_meta("get_webhooks", get_webhooks_rpc::<T>);
+    io.add_method("get_audit_logs", get_audit_logs_rpc);
 }
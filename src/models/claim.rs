@@ -57,6 +57,35 @@ pub fn get_claim_cota_by_lock_hash(lock_hash_: [u8; 32]) -> DBResult<ClaimDb> {
     Ok((claims, block_height))
 }
 
+pub fn get_claim_cota_by_lock_hash_and_block_range(
+    lock_hash_: [u8; 32],
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<ClaimDb>, Error> {
+    let start_time = Local::now().timestamp_millis();
+    let (lock_hash_hex, lock_hash_crc_) = parse_lock_hash(lock_hash_);
+    let claims: Vec<ClaimDb> = claimed_cota_nft_kv_pairs
+        .select(get_selection())
+        .filter(lock_hash_crc.eq(lock_hash_crc_))
+        .filter(lock_hash.eq(lock_hash_hex))
+        .filter(block_number.gt(from_block))
+        .filter(block_number.le(to_block))
+        .order(block_number.asc())
+        .load::<ClaimCotaNft>(&get_conn())
+        .map_or_else(
+            |e| {
+                error!("Query claim error: {}", e.to_string());
+                Err(Error::DatabaseQueryInvalid(e.to_string()))
+            },
+            |claims| Ok(parse_claimed_cota_nft(claims)),
+        )?;
+    diff_time(
+        start_time,
+        "SQL get_claim_cota_by_lock_hash_and_block_range",
+    );
+    Ok(claims)
+}
+
 pub fn is_exist_in_claim(
     lock_hash_: [u8; 32],
     cota_id_: [u8; 20],
@@ -1,8 +1,10 @@
+use crate::entries::constants::STATE_BURNED;
 use crate::models::claim::{get_claim_cota_by_lock_hash, is_exist_in_claim, ClaimDb};
 use crate::models::class::{get_class_info_by_cota_id, ClassInfo};
 use crate::models::define::{get_define_cota_by_cota_id, get_define_cota_by_lock_hash, DefineDb};
 use crate::models::hold::{
-    check_hold_cota_by_lock_hash, get_hold_cota_by_lock_hash, get_hold_cota_by_lock_hash_and_page,
+    check_hold_cota_by_lock_hash, get_hold_cota_by_cota_id_and_token_index,
+    get_hold_cota_by_lock_hash, get_hold_cota_by_lock_hash_and_page,
     get_hold_cota_count_by_lock_hash, HoldDb,
 };
 use crate::models::scripts::get_script_id_by_lock_script;
@@ -116,6 +118,11 @@ pub fn check_cota_claimed(
     check_hold_cota_by_lock_hash(blake2b_256(lock_script), (cota_id, index))
 }
 
+pub fn is_cota_nft_burned(cota_id: [u8; 20], token_index: [u8; 4]) -> Result<bool, Error> {
+    let hold_opt = get_hold_cota_by_cota_id_and_token_index(cota_id, token_index)?;
+    Ok(hold_opt.map_or(false, |hold| hold.state & STATE_BURNED != 0))
+}
+
 pub fn get_sender_account_by_cota_nft(
     lock_script: &[u8],
     cota_id: [u8; 20],
@@ -1,5 +1,6 @@
 use super::get_conn;
 use super::helper::{parse_cota_id_index_pairs, parse_lock_hash};
+use crate::entries::constants::STATE_BURNED;
 use crate::models::block::get_syncer_tip_block_number;
 use crate::models::helper::PAGE_SIZE;
 use crate::models::{DBResult, DBTotalResult};
@@ -8,6 +9,9 @@ use crate::schema::hold_cota_nft_kv_pairs::*;
 use crate::utils::error::Error;
 use crate::utils::helper::{diff_time, parse_bytes_n};
 use chrono::prelude::*;
+use diesel::dsl::sql;
+use diesel::expression::SqlLiteral;
+use diesel::sql_types::Bool;
 use diesel::*;
 use log::error;
 use serde::{Deserialize, Serialize};
@@ -96,6 +100,7 @@ pub fn get_hold_cota_by_lock_hash(
     Ok((holds, block_height))
 }
 
+// The burned NFTs stay in the holds with the burned bit of their states, and are not counted
 pub fn get_hold_cota_count_by_lock_hash(
     lock_hash_: [u8; 32],
     cota_id_: [u8; 20],
@@ -103,10 +108,11 @@ pub fn get_hold_cota_count_by_lock_hash(
     let start_time = Local::now().timestamp_millis();
     let (lock_hash_hex, lock_hash_crc_) = parse_lock_hash(lock_hash_);
     let cota_id_str = hex::encode(cota_id_);
-    let hold_count: i64 = hold_cota_nft_kv_pairs
+    let hold_count = hold_cota_nft_kv_pairs
         .filter(lock_hash_crc.eq(lock_hash_crc_))
         .filter(lock_hash.eq(lock_hash_hex))
         .filter(cota_id.eq(cota_id_str))
+        .filter(not_burned())
         .count()
         .get_result::<i64>(&get_conn())
         .map_err(|e| {
@@ -117,6 +123,29 @@ pub fn get_hold_cota_count_by_lock_hash(
     Ok(hold_count)
 }
 
+pub fn get_hold_cota_by_cota_id_and_token_index(
+    cota_id_: [u8; 20],
+    token_index_: [u8; 4],
+) -> Result<Option<HoldDb>, Error> {
+    let start_time = Local::now().timestamp_millis();
+    let holds: Vec<HoldDb> = hold_cota_nft_kv_pairs
+        .select(get_selection())
+        .filter(cota_id.eq(hex::encode(cota_id_)))
+        .filter(token_index.eq(u32::from_be_bytes(token_index_)))
+        .order(updated_at.desc())
+        .limit(1)
+        .load::<HoldCotaNft>(&get_conn())
+        .map_or_else(
+            |e| {
+                error!("Query hold error: {}", e.to_string());
+                Err(Error::DatabaseQueryInvalid(e.to_string()))
+            },
+            |holds| Ok(parse_hold_cota_nfts(holds)),
+        )?;
+    diff_time(start_time, "SQL get_hold_cota_by_cota_id_and_token_index");
+    Ok(holds.first().cloned())
+}
+
 pub fn check_hold_cota_by_lock_hash(
     lock_hash_: [u8; 32],
     cota_id_and_token_index_pair: ([u8; 20], [u8; 4]),
@@ -142,6 +171,7 @@ pub fn check_hold_cota_by_lock_hash(
     Ok((is_exist, block_height))
 }
 
+// The burned NFTs are left out of the pages and the total
 pub fn get_hold_cota_by_lock_hash_and_page(
     lock_hash_: [u8; 32],
     page: i64,
@@ -156,12 +186,14 @@ pub fn get_hold_cota_by_lock_hash_and_page(
             .filter(lock_hash_crc.eq(lock_hash_crc_))
             .filter(lock_hash.eq(lock_hash_hex.clone()))
             .filter(cota_id.eq(hex::encode(cota_id_)))
+            .filter(not_burned())
             .count()
             .get_result::<i64>(conn),
         None => hold_cota_nft_kv_pairs
             .filter(lock_hash_crc.eq(lock_hash_crc_))
             .filter(lock_hash.eq(lock_hash_hex.clone()))
             .filter(cota_id.ne(hex::encode([0u8; 20])))
+            .filter(not_burned())
             .count()
             .get_result::<i64>(conn),
     };
@@ -177,6 +209,7 @@ pub fn get_hold_cota_by_lock_hash_and_page(
             .filter(lock_hash_crc.eq(lock_hash_crc_))
             .filter(lock_hash.eq(lock_hash_hex))
             .filter(cota_id.eq(hex::encode(cota_id_)))
+            .filter(not_burned())
             .order(updated_at.desc())
             .limit(page_size)
             .offset(page_size * page)
@@ -186,6 +219,7 @@ pub fn get_hold_cota_by_lock_hash_and_page(
             .filter(lock_hash_crc.eq(lock_hash_crc_))
             .filter(lock_hash.eq(lock_hash_hex))
             .filter(cota_id.ne(hex::encode([0u8; 20])))
+            .filter(not_burned())
             .order(updated_at.desc())
             .limit(page_size)
             .offset(page_size * page)
@@ -202,6 +236,61 @@ pub fn get_hold_cota_by_lock_hash_and_page(
     Ok((holds, total, block_height))
 }
 
+pub fn get_hold_cota_by_lock_hash_and_block_range(
+    lock_hash_: [u8; 32],
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<HoldDb>, Error> {
+    let start_time = Local::now().timestamp_millis();
+    let (lock_hash_hex, lock_hash_crc_) = parse_lock_hash(lock_hash_);
+    let holds: Vec<HoldDb> = hold_cota_nft_kv_pairs
+        .select(get_selection())
+        .filter(lock_hash_crc.eq(lock_hash_crc_))
+        .filter(lock_hash.eq(lock_hash_hex))
+        .filter(block_number.gt(from_block))
+        .filter(block_number.le(to_block))
+        .order(block_number.asc())
+        .load::<HoldCotaNft>(&get_conn())
+        .map_or_else(
+            |e| {
+                error!("Query hold error: {}", e.to_string());
+                Err(Error::DatabaseQueryInvalid(e.to_string()))
+            },
+            |holds| Ok(parse_hold_cota_nfts(holds)),
+        )?;
+    diff_time(start_time, "SQL get_hold_cota_by_lock_hash_and_block_range");
+    Ok(holds)
+}
+
+// The holds of the class in the inclusive token index range in the order of token index
+pub fn get_hold_cota_by_lock_hash_and_token_range(
+    lock_hash_: [u8; 32],
+    cota_id_: [u8; 20],
+    (token_index_start, token_index_end): (u32, u32),
+) -> Result<Vec<HoldDb>, Error> {
+    let start_time = Local::now().timestamp_millis();
+    let (lock_hash_hex, lock_hash_crc_) = parse_lock_hash(lock_hash_);
+    let mut holds: Vec<HoldDb> = hold_cota_nft_kv_pairs
+        .select(get_selection())
+        .filter(lock_hash_crc.eq(lock_hash_crc_))
+        .filter(lock_hash.eq(lock_hash_hex))
+        .filter(cota_id.eq(hex::encode(cota_id_)))
+        .filter(token_index.ge(token_index_start))
+        .filter(token_index.le(token_index_end))
+        .order((token_index.asc(), updated_at.desc()))
+        .load::<HoldCotaNft>(&get_conn())
+        .map_or_else(
+            |e| {
+                error!("Query hold error: {}", e.to_string());
+                Err(Error::DatabaseQueryInvalid(e.to_string()))
+            },
+            |holds| Ok(parse_hold_cota_nfts(holds)),
+        )?;
+    holds.dedup_by_key(|hold| hold.token_index);
+    diff_time(start_time, "SQL get_hold_cota_by_lock_hash_and_token_range");
+    Ok(holds)
+}
+
 fn parse_hold_cota_nfts(holds: Vec<HoldCotaNft>) -> Vec<HoldDb> {
     holds.into_iter().map(parse_hold_cota_nft).collect()
 }
@@ -219,3 +308,8 @@ fn parse_hold_cota_nft(hold: HoldCotaNft) -> HoldDb {
 fn get_selection() -> (cota_id, token_index, configure, state, characteristic) {
     (cota_id, token_index, configure, state, characteristic)
 }
+
+// MySQL compares the bits of the state with the bitwise AND operator
+fn not_burned() -> SqlLiteral<Bool> {
+    sql::<Bool>(&format!("state & {} = 0", STATE_BURNED))
+}
@@ -0,0 +1,53 @@
+use super::get_conn;
+use crate::models::block::get_syncer_tip_block_number;
+use crate::models::helper::PAGE_SIZE;
+use crate::schema::register_cota_kv_pairs::dsl::register_cota_kv_pairs;
+use crate::schema::register_cota_kv_pairs::*;
+use crate::utils::error::Error;
+use crate::utils::helper::{diff_time, parse_bytes_n};
+use chrono::prelude::*;
+use diesel::*;
+use log::error;
+
+pub fn get_registered_lock_hashes() -> Result<Vec<[u8; 32]>, Error> {
+    let start_time = Local::now().timestamp_millis();
+    let mut page: i64 = 0;
+    let mut lock_hashes: Vec<[u8; 32]> = Vec::new();
+    loop {
+        let lock_hashes_page: Vec<String> = register_cota_kv_pairs
+            .select(lock_hash)
+            .order(id.asc())
+            .limit(PAGE_SIZE)
+            .offset(PAGE_SIZE * page)
+            .load::<String>(&get_conn())
+            .map_err(|e| {
+                error!("Query registry error: {}", e.to_string());
+                Error::DatabaseQueryInvalid(e.to_string())
+            })?;
+        let length = lock_hashes_page.len();
+        for lock_hash_ in lock_hashes_page {
+            lock_hashes.push(parse_bytes_n::<32>(lock_hash_)?);
+        }
+        if length < (PAGE_SIZE as usize) {
+            break;
+        }
+        page += 1;
+    }
+    diff_time(start_time, "SQL get_registered_lock_hashes");
+    Ok(lock_hashes)
+}
+
+pub fn check_registered(lock_hash_: [u8; 32]) -> Result<(bool, u64), Error> {
+    let start_time = Local::now().timestamp_millis();
+    let count = register_cota_kv_pairs
+        .filter(lock_hash.eq(hex::encode(lock_hash_)))
+        .count()
+        .get_result::<i64>(&get_conn())
+        .map_err(|e| {
+            error!("Check registry count error: {:?}", e.to_string());
+            Error::DatabaseQueryInvalid("Registry".to_string())
+        })?;
+    let block_height = get_syncer_tip_block_number()?;
+    diff_time(start_time, "SQL check_registered");
+    Ok((count > 0, block_height))
+}
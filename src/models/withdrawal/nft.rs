@@ -1,3 +1,4 @@
+use crate::entries::constants::CONFIGURE_CLAIM_DISABLED;
 use crate::models::block::get_syncer_tip_block_number;
 use crate::models::helper::{generate_crc, PAGE_SIZE};
 use crate::models::helper::{parse_cota_id_index_pairs, parse_lock_hash};
@@ -8,6 +9,9 @@ use crate::schema::withdraw_cota_nft_kv_pairs::*;
 use crate::utils::error::Error;
 use crate::utils::helper::{diff_time, parse_bytes_n};
 use chrono::prelude::*;
+use cota_smt::smt::blake2b_256;
+use diesel::dsl::sql;
+use diesel::sql_types::{Bool, Char, Integer, Unsigned};
 use diesel::*;
 use log::error;
 use serde::{Deserialize, Serialize};
@@ -195,6 +199,31 @@ pub fn get_withdrawal_cota_by_script_id(
     Ok((withdrawals, total, block_height))
 }
 
+pub fn get_withdrawal_cota_by_script_id_and_block_range(
+    script_id: i64,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<WithdrawNFTDb>, Error> {
+    let start_time = Local::now().timestamp_millis();
+    let withdraw_cota_nfts: Vec<WithdrawCotaNft> = withdraw_cota_nft_kv_pairs
+        .select(get_selection())
+        .filter(receiver_lock_script_id.eq(script_id))
+        .filter(block_number.gt(from_block))
+        .filter(block_number.le(to_block))
+        .order(block_number.asc())
+        .load::<WithdrawCotaNft>(&get_conn())
+        .map_err(|e| {
+            error!("Query withdraw error: {}", e.to_string());
+            Error::DatabaseQueryInvalid(e.to_string())
+        })?;
+    let withdrawals = parse_withdraw_cota_nft(withdraw_cota_nfts);
+    diff_time(
+        start_time,
+        "SQL get_withdrawal_cota_by_script_id_and_block_range",
+    );
+    Ok(withdrawals)
+}
+
 pub fn get_cota_info_by_cota_id_token_index(
     cota_id_: [u8; 20],
     token_index_: [u8; 4],
@@ -255,6 +284,76 @@ pub fn get_sender_lock_by_script_id(
     }
 }
 
+// The withdrawal with the lock hash of its sender, which is withdrawn to the receiver
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct ReceivedWithdrawDb {
+    pub lock_hash:  [u8; 32],
+    pub withdrawal: WithdrawDb,
+}
+
+// The withdrawals to the receiver which have not been claimed by it and whose configure allows
+// claiming, in the order of the withdrawals. The claimed ones are left out by an anti-join
+// against the claims of the receiver, and at most limit withdrawals are returned.
+pub fn get_unclaimed_withdrawals_by_script_id(
+    script_id: i64,
+    receiver_lock_script: &[u8],
+    limit: i64,
+) -> Result<Vec<ReceivedWithdrawDb>, Error> {
+    let start_time = Local::now().timestamp_millis();
+    let (claim_lock_hash_hex, claim_lock_hash_crc) =
+        parse_lock_hash(blake2b_256(receiver_lock_script));
+    let withdrawals: Vec<(String, WithdrawCotaNft)> = withdraw_cota_nft_kv_pairs
+        .select((lock_hash, get_selection()))
+        .filter(receiver_lock_script_id.eq(script_id))
+        .filter(sql::<Bool>(&format!(
+            "configure & {} = 0",
+            CONFIGURE_CLAIM_DISABLED
+        )))
+        .filter(
+            sql::<Bool>(
+                "NOT EXISTS (SELECT 1 FROM claimed_cota_nft_kv_pairs AS claimed \
+                 WHERE claimed.lock_hash_crc = ",
+            )
+            .bind::<Unsigned<Integer>, _>(claim_lock_hash_crc)
+            .sql(" AND claimed.lock_hash = ")
+            .bind::<Char, _>(claim_lock_hash_hex)
+            .sql(
+                " AND claimed.cota_id_crc = withdraw_cota_nft_kv_pairs.cota_id_crc \
+                 AND claimed.cota_id = withdraw_cota_nft_kv_pairs.cota_id \
+                 AND claimed.token_index = withdraw_cota_nft_kv_pairs.token_index \
+                 AND claimed.out_point_crc = withdraw_cota_nft_kv_pairs.out_point_crc \
+                 AND claimed.out_point = withdraw_cota_nft_kv_pairs.out_point)",
+            ),
+        )
+        .order(block_number.asc())
+        .limit(limit)
+        .load::<(String, WithdrawCotaNft)>(&get_conn())
+        .map_err(|e| {
+            error!("Query withdraw error: {}", e.to_string());
+            Error::DatabaseQueryInvalid(e.to_string())
+        })?;
+    let received_withdrawals = withdrawals
+        .into_iter()
+        .map(|(lock_hash_, withdrawal)| ReceivedWithdrawDb {
+            lock_hash:  parse_bytes_n::<32>(lock_hash_).unwrap(),
+            withdrawal: WithdrawDb {
+                cota_id:              parse_bytes_n::<20>(withdrawal.cota_id).unwrap(),
+                token_index:          withdrawal.token_index.to_be_bytes(),
+                configure:            withdrawal.configure,
+                state:                withdrawal.state,
+                characteristic:       parse_bytes_n::<20>(withdrawal.characteristic).unwrap(),
+                receiver_lock_script: receiver_lock_script.to_vec(),
+                out_point:            parse_bytes_n::<24>(withdrawal.out_point).unwrap(),
+                version:              withdrawal.version,
+                block_number:         withdrawal.block_number,
+                tx_hash:              parse_bytes_n::<32>(withdrawal.tx_hash).unwrap(),
+            },
+        })
+        .collect();
+    diff_time(start_time, "SQL get_unclaimed_withdrawals_by_script_id");
+    Ok(received_withdrawals)
+}
+
 pub fn get_receiver_lock_by_cota_id_and_token_index(
     cota_id_: [u8; 20],
     token_index_: [u8; 4],
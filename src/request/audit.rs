@@ -0,0 +1,43 @@
+use super::helper::HexParser;
+use crate::utils::error::Error;
+use cota_smt::smt::blake2b_256;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct AuditLogReq {
+    pub lock_hash: Option<[u8; 32]>,
+    pub smt_root:  Option<[u8; 32]>,
+    pub limit:     usize,
+}
+
+impl AuditLogReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        let lock_hash = match (map.get("lock_script"), map.get("lock_hash")) {
+            (Some(_), _) => Some(blake2b_256(map.get_hex_vec_filed("lock_script")?)),
+            (None, Some(_)) => Some(map.get_hex_bytes_filed::<32>("lock_hash")?),
+            (None, None) => None,
+        };
+        let smt_root = match map.get("smt_root") {
+            Some(_) => Some(map.get_hex_bytes_filed::<32>("smt_root")?),
+            None => None,
+        };
+        if lock_hash.is_none() && smt_root.is_none() {
+            return Err(Error::RequestParamNotFound(
+                "lock_script, lock_hash or smt_root".to_string(),
+            ));
+        }
+        let limit = match map.get("limit") {
+            Some(_) => (map.get_u64_filed("limit")? as usize).min(MAX_LIMIT),
+            None => DEFAULT_LIMIT,
+        };
+        Ok(AuditLogReq {
+            lock_hash,
+            smt_root,
+            limit,
+        })
+    }
+}
@@ -0,0 +1,37 @@
+use super::helper::HexParser;
+use crate::request::helper::{parse_dry_run, parse_vec_map, ReqParser};
+use crate::utils::error::Error;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct BurnNft {
+    pub cota_id:     [u8; 20],
+    pub token_index: [u8; 4],
+}
+
+impl ReqParser for BurnNft {
+    fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        Ok(BurnNft {
+            cota_id:     map.get_hex_bytes_filed::<20>("cota_id")?,
+            token_index: map.get_hex_bytes_filed::<4>("token_index")?,
+        })
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct BurnReq {
+    pub lock_script: Vec<u8>,
+    pub nfts:        Vec<BurnNft>,
+    pub dry_run:     bool,
+}
+
+impl BurnReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        Ok(BurnReq {
+            lock_script: map.get_hex_vec_filed("lock_script")?,
+            nfts:        parse_vec_map::<BurnNft>(map, "nfts")?,
+            dry_run:     parse_dry_run(map)?,
+        })
+    }
+}
@@ -1,38 +1,82 @@
 use super::helper::HexParser;
 use super::update::Nft;
-use crate::request::helper::{parse_vec_map, ReqParser};
+use crate::request::helper::{parse_dry_run, parse_vec_map, ReqParser};
 use crate::utils::error::Error;
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::Value;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct Claim {
-    pub cota_id:     [u8; 20],
-    pub token_index: [u8; 4],
+    pub cota_id:                [u8; 20],
+    pub token_index:            [u8; 4],
+    pub withdrawal_lock_script: Vec<u8>,
 }
 
 impl ReqParser for Claim {
     fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
         Ok(Claim {
-            cota_id:     map.get_hex_bytes_filed::<20>("cota_id")?,
-            token_index: map.get_hex_bytes_filed::<4>("token_index")?,
+            cota_id:                map.get_hex_bytes_filed::<20>("cota_id")?,
+            token_index:            map.get_hex_bytes_filed::<4>("token_index")?,
+            withdrawal_lock_script: map.get_optional_hex_vec_filed("withdrawal_lock_script")?,
         })
     }
 }
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct ClaimReq {
-    pub lock_script:            Vec<u8>,
-    pub withdrawal_lock_script: Vec<u8>,
-    pub claims:                 Vec<Claim>,
+    pub lock_script: Vec<u8>,
+    pub claims:      Vec<Claim>,
+    pub dry_run:     bool,
 }
 
 impl ClaimReq {
     pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        let mut claims = parse_vec_map::<Claim>(map, "claims")?;
+        fill_withdrawal_lock_scripts(
+            map,
+            claims
+                .iter_mut()
+                .map(|claim| &mut claim.withdrawal_lock_script),
+        )?;
         Ok(ClaimReq {
-            lock_script:            map.get_hex_vec_filed("lock_script")?,
-            withdrawal_lock_script: map.get_hex_vec_filed("withdrawal_lock_script")?,
-            claims:                 parse_vec_map::<Claim>(map, "claims")?,
+            lock_script: map.get_hex_vec_filed("lock_script")?,
+            claims,
+            dry_run: parse_dry_run(map)?,
+        })
+    }
+}
+
+// The NFTs of several senders can be claimed together with the withdrawal lock scripts of
+// their own, and the withdrawal lock script of the request is the default one
+fn fill_withdrawal_lock_scripts<'a>(
+    map: &Map<String, Value>,
+    lock_scripts: impl Iterator<Item = &'a mut Vec<u8>>,
+) -> Result<(), Error> {
+    let default_lock_script = map.get_optional_hex_vec_filed("withdrawal_lock_script")?;
+    for lock_script in lock_scripts {
+        if lock_script.is_empty() {
+            if default_lock_script.is_empty() {
+                return Err(Error::RequestParamNotFound(
+                    "withdrawal_lock_script".to_owned(),
+                ));
+            }
+            *lock_script = default_lock_script.clone();
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct ClaimAllReq {
+    pub lock_script: Vec<u8>,
+    pub dry_run:     bool,
+}
+
+impl ClaimAllReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        Ok(ClaimAllReq {
+            lock_script: map.get_hex_vec_filed("lock_script")?,
+            dry_run:     parse_dry_run(map)?,
         })
     }
 }
@@ -55,18 +99,38 @@ impl IsClaimedReq {
 }
 
 #[derive(Clone, Eq, PartialEq)]
-pub struct ClaimUpdateReq {
-    pub lock_script:            Vec<u8>,
+pub struct ClaimUpdateNft {
+    pub nft:                    Nft,
     pub withdrawal_lock_script: Vec<u8>,
-    pub nfts:                   Vec<Nft>,
+}
+
+impl ReqParser for ClaimUpdateNft {
+    fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        Ok(ClaimUpdateNft {
+            nft:                    Nft::from_map(map)?,
+            withdrawal_lock_script: map.get_optional_hex_vec_filed("withdrawal_lock_script")?,
+        })
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct ClaimUpdateReq {
+    pub lock_script: Vec<u8>,
+    pub nfts:        Vec<ClaimUpdateNft>,
+    pub dry_run:     bool,
 }
 
 impl ClaimUpdateReq {
     pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        let mut nfts = parse_vec_map::<ClaimUpdateNft>(map, "nfts")?;
+        fill_withdrawal_lock_scripts(
+            map,
+            nfts.iter_mut().map(|nft| &mut nft.withdrawal_lock_script),
+        )?;
         Ok(ClaimUpdateReq {
-            lock_script:            map.get_hex_vec_filed("lock_script")?,
-            withdrawal_lock_script: map.get_hex_vec_filed("withdrawal_lock_script")?,
-            nfts:                   parse_vec_map::<Nft>(map, "nfts")?,
+            lock_script: map.get_hex_vec_filed("lock_script")?,
+            nfts,
+            dry_run: parse_dry_run(map)?,
         })
     }
 }
@@ -1,15 +1,19 @@
 use super::helper::HexParser;
+use crate::request::helper::parse_dry_run;
 use crate::utils::error::Error;
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::Value;
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct DefineReq {
-    pub lock_script: Vec<u8>,
-    pub cota_id:     [u8; 20],
-    pub total:       [u8; 4],
-    pub issued:      [u8; 4],
-    pub configure:   u8,
+    pub lock_script:           Vec<u8>,
+    pub cota_id:               [u8; 20],
+    pub total:                 [u8; 4],
+    pub issued:                [u8; 4],
+    pub configure:             u8,
+    pub dry_run:               bool,
+    // The molecule OutPoint of the first input of the define transaction
+    pub first_input_out_point: Option<[u8; 36]>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -19,12 +23,18 @@ pub struct DefineInfoReq {
 
 impl DefineReq {
     pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        let first_input_out_point = match map.get("first_input_out_point") {
+            Some(_) => Some(map.get_hex_bytes_filed::<36>("first_input_out_point")?),
+            None => None,
+        };
         Ok(DefineReq {
             lock_script: map.get_hex_vec_filed("lock_script")?,
-            cota_id:     map.get_hex_bytes_filed::<20>("cota_id")?,
-            total:       map.get_hex_bytes_filed::<4>("total")?,
-            issued:      map.get_hex_bytes_filed::<4>("issued")?,
-            configure:   map.get_hex_bytes_filed::<1>("configure")?[0],
+            cota_id: map.get_hex_bytes_filed::<20>("cota_id")?,
+            total: map.get_hex_bytes_filed::<4>("total")?,
+            issued: map.get_hex_bytes_filed::<4>("issued")?,
+            configure: map.get_hex_bytes_filed::<1>("configure")?[0],
+            dry_run: parse_dry_run(map)?,
+            first_input_out_point,
         })
     }
 }
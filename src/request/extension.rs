@@ -1,4 +1,4 @@
-use super::helper::{parse_vec_bytes, parse_vec_map, HexParser, ReqParser};
+use super::helper::{parse_dry_run, parse_vec_bytes, parse_vec_map, HexParser, ReqParser};
 use crate::utils::error::Error;
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::Value;
@@ -29,6 +29,7 @@ pub struct ExtSubkeysReq {
     pub lock_script: Vec<u8>,
     pub ext_action:  u8,
     pub subkeys:     Vec<ExtSubkey>,
+    pub dry_run:     bool,
 }
 
 impl ExtSubkeysReq {
@@ -41,6 +42,7 @@ impl ExtSubkeysReq {
             lock_script: map.get_hex_vec_filed("lock_script")?,
             subkeys: parse_vec_map::<ExtSubkey>(map, "subkeys")?,
             ext_action,
+            dry_run: parse_dry_run(map)?,
         })
     }
 }
@@ -53,6 +55,7 @@ pub struct ExtSocialReq {
     pub must:          u8,
     pub total:         u8,
     pub signers:       Vec<Vec<u8>>,
+    pub dry_run:       bool,
 }
 
 impl ExtSocialReq {
@@ -77,6 +80,7 @@ impl ExtSocialReq {
             must,
             total,
             signers,
+            dry_run: parse_dry_run(map)?,
         })
     }
 }
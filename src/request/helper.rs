@@ -41,6 +41,16 @@ pub fn parse_vec_bytes(map: &Map<String, Value>, key: &str) -> Result<Vec<Vec<u8
     Ok(vec)
 }
 
+// The generated SMT is not saved when dry_run is true, which is false by default
+pub fn parse_dry_run(map: &Map<String, Value>) -> Result<bool, Error> {
+    match map.get("dry_run") {
+        Some(value) => value
+            .as_bool()
+            .ok_or(Error::RequestParamTypeInvalid("dry_run".to_owned())),
+        None => Ok(false),
+    }
+}
+
 pub trait HexParser {
     fn get_hex_bytes_filed<const N: usize>(&self, key: &str) -> Result<[u8; N], Error>;
     fn get_hex_vec_filed(&self, key: &str) -> Result<Vec<u8>, Error>;
@@ -0,0 +1,43 @@
+use crate::request::define::DefineReq;
+use crate::response::witness::info::{ClassInfo, IssuerInfo};
+use crate::utils::error::Error;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+use serde::de::DeserializeOwned;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct IssuerMetadataReq {
+    pub issuer: IssuerInfo,
+}
+
+impl IssuerMetadataReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        Ok(IssuerMetadataReq {
+            issuer: parse_info::<IssuerInfo>(map, "issuer")?,
+        })
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct ClassMetadataReq {
+    pub define: DefineReq,
+    pub class:  ClassInfo,
+}
+
+impl ClassMetadataReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        Ok(ClassMetadataReq {
+            define: DefineReq::from_map(map)?,
+            class:  parse_info::<ClassInfo>(map, "class")?,
+        })
+    }
+}
+
+// The information objects follow the data of the CoTA metadata standard
+fn parse_info<T: DeserializeOwned>(map: &Map<String, Value>, key: &str) -> Result<T, Error> {
+    let value = map
+        .get(key)
+        .ok_or(Error::RequestParamNotFound(key.to_owned()))?;
+    serde_json::from_value::<T>(value.clone())
+        .map_err(|_| Error::RequestParamTypeInvalid(key.to_owned()))
+}
@@ -1,5 +1,5 @@
 use super::helper::HexParser;
-use crate::request::helper::{parse_vec_map, ReqParser};
+use crate::request::helper::{parse_dry_run, parse_vec_map, ReqParser};
 use crate::utils::error::Error;
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::Value;
@@ -29,6 +29,7 @@ pub struct MintReq {
     pub cota_id:     [u8; 20],
     pub out_point:   [u8; 24],
     pub withdrawals: Vec<MintWithdrawal>,
+    pub dry_run:     bool,
 }
 
 impl MintReq {
@@ -38,6 +39,7 @@ impl MintReq {
             cota_id:     map.get_hex_bytes_filed::<20>("cota_id")?,
             out_point:   map.get_hex_bytes_filed::<24>("out_point")?,
             withdrawals: parse_vec_map::<MintWithdrawal>(map, "withdrawals")?,
+            dry_run:     parse_dry_run(map)?,
         })
     }
 }
@@ -1,12 +1,19 @@
+pub(crate) mod audit;
+pub(crate) mod burn;
 pub(crate) mod claim;
 pub(crate) mod define;
 pub(crate) mod extension;
 pub(crate) mod fetch;
 mod helper;
+pub(crate) mod metadata;
 pub(crate) mod mint;
+pub(crate) mod registry;
 pub(crate) mod social;
 pub(crate) mod subkey;
+pub(crate) mod subscription;
+pub(crate) mod transaction;
 pub(crate) mod transfer;
 pub(crate) mod update;
+pub(crate) mod webhook;
 pub(crate) mod withdrawal;
 pub(crate) mod witness;
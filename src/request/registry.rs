@@ -0,0 +1,48 @@
+use super::helper::HexParser;
+use crate::request::helper::{parse_dry_run, parse_vec_bytes};
+use crate::utils::error::Error;
+use crate::utils::helper::parse_vec_n;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct RegistryReq {
+    pub lock_hashes: Vec<[u8; 32]>,
+    pub dry_run:     bool,
+}
+
+impl RegistryReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        let mut lock_hashes: Vec<[u8; 32]> = Vec::new();
+        for lock_hash in parse_vec_bytes(map, "lock_hashes")? {
+            if lock_hash.len() != 32 {
+                return Err(Error::RequestParamHexLenInvalid {
+                    msg:      "lock_hashes".to_owned(),
+                    got:      lock_hash.len(),
+                    expected: 32,
+                });
+            }
+            let lock_hash = parse_vec_n::<32>(lock_hash);
+            if !lock_hashes.contains(&lock_hash) {
+                lock_hashes.push(lock_hash);
+            }
+        }
+        Ok(RegistryReq {
+            lock_hashes,
+            dry_run: parse_dry_run(map)?,
+        })
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct IsRegisteredReq {
+    pub lock_hash: [u8; 32],
+}
+
+impl IsRegisteredReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        Ok(IsRegisteredReq {
+            lock_hash: map.get_hex_bytes_filed::<32>("lock_hash")?,
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use super::helper::HexParser;
+use crate::utils::error::Error;
+use ckb_jsonrpc_types::{OutPoint, Transaction};
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct CotaTransactionReq {
+    pub transaction: Option<Transaction>,
+    pub tx_hash:     Option<[u8; 32]>,
+}
+
+impl CotaTransactionReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        let transaction = match map.get("transaction") {
+            Some(tx) => Some(
+                serde_json::from_value::<Transaction>(tx.clone())
+                    .map_err(|_| Error::RequestParamTypeInvalid("transaction".to_string()))?,
+            ),
+            None => None,
+        };
+        let tx_hash = match map.get("tx_hash") {
+            Some(_) => Some(map.get_hex_bytes_filed::<32>("tx_hash")?),
+            None => None,
+        };
+        if transaction.is_none() == tx_hash.is_none() {
+            return Err(Error::RequestParamTypeInvalid(
+                "transaction or tx_hash".to_string(),
+            ));
+        }
+        Ok(CotaTransactionReq {
+            transaction,
+            tx_hash,
+        })
+    }
+}
+
+// The fee rate is in shannons per 1000 bytes, the same as the min fee rate of CKB node
+pub const DEFAULT_FEE_RATE: u64 = 1000;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct BuildTxReq {
+    pub fee_rate:      u64,
+    // The out point of the capacity cell which pays the fee
+    pub fee_out_point: OutPoint,
+}
+
+impl BuildTxReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        let fee_rate = match map.get("fee_rate") {
+            Some(_) => map.get_u64_filed("fee_rate")?,
+            None => DEFAULT_FEE_RATE,
+        };
+        let fee_out_point = map
+            .get("fee_out_point")
+            .ok_or_else(|| Error::RequestParamNotFound("fee_out_point".to_string()))
+            .and_then(|out_point| {
+                serde_json::from_value::<OutPoint>(out_point.clone())
+                    .map_err(|_| Error::RequestParamTypeInvalid("fee_out_point".to_string()))
+            })?;
+        Ok(BuildTxReq {
+            fee_rate,
+            fee_out_point,
+        })
+    }
+}
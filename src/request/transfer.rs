@@ -1,5 +1,5 @@
 use super::helper::HexParser;
-use crate::request::helper::{parse_vec_map, ReqParser};
+use crate::request::helper::{parse_dry_run, parse_vec_map, ReqParser};
 use crate::request::withdrawal::TransferWithdrawal;
 use crate::utils::error::Error;
 use cota_smt::ckb_types::packed::Script;
@@ -15,6 +15,7 @@ pub struct TransferReq {
     pub withdrawal_lock_hash:   [u8; 32],
     pub transfer_out_point:     [u8; 24],
     pub transfers:              Vec<TransferWithdrawal>,
+    pub dry_run:                bool,
 }
 
 impl TransferReq {
@@ -38,6 +39,7 @@ impl TransferReq {
             withdrawal_lock_hash,
             transfer_out_point: map.get_hex_bytes_filed::<24>("transfer_out_point")?,
             transfers: parse_vec_map::<TransferWithdrawal>(map, "transfers")?,
+            dry_run: parse_dry_run(map)?,
         })
     }
 }
@@ -69,6 +71,7 @@ pub struct TransferUpdateReq {
     pub withdrawal_lock_script: Vec<u8>,
     pub transfer_out_point:     [u8; 24],
     pub transfers:              Vec<TransferUpdate>,
+    pub dry_run:                bool,
 }
 
 impl TransferUpdateReq {
@@ -78,6 +81,7 @@ impl TransferUpdateReq {
             withdrawal_lock_script: map.get_hex_vec_filed("withdrawal_lock_script")?,
             transfer_out_point:     map.get_hex_bytes_filed::<24>("transfer_out_point")?,
             transfers:              parse_vec_map::<TransferUpdate>(map, "transfers")?,
+            dry_run:                parse_dry_run(map)?,
         })
     }
 }
@@ -123,6 +127,7 @@ pub struct SequentialTransferReq {
     pub lock_script: Vec<u8>,
     pub transfers:   Vec<SequentialTransfer>,
     pub subkey:      Option<SubKeyUnlock>,
+    pub dry_run:     bool,
 }
 
 impl SequentialTransferReq {
@@ -131,6 +136,7 @@ impl SequentialTransferReq {
             lock_script: map.get_hex_vec_filed("lock_script")?,
             transfers:   parse_vec_map::<SequentialTransfer>(map, "transfers")?,
             subkey:      None,
+            dry_run:     parse_dry_run(map)?,
         };
         if let Some(subkey) = map.get("subkey") {
             if subkey.as_object().is_none() {
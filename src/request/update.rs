@@ -1,5 +1,5 @@
 use super::helper::HexParser;
-use crate::request::helper::{parse_vec_map, ReqParser};
+use crate::request::helper::{parse_dry_run, parse_vec_map, ReqParser};
 use crate::utils::error::Error;
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::Value;
@@ -27,6 +27,7 @@ impl ReqParser for Nft {
 pub struct UpdateReq {
     pub lock_script: Vec<u8>,
     pub nfts:        Vec<Nft>,
+    pub dry_run:     bool,
 }
 
 impl UpdateReq {
@@ -34,6 +35,87 @@ impl UpdateReq {
         Ok(UpdateReq {
             lock_script: map.get_hex_vec_filed("lock_script")?,
             nfts:        parse_vec_map::<Nft>(map, "nfts")?,
+            dry_run:     parse_dry_run(map)?,
         })
     }
 }
+
+// The transform is applied to the selected NFTs in order: the characteristic is replaced, and then
+// the bits of state and characteristic are set and cleared
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub struct NftTransform {
+    pub state_set:            u8,
+    pub state_clear:          u8,
+    pub characteristic:       Option<[u8; 20]>,
+    pub characteristic_set:   [u8; 20],
+    pub characteristic_clear: [u8; 20],
+}
+
+// The held NFTs of the class in the inclusive token index range are selected, and only the ones
+// whose state bits in match_state_mask equal match_state if the mask is given
+#[derive(Clone, Eq, PartialEq)]
+pub struct UpdateByFilterReq {
+    pub lock_script:       Vec<u8>,
+    pub cota_id:           [u8; 20],
+    pub token_index_start: u32,
+    pub token_index_end:   u32,
+    pub match_state_mask:  u8,
+    pub match_state:       u8,
+    pub transform:         NftTransform,
+    pub dry_run:           bool,
+}
+
+impl UpdateByFilterReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        let transform = NftTransform {
+            state_set:            parse_optional_hex_bytes::<1>(map, "state_set")?.unwrap_or([0])
+                [0],
+            state_clear:          parse_optional_hex_bytes::<1>(map, "state_clear")?.unwrap_or([0])
+                [0],
+            characteristic:       parse_optional_hex_bytes::<20>(map, "characteristic")?,
+            characteristic_set:   parse_optional_hex_bytes::<20>(map, "characteristic_set")?
+                .unwrap_or_default(),
+            characteristic_clear: parse_optional_hex_bytes::<20>(map, "characteristic_clear")?
+                .unwrap_or_default(),
+        };
+        if transform == NftTransform::default() {
+            return Err(Error::RequestParamNotFound(
+                "state_set, state_clear, characteristic, characteristic_set or characteristic_clear"
+                    .to_owned(),
+            ));
+        }
+        let token_index_start =
+            parse_optional_hex_bytes::<4>(map, "token_index_start")?.map_or(0, u32::from_be_bytes);
+        let token_index_end = parse_optional_hex_bytes::<4>(map, "token_index_end")?
+            .map_or(u32::MAX, u32::from_be_bytes);
+        if token_index_start > token_index_end {
+            return Err(Error::TokenIndexRangeInvalid {
+                start: token_index_start,
+                end:   token_index_end,
+            });
+        }
+        let match_state_mask =
+            parse_optional_hex_bytes::<1>(map, "match_state_mask")?.unwrap_or([0])[0];
+        let match_state = parse_optional_hex_bytes::<1>(map, "match_state")?.unwrap_or([0])[0];
+        Ok(UpdateByFilterReq {
+            lock_script: map.get_hex_vec_filed("lock_script")?,
+            cota_id: map.get_hex_bytes_filed::<20>("cota_id")?,
+            token_index_start,
+            token_index_end,
+            match_state_mask,
+            match_state: match_state & match_state_mask,
+            transform,
+            dry_run: parse_dry_run(map)?,
+        })
+    }
+}
+
+fn parse_optional_hex_bytes<const N: usize>(
+    map: &Map<String, Value>,
+    key: &str,
+) -> Result<Option<[u8; N]>, Error> {
+    match map.get(key) {
+        Some(_) => Ok(Some(map.get_hex_bytes_filed::<N>(key)?)),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,60 @@
+use super::helper::HexParser;
+use crate::utils::error::Error;
+use crate::webhook::WebhookEvent;
+use cota_smt::ckb_types::packed::Script;
+use cota_smt::ckb_types::prelude::Entity;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct WebhookReq {
+    pub url:         String,
+    pub secret:      String,
+    pub event:       WebhookEvent,
+    pub lock_script: Option<Vec<u8>>,
+    pub cota_id:     Option<[u8; 20]>,
+}
+
+impl WebhookReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        let url = map.get_str_filed("url")?;
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(Error::RequestParamTypeInvalid("url".to_string()));
+        }
+        let event = match map.get_str_filed("event")?.as_str() {
+            "withdrawal" => WebhookEvent::Withdrawal,
+            "issued" => WebhookEvent::Issued,
+            _ => return Err(Error::RequestParamTypeInvalid("event".to_string())),
+        };
+        let (lock_script, cota_id) = match event {
+            WebhookEvent::Withdrawal => {
+                let lock_script = map.get_hex_vec_filed("lock_script")?;
+                if Script::from_slice(&lock_script).is_err() {
+                    return Err(Error::RequestParamTypeInvalid("Script".to_string()));
+                }
+                (Some(lock_script), None)
+            }
+            WebhookEvent::Issued => (None, Some(map.get_hex_bytes_filed::<20>("cota_id")?)),
+        };
+        Ok(WebhookReq {
+            url,
+            secret: map.get_str_filed("secret")?,
+            event,
+            lock_script,
+            cota_id,
+        })
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct WebhookIdReq {
+    pub webhook_id: u64,
+}
+
+impl WebhookIdReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        Ok(WebhookIdReq {
+            webhook_id: map.get_u64_filed("webhook_id")?,
+        })
+    }
+}
@@ -1,5 +1,5 @@
 use super::helper::HexParser;
-use crate::request::helper::{parse_vec_map, ReqParser};
+use crate::request::helper::{parse_dry_run, parse_vec_map, ReqParser};
 use crate::utils::error::Error;
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::Value;
@@ -26,6 +26,7 @@ pub struct WithdrawalReq {
     pub lock_script: Vec<u8>,
     pub out_point:   [u8; 24],
     pub withdrawals: Vec<TransferWithdrawal>,
+    pub dry_run:     bool,
 }
 
 impl WithdrawalReq {
@@ -34,6 +35,7 @@ impl WithdrawalReq {
             lock_script: map.get_hex_vec_filed("lock_script")?,
             out_point:   map.get_hex_bytes_filed::<24>("out_point")?,
             withdrawals: parse_vec_map::<TransferWithdrawal>(map, "withdrawals")?,
+            dry_run:     parse_dry_run(map)?,
         })
     }
 }
@@ -1,4 +1,5 @@
 use super::helper::HexParser;
+use super::transaction::CotaTransactionReq;
 use crate::utils::error::Error;
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::Value;
@@ -6,14 +7,74 @@ use jsonrpc_http_server::jsonrpc_core::Value;
 #[derive(Clone, Eq, PartialEq)]
 pub struct WitnessReq {
     pub witness: Vec<u8>,
-    pub version: u8,
+    pub version: Option<u8>,
 }
 
 impl WitnessReq {
     pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
         Ok(WitnessReq {
             witness: map.get_hex_vec_filed("witness")?,
-            version: map.get_u8_filed("version")?,
+            version: parse_version(map)?,
+        })
+    }
+}
+
+// The version is taken from the CoTA cell data when it is not given, and it will be detected
+// from the witness when both are absent
+fn parse_version(map: &Map<String, Value>) -> Result<Option<u8>, Error> {
+    if map.get("version").is_some() {
+        return Ok(Some(map.get_u8_filed("version")?));
+    }
+    match map.get("cota_cell_data") {
+        Some(_) => {
+            let cell_data = map.get_hex_vec_filed("cota_cell_data")?;
+            if cell_data.len() != 1 && cell_data.len() != 33 {
+                return Err(Error::RequestParamHexLenInvalid {
+                    msg:      "cota_cell_data".to_owned(),
+                    got:      cell_data.len(),
+                    expected: 33,
+                });
+            }
+            Ok(Some(cell_data[0]))
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct VerifyWitnessReq {
+    pub witness:     Option<Vec<u8>>,
+    pub version:     Option<u8>,
+    pub old_root:    Option<[u8; 32]>,
+    pub new_root:    Option<[u8; 32]>,
+    pub transaction: Option<CotaTransactionReq>,
+}
+
+impl VerifyWitnessReq {
+    pub fn from_map(map: &Map<String, Value>) -> Result<Self, Error> {
+        if map.get("transaction").is_some() || map.get("tx_hash").is_some() {
+            return Ok(VerifyWitnessReq {
+                witness:     None,
+                version:     None,
+                old_root:    None,
+                new_root:    None,
+                transaction: Some(CotaTransactionReq::from_map(map)?),
+            });
+        }
+        let old_root = match map.get("old_smt_root") {
+            Some(_) => Some(map.get_hex_bytes_filed::<32>("old_smt_root")?),
+            None => None,
+        };
+        let new_root = match map.get("new_smt_root") {
+            Some(_) => Some(map.get_hex_bytes_filed::<32>("new_smt_root")?),
+            None => None,
+        };
+        Ok(VerifyWitnessReq {
+            witness: Some(map.get_hex_vec_filed("witness")?),
+            version: parse_version(map)?,
+            old_root,
+            new_root,
+            transaction: None,
         })
     }
 }
@@ -0,0 +1,15 @@
+use crate::audit::AuditRecord;
+use crate::response::helper::{parse_json_err, Inserter};
+use crate::utils::error::Error;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+pub fn parse_audit_logs_response(records: Vec<AuditRecord>) -> Result<Value, Error> {
+    let mut logs: Vec<Value> = Vec::with_capacity(records.len());
+    for record in records {
+        logs.push(serde_json::to_value(record).map_err(parse_json_err)?);
+    }
+    let mut map = Map::new();
+    map.insert_array("logs", logs);
+    Ok(Value::Object(map))
+}
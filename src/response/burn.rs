@@ -0,0 +1,21 @@
+use super::helper::{insert_tx_estimate, Inserter};
+use super::update::estimate_update_tx;
+use ckb_types::prelude::Entity;
+use cota_smt::smt::H256;
+use cota_smt::update::UpdateCotaNFTEntries;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+pub fn parse_burn_smt(
+    (root_hash, burn_entries): (H256, UpdateCotaNFTEntries),
+    block_number: u64,
+) -> Value {
+    let burn_entry = hex::encode(burn_entries.as_slice());
+    let burn_root_hash = hex::encode(root_hash.as_slice());
+    let mut map = Map::new();
+    map.insert_str("smt_root_hash", burn_root_hash);
+    map.insert_str("burn_smt_entry", burn_entry);
+    insert_tx_estimate(&mut map, estimate_update_tx(&burn_entries));
+    map.insert_u64("block_number", block_number);
+    Value::Object(map)
+}
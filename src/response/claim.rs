@@ -1,4 +1,5 @@
-use crate::response::helper::Inserter;
+use crate::business::estimate::TxEstimate;
+use crate::response::helper::{insert_tx_estimate, Inserter};
 use ckb_types::prelude::Entity;
 use cota_smt::smt::H256;
 use cota_smt::transfer::ClaimCotaNFTV2Entries;
@@ -13,32 +14,77 @@ pub fn parse_claimed_response(claimed: bool, block_number: u64) -> Value {
     Value::Object(map)
 }
 
+// The fields of the first batch are kept at the top level, which are all of the claim when the
+// NFTs are withdrawn in one transaction, and the batches are committed in order
 pub fn parse_claimed_smt(
-    (root_hash, claim_entries, block_hash): (H256, ClaimCotaNFTV2Entries, H256),
+    claim_smts: Vec<(H256, ClaimCotaNFTV2Entries, H256)>,
     block_number: u64,
 ) -> Value {
-    let claim_entry = hex::encode(claim_entries.as_slice());
-    let claim_root_hash = hex::encode(root_hash.as_slice());
-    let withdraw_block_hash = hex::encode(block_hash.as_slice());
-    let mut map = Map::new();
-    map.insert_str("smt_root_hash", claim_root_hash);
-    map.insert_str("claim_smt_entry", claim_entry);
-    map.insert_str("withdraw_block_hash", withdraw_block_hash);
-    map.insert_u64("block_number", block_number);
-    Value::Object(map)
+    let batches = claim_smts
+        .into_iter()
+        .map(|(root_hash, claim_entries, block_hash)| {
+            let estimate = TxEstimate::new(
+                claim_entries.as_slice().len(),
+                claim_entries.hold_keys().len() + claim_entries.claim_keys().len() * 2,
+                claim_entries.proof().raw_data().len()
+                    + claim_entries.withdrawal_proof().raw_data().len(),
+            );
+            parse_claim_batch(
+                "claim_smt_entry",
+                root_hash,
+                claim_entries.as_slice(),
+                block_hash,
+                estimate,
+            )
+        })
+        .collect();
+    parse_claim_batches(batches, block_number)
 }
 
 pub fn parse_claimed_update_smt(
-    (root_hash, claim_update_entries, block_hash): (H256, ClaimUpdateCotaNFTV2Entries, H256),
+    claim_update_smts: Vec<(H256, ClaimUpdateCotaNFTV2Entries, H256)>,
     block_number: u64,
 ) -> Value {
-    let claim_update_entry = hex::encode(claim_update_entries.as_slice());
-    let claim_root_hash = hex::encode(root_hash.as_slice());
-    let withdraw_block_hash = hex::encode(block_hash.as_slice());
+    let batches = claim_update_smts
+        .into_iter()
+        .map(|(root_hash, claim_update_entries, block_hash)| {
+            let estimate = TxEstimate::new(
+                claim_update_entries.as_slice().len(),
+                claim_update_entries.hold_keys().len()
+                    + claim_update_entries.claim_keys().len() * 2,
+                claim_update_entries.proof().raw_data().len()
+                    + claim_update_entries.withdrawal_proof().raw_data().len(),
+            );
+            parse_claim_batch(
+                "claim_update_smt_entry",
+                root_hash,
+                claim_update_entries.as_slice(),
+                block_hash,
+                estimate,
+            )
+        })
+        .collect();
+    parse_claim_batches(batches, block_number)
+}
+
+fn parse_claim_batch(
+    entry_key: &str,
+    root_hash: H256,
+    entries: &[u8],
+    block_hash: H256,
+    estimate: TxEstimate,
+) -> Map<String, Value> {
     let mut map = Map::new();
-    map.insert_str("smt_root_hash", claim_root_hash);
-    map.insert_str("claim_update_smt_entry", claim_update_entry);
-    map.insert_str("withdraw_block_hash", withdraw_block_hash);
+    map.insert_str("smt_root_hash", hex::encode(root_hash.as_slice()));
+    map.insert_str(entry_key, hex::encode(entries));
+    map.insert_str("withdraw_block_hash", hex::encode(block_hash.as_slice()));
+    insert_tx_estimate(&mut map, estimate);
+    map
+}
+
+fn parse_claim_batches(batches: Vec<Map<String, Value>>, block_number: u64) -> Value {
+    let mut map = batches.first().cloned().unwrap_or_default();
     map.insert_u64("block_number", block_number);
+    map.insert_obj_vec("batches", batches);
     Value::Object(map)
 }
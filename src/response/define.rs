@@ -1,6 +1,7 @@
+use crate::business::estimate::TxEstimate;
 use crate::models::class::ClassInfo;
 use crate::models::define::DefineDb;
-use crate::response::helper::Inserter;
+use crate::response::helper::{insert_tx_estimate, Inserter};
 use crate::utils::error::Error;
 use ckb_types::prelude::Entity;
 use cota_smt::define::DefineCotaNFTEntries;
@@ -20,6 +21,14 @@ pub fn parse_define_smt(
     let mut map = Map::new();
     map.insert_str("smt_root_hash", define_root_hash);
     map.insert_str("define_smt_entry", define_entry);
+    insert_tx_estimate(
+        &mut map,
+        TxEstimate::new(
+            define_entries.as_slice().len(),
+            define_entries.define_keys().len(),
+            define_entries.proof().raw_data().len(),
+        ),
+    );
     map.insert_u64("block_number", block_number);
     Value::Object(map)
 }
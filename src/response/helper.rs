@@ -1,3 +1,4 @@
+use crate::business::estimate::TxEstimate;
 use crate::utils::error::Error;
 use jsonrpc_http_server::jsonrpc_core::serde_json::{Map, Number};
 use jsonrpc_http_server::jsonrpc_core::Value;
@@ -61,6 +62,12 @@ impl Inserter for Map<String, Value> {
     }
 }
 
+// The serialized witness size and the estimated cycles of the transaction carrying the entries
+pub fn insert_tx_estimate(map: &mut Map<String, Value>, estimate: TxEstimate) {
+    map.insert_u64("witness_size", estimate.witness_size as u64);
+    map.insert_u64("estimated_cycles", estimate.cycles);
+}
+
 pub fn parse_json_err(_err: serde_json::Error) -> Error {
     Error::Other("Json parse error".to_string())
 }
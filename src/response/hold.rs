@@ -25,7 +25,9 @@ pub fn parse_hold_response(
     Ok(Value::Object(map))
 }
 
-fn parse_hold_value((hold, class_info): (HoldDb, Option<ClassInfo>)) -> Result<Value, Error> {
+pub(crate) fn parse_hold_value(
+    (hold, class_info): (HoldDb, Option<ClassInfo>),
+) -> Result<Value, Error> {
     let mut map = Map::new();
     map.insert_hex("cota_id", &hold.cota_id);
     map.insert_hex("token_index", &hold.token_index);
@@ -30,6 +30,7 @@ pub async fn generate_aggregator_info(
 
 pub fn parse_cota_nft_info_response(
     withdrawals: Option<WithdrawNFTDb>,
+    burned: bool,
     block_number: u64,
 ) -> Result<Value, Error> {
     let withdrawal = withdrawals.map_or(WithdrawNFTDb::default(), |nft| nft);
@@ -37,6 +38,7 @@ pub fn parse_cota_nft_info_response(
     map.insert_hex("state", &[withdrawal.state]);
     map.insert_hex("configure", &[withdrawal.configure]);
     map.insert_hex("characteristic", &withdrawal.characteristic);
+    map.insert("burned".to_owned(), Value::Bool(burned));
     map.insert_u64("block_number", block_number);
     Ok(Value::Object(map))
 }
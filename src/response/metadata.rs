@@ -0,0 +1,23 @@
+use super::define::parse_define_smt;
+use super::helper::Inserter;
+use cota_smt::define::DefineCotaNFTEntries;
+use cota_smt::smt::H256;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+pub fn parse_issuer_metadata(output_type: Vec<u8>) -> Value {
+    let mut map = Map::new();
+    map.insert_hex("output_type", &output_type);
+    Value::Object(map)
+}
+
+pub fn parse_class_metadata(
+    (define_smt, output_type): ((H256, DefineCotaNFTEntries), Vec<u8>),
+    block_number: u64,
+) -> Value {
+    let mut define = parse_define_smt(define_smt, block_number);
+    if let Value::Object(map) = &mut define {
+        map.insert_hex("output_type", &output_type);
+    }
+    define
+}
@@ -1,4 +1,5 @@
-use super::helper::{parse_json_err, Inserter};
+use super::helper::{insert_tx_estimate, parse_json_err, Inserter};
+use crate::business::estimate::TxEstimate;
 use crate::models::class::ClassInfo;
 use crate::models::withdrawal::nft::WithdrawDb;
 use crate::utils::error::Error;
@@ -55,6 +56,15 @@ pub fn parse_mint_smt(
     let mut map = Map::new();
     map.insert_str("smt_root_hash", mint_root_hash);
     map.insert_str("mint_smt_entry", mint_entry);
+    insert_tx_estimate(&mut map, estimate_mint_tx(&mint_entries));
     map.insert_u64("block_number", block_number);
     Value::Object(map)
 }
+
+fn estimate_mint_tx(mint_entries: &MintCotaNFTV1Entries) -> TxEstimate {
+    TxEstimate::new(
+        mint_entries.as_slice().len(),
+        mint_entries.define_keys().len() + mint_entries.withdrawal_keys().len(),
+        mint_entries.proof().raw_data().len(),
+    )
+}
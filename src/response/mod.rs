@@ -1,3 +1,5 @@
+pub mod audit;
+pub mod burn;
 pub mod claim;
 pub mod define;
 pub mod extension;
@@ -6,11 +8,16 @@ pub mod hold;
 pub mod info;
 pub mod issuer;
 pub mod joyid_metadata;
+pub mod metadata;
 pub mod mint;
+pub mod registry;
 pub mod social;
 pub mod subkey;
+pub mod subscription;
 pub mod transaction;
 pub mod transfer;
 pub mod update;
+pub mod verify;
+pub mod webhook;
 pub mod withdrawal;
 pub mod witness;
@@ -0,0 +1,35 @@
+use crate::business::estimate::TxEstimate;
+use crate::response::helper::{insert_tx_estimate, Inserter};
+use ckb_types::prelude::Entity;
+use cota_smt::registry::CotaNFTRegistryEntries;
+use cota_smt::smt::H256;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+pub fn parse_registry_smt(
+    (root_hash, registry_entries): (H256, CotaNFTRegistryEntries),
+    block_number: u64,
+) -> Value {
+    let registry_entry = hex::encode(registry_entries.as_slice());
+    let registry_root_hash = hex::encode(root_hash.as_slice());
+    let mut map = Map::new();
+    map.insert_str("smt_root_hash", registry_root_hash);
+    map.insert_str("registry_smt_entry", registry_entry);
+    insert_tx_estimate(
+        &mut map,
+        TxEstimate::new(
+            registry_entries.as_slice().len(),
+            registry_entries.registries().len(),
+            registry_entries.proof().raw_data().len(),
+        ),
+    );
+    map.insert_u64("block_number", block_number);
+    Value::Object(map)
+}
+
+pub fn parse_registered_response(registered: bool, block_number: u64) -> Value {
+    let mut map = Map::new();
+    map.insert("registered".to_string(), Value::Bool(registered));
+    map.insert_u64("block_number", block_number);
+    Value::Object(map)
+}
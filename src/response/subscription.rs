@@ -0,0 +1,53 @@
+use crate::models::claim::ClaimDb;
+use crate::models::class::ClassInfo;
+use crate::models::hold::HoldDb;
+use crate::models::withdrawal::nft::WithdrawNFTDb;
+use crate::response::helper::Inserter;
+use crate::response::hold::parse_hold_value;
+use crate::response::withdrawal::parse_withdrawal_value;
+use crate::utils::error::Error;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+pub fn parse_hold_notification(
+    holds: Vec<(HoldDb, Option<ClassInfo>)>,
+    block_number: u64,
+) -> Result<Value, Error> {
+    let mut nfts: Vec<Value> = Vec::new();
+    for hold in holds {
+        nfts.push(parse_hold_value(hold)?);
+    }
+    Ok(parse_notification(nfts, block_number))
+}
+
+pub fn parse_withdrawal_notification(
+    withdrawals: Vec<(WithdrawNFTDb, Option<ClassInfo>)>,
+    block_number: u64,
+) -> Result<Value, Error> {
+    let mut nfts: Vec<Value> = Vec::new();
+    for withdrawal in withdrawals {
+        nfts.push(parse_withdrawal_value(withdrawal)?);
+    }
+    Ok(parse_notification(nfts, block_number))
+}
+
+pub fn parse_claim_notification(claims: Vec<ClaimDb>, block_number: u64) -> Value {
+    let nfts: Vec<Value> = claims
+        .into_iter()
+        .map(|claim| {
+            let mut map = Map::new();
+            map.insert_hex("cota_id", &claim.cota_id);
+            map.insert_hex("token_index", &claim.token_index);
+            map.insert_hex("out_point", &claim.out_point);
+            Value::Object(map)
+        })
+        .collect();
+    parse_notification(nfts, block_number)
+}
+
+fn parse_notification(nfts: Vec<Value>, block_number: u64) -> Value {
+    let mut map = Map::new();
+    map.insert_u64("block_number", block_number);
+    map.insert_array("nfts", nfts);
+    Value::Object(map)
+}
@@ -1,6 +1,10 @@
+use crate::business::cota_transaction::CotaCellChange;
 use crate::business::transaction::{CotaTransaction, HistoryTransaction};
 use crate::response::helper::Inserter;
+use crate::response::witness::cota::parse_cota_witness;
 use crate::utils::error::Error;
+use ckb_jsonrpc_types::Transaction;
+use cota_smt::smt::H256;
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::Value;
 
@@ -49,3 +53,87 @@ fn parse_transaction(tx: CotaTransaction) -> Result<Value, Error> {
     let tx_map: Map<String, Value> = serde_json::from_str(&tx_json).map_err(parse_json_err)?;
     Ok(Value::Object(tx_map))
 }
+
+pub fn parse_cota_transaction(tx_hash: [u8; 32], changes: Vec<CotaCellChange>) -> Value {
+    let cota_cells: Vec<Value> = changes
+        .into_iter()
+        .map(|change| Value::Object(parse_cota_cell_change(change)))
+        .collect();
+    let mut map = Map::new();
+    map.insert_hex("tx_hash", &tx_hash);
+    map.insert_array("cota_cells", cota_cells);
+    Value::Object(map)
+}
+
+fn parse_cota_cell_change(change: CotaCellChange) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert_hex("lock_script", &change.lock_script);
+    map.insert_hex("lock_hash", &change.lock_hash());
+    insert_opt_u32(&mut map, "input_index", change.input_index);
+    insert_opt_u32(&mut map, "output_index", change.output_index);
+    insert_opt_hex(&mut map, "old_smt_root", change.old_root);
+    insert_opt_hex(&mut map, "new_smt_root", change.new_root);
+    match change.witness {
+        Some(witness) => match parse_cota_witness(witness, change.version) {
+            Ok(witness) => {
+                map.insert("witness".to_owned(), witness);
+            }
+            Err(err) => {
+                map.insert_null("witness");
+                map.insert_str("witness_error", err.to_msg());
+            }
+        },
+        None => {
+            map.insert_null("witness");
+        }
+    }
+    map
+}
+
+fn insert_opt_u32(map: &mut Map<String, Value>, key: &str, value: Option<u32>) {
+    match value {
+        Some(value) => map.insert_u32(key, value),
+        None => map.insert_null(key),
+    };
+}
+
+fn insert_opt_hex(map: &mut Map<String, Value>, key: &str, value: Option<[u8; 32]>) {
+    match value {
+        Some(value) => map.insert_hex(key, &value),
+        None => map.insert_null(key),
+    };
+}
+
+pub fn parse_unsigned_cota_tx(
+    (transaction, fee): (Transaction, u64),
+    smt_root: H256,
+    block_number: u64,
+) -> Result<Value, Error> {
+    let mut map = Map::new();
+    map.insert(
+        "transaction".to_owned(),
+        serde_json::to_value(transaction).map_err(parse_json_err)?,
+    );
+    map.insert_u64("fee", fee);
+    map.insert_str("smt_root_hash", hex::encode(smt_root.as_slice()));
+    map.insert_u64("block_number", block_number);
+    Ok(Value::Object(map))
+}
+
+// The fields of the first transaction are kept at the top level, and the transactions are
+// chained in order so that they must be signed and sent one after another
+pub fn parse_unsigned_cota_txs(
+    txs: Vec<((Transaction, u64), H256)>,
+    block_number: u64,
+) -> Result<Value, Error> {
+    let txs = txs
+        .into_iter()
+        .map(|(tx, smt_root)| parse_unsigned_cota_tx(tx, smt_root, block_number))
+        .collect::<Result<Vec<Value>, Error>>()?;
+    let mut map = match txs.first() {
+        Some(Value::Object(map)) => map.clone(),
+        _ => Map::new(),
+    };
+    map.insert_array("batches", txs);
+    Ok(Value::Object(map))
+}
@@ -1,5 +1,6 @@
+use crate::business::estimate::TxEstimate;
 use crate::entries::sequential_transfer::SequentialTransferResult;
-use crate::response::helper::Inserter;
+use crate::response::helper::{insert_tx_estimate, Inserter};
 use ckb_types::prelude::Entity;
 use cota_smt::smt::H256;
 use cota_smt::transfer::TransferCotaNFTV2Entries;
@@ -18,6 +19,7 @@ pub fn parse_transfer_smt(
     map.insert_str("smt_root_hash", transfer_root_hash);
     map.insert_str("transfer_smt_entry", transfer_entry);
     map.insert_str("withdraw_block_hash", withdraw_block_hash);
+    insert_tx_estimate(&mut map, estimate_transfer_tx(&transfer_entries));
     map.insert_u64("block_number", block_number);
     Value::Object(map)
 }
@@ -33,6 +35,16 @@ pub fn parse_transfer_update_smt(
     map.insert_str("smt_root_hash", transfer_root_hash);
     map.insert_str("transfer_update_smt_entry", transfer_update_entry);
     map.insert_str("withdraw_block_hash", withdraw_block_hash);
+    insert_tx_estimate(
+        &mut map,
+        TxEstimate::new(
+            transfer_update_entries.as_slice().len(),
+            transfer_update_entries.claim_keys().len() * 2
+                + transfer_update_entries.withdrawal_keys().len(),
+            transfer_update_entries.proof().raw_data().len()
+                + transfer_update_entries.withdrawal_proof().raw_data().len(),
+        ),
+    );
     map.insert_u64("block_number", block_number);
     Value::Object(map)
 }
@@ -48,6 +60,7 @@ pub fn parse_sequential_transfer_smt(
     map.insert_str("smt_root_hash", transfer_root_hash);
     map.insert_str("transfer_smt_entry", transfer_entry);
     map.insert_str("withdraw_block_hash", withdraw_block_hash);
+    insert_tx_estimate(&mut map, estimate_transfer_tx(&transfer_entries));
     map.insert_u64("block_number", block_number);
     if let Some(subkey_unlock) = current_subkey_entries {
         map.insert_str("subkey_unlock_entry", hex::encode(subkey_unlock.as_slice()));
@@ -60,3 +73,14 @@ pub fn parse_sequential_transfer_smt(
     }
     Value::Object(map)
 }
+
+// The claim leaves of the receiver are verified with the withdrawal leaves of the sender, so the
+// withdrawal proof is counted as well
+fn estimate_transfer_tx(transfer_entries: &TransferCotaNFTV2Entries) -> TxEstimate {
+    TxEstimate::new(
+        transfer_entries.as_slice().len(),
+        transfer_entries.claim_keys().len() * 2 + transfer_entries.withdrawal_keys().len(),
+        transfer_entries.proof().raw_data().len()
+            + transfer_entries.withdrawal_proof().raw_data().len(),
+    )
+}
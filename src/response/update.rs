@@ -1,4 +1,5 @@
-use super::helper::Inserter;
+use super::helper::{insert_tx_estimate, Inserter};
+use crate::business::estimate::TxEstimate;
 use ckb_types::prelude::Entity;
 use cota_smt::smt::H256;
 use cota_smt::update::UpdateCotaNFTEntries;
@@ -14,6 +15,37 @@ pub fn parse_update_smt(
     let mut map = Map::new();
     map.insert_str("smt_root_hash", update_root_hash);
     map.insert_str("update_smt_entry", update_entry);
+    insert_tx_estimate(&mut map, estimate_update_tx(&update_entries));
     map.insert_u64("block_number", block_number);
     Value::Object(map)
 }
+
+// The fields of the first batch are kept at the top level, the same as the claim batches
+pub fn parse_update_batches(
+    update_smts: Vec<(H256, UpdateCotaNFTEntries)>,
+    block_number: u64,
+) -> Value {
+    let batches: Vec<Map<String, Value>> = update_smts
+        .into_iter()
+        .map(|(root_hash, update_entries)| {
+            let mut map = Map::new();
+            map.insert_str("smt_root_hash", hex::encode(root_hash.as_slice()));
+            map.insert_str("update_smt_entry", hex::encode(update_entries.as_slice()));
+            insert_tx_estimate(&mut map, estimate_update_tx(&update_entries));
+            map
+        })
+        .collect();
+    let mut map = batches.first().cloned().unwrap_or_default();
+    map.insert_u64("block_number", block_number);
+    map.insert_obj_vec("batches", batches);
+    Value::Object(map)
+}
+
+// The burn entries are the update entries as well
+pub fn estimate_update_tx(update_entries: &UpdateCotaNFTEntries) -> TxEstimate {
+    TxEstimate::new(
+        update_entries.as_slice().len(),
+        update_entries.hold_keys().len(),
+        update_entries.proof().raw_data().len(),
+    )
+}
@@ -0,0 +1,64 @@
+use crate::business::cota_transaction::CotaCellChange;
+use crate::entries::verify::WitnessVerification;
+use crate::response::helper::Inserter;
+use crate::utils::error::Error;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+pub fn parse_witness_verification(verification: WitnessVerification) -> Value {
+    Value::Object(parse_verification(verification))
+}
+
+pub fn parse_transaction_verification(
+    tx_hash: [u8; 32],
+    verifications: Vec<(CotaCellChange, Result<WitnessVerification, Error>)>,
+) -> Value {
+    let cota_cells: Vec<Value> = verifications
+        .into_iter()
+        .map(|(change, verification)| {
+            let mut map = match verification {
+                Ok(verification) => parse_verification(verification),
+                Err(err) => {
+                    let mut map = Map::new();
+                    map.insert("valid".to_owned(), Value::Bool(false));
+                    map.insert_str("error", err.to_msg());
+                    map
+                }
+            };
+            map.insert_hex("lock_hash", &change.lock_hash());
+            match change.input_index {
+                Some(index) => map.insert_u32("input_index", index),
+                None => map.insert_null("input_index"),
+            };
+            match change.output_index {
+                Some(index) => map.insert_u32("output_index", index),
+                None => map.insert_null("output_index"),
+            };
+            Value::Object(map)
+        })
+        .collect();
+    let mut map = Map::new();
+    map.insert_hex("tx_hash", &tx_hash);
+    map.insert_array("cota_cells", cota_cells);
+    Value::Object(map)
+}
+
+fn parse_verification(verification: WitnessVerification) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert("valid".to_owned(), Value::Bool(verification.is_valid()));
+    map.insert_str("action", verification.action);
+    match verification.version {
+        Some(version) => map.insert_u8("version", version),
+        None => map.insert_null("version"),
+    };
+    map.insert_u64("leaves_count", verification.leaves_count as u64);
+    map.insert(
+        "old_root_matched".to_owned(),
+        Value::Bool(verification.old_root_matched),
+    );
+    map.insert(
+        "new_root_matched".to_owned(),
+        Value::Bool(verification.new_root_matched),
+    );
+    map
+}
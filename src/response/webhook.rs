@@ -0,0 +1,59 @@
+use crate::models::define::DefineDb;
+use crate::response::helper::Inserter;
+use crate::webhook::Webhook;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+
+pub fn parse_webhook_response(webhook: Webhook) -> Value {
+    Value::Object(parse_webhook(webhook))
+}
+
+pub fn parse_webhooks_response(webhooks: Vec<Webhook>) -> Value {
+    let mut map = Map::new();
+    map.insert_obj_vec(
+        "webhooks",
+        webhooks.into_iter().map(parse_webhook).collect(),
+    );
+    Value::Object(map)
+}
+
+pub fn parse_webhook_payload(webhook: &Webhook, data: Value, block_number: u64) -> Value {
+    let mut map = Map::new();
+    map.insert_u64("webhook_id", webhook.id);
+    map.insert_str("event", webhook.event.as_str().to_string());
+    map.insert_u64("block_number", block_number);
+    map.insert("data".to_string(), data);
+    Value::Object(map)
+}
+
+pub fn parse_issued_payload(
+    cota_id: [u8; 20],
+    define: DefineDb,
+    previous_issued: Option<u32>,
+) -> Value {
+    let mut map = Map::new();
+    map.insert_hex("cota_id", &cota_id);
+    map.insert_u32("total", define.total);
+    map.insert_u32("issued", define.issued);
+    match previous_issued {
+        Some(issued) => map.insert_u32("previous_issued", issued),
+        None => map.insert_null("previous_issued"),
+    };
+    Value::Object(map)
+}
+
+fn parse_webhook(webhook: Webhook) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert_u64("webhook_id", webhook.id);
+    map.insert_str("url", webhook.url);
+    map.insert_str("event", webhook.event.as_str().to_string());
+    match webhook.lock_script {
+        Some(lock_script) => map.insert_hex("lock_script", &lock_script),
+        None => map.insert_null("lock_script"),
+    };
+    match webhook.cota_id {
+        Some(cota_id) => map.insert_hex("cota_id", &cota_id),
+        None => map.insert_null("cota_id"),
+    };
+    map
+}
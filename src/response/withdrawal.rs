@@ -1,7 +1,8 @@
+use crate::business::estimate::TxEstimate;
 use crate::business::helper::address_from_script;
 use crate::models::class::ClassInfo;
 use crate::models::withdrawal::nft::WithdrawNFTDb;
-use crate::response::helper::Inserter;
+use crate::response::helper::{insert_tx_estimate, Inserter};
 use crate::utils::error::Error;
 use ckb_types::prelude::Entity;
 use cota_smt::smt::H256;
@@ -29,7 +30,7 @@ pub fn parse_withdrawal_response(
     Ok(Value::Object(map))
 }
 
-fn parse_withdrawal_value(
+pub(crate) fn parse_withdrawal_value(
     (withdrawal, class_info): (WithdrawNFTDb, Option<ClassInfo>),
 ) -> Result<Value, Error> {
     let mut map = Map::new();
@@ -57,6 +58,14 @@ pub fn parse_withdrawal_smt(
     let mut map = Map::new();
     map.insert_str("smt_root_hash", withdrawal_root_hash);
     map.insert_str("withdrawal_smt_entry", withdrawal_entry);
+    insert_tx_estimate(
+        &mut map,
+        TxEstimate::new(
+            withdrawal_entries.as_slice().len(),
+            withdrawal_entries.hold_keys().len() + withdrawal_entries.withdrawal_keys().len(),
+            withdrawal_entries.proof().raw_data().len(),
+        ),
+    );
     map.insert_u64("block_number", block_number);
     Value::Object(map)
 }
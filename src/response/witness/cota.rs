@@ -1,4 +1,5 @@
 use super::info::{ClassInfo, IssuerInfo};
+use super::joyid::{parse_extension, parse_social_unlock, parse_subkey_unlock};
 use super::parser::{
     parse_claim, parse_claim_update, parse_define, parse_mint, parse_mint_v1, parse_transfer,
     parse_transfer_update, parse_transfer_update_v1, parse_transfer_v1, parse_update,
@@ -15,6 +16,7 @@ use ckb_types::bytes::Bytes;
 use ckb_types::packed::{BytesOpt, WitnessArgs};
 use ckb_types::prelude::Unpack;
 use cota_smt::define::DefineCotaNFTEntries;
+use cota_smt::extension::ExtensionEntries;
 use cota_smt::mint::{MintCotaNFTEntries, MintCotaNFTV1Entries};
 use cota_smt::transfer::{
     ClaimCotaNFTEntries, ClaimCotaNFTV2Entries, TransferCotaNFTEntries, TransferCotaNFTV1Entries,
@@ -25,6 +27,7 @@ use cota_smt::transfer_update::{
     TransferUpdateCotaNFTV1Entries, TransferUpdateCotaNFTV2Entries,
 };
 use cota_smt::update::UpdateCotaNFTEntries;
+use joyid_smt::joyid::{SocialUnlockEntries, SubKeyUnlockEntries};
 use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
 use jsonrpc_http_server::jsonrpc_core::Value;
 use molecule::error::VerificationError;
@@ -33,8 +36,10 @@ use serde_json::{from_slice, json};
 
 type CotaMap = Map<String, Value>;
 
-pub fn parse_cota_witness(witness: Vec<u8>, version: u8) -> Result<Value, Error> {
-    if version > 2 {
+// The witness is decoded with the given version, otherwise every compatible entries layout
+// of the action is verified strictly and the first one decoded is taken as the version
+pub fn parse_cota_witness(witness: Vec<u8>, version: Option<u8>) -> Result<Value, Error> {
+    if version.map_or(false, |version| version > 2) {
         return Err(Error::WitnessParseInvalid("Version invalid".to_string()));
     }
     let witness_args = WitnessArgs::from_slice(&witness)
@@ -55,92 +60,135 @@ const UPDATE: u8 = 5;
 const TRANSFER: u8 = 6;
 const CLAIM_UPDATE: u8 = 7;
 const TRANSFER_UPDATE: u8 = 8;
-fn parse_cota(input_type: BytesOpt, version: u8, mut cota_map: CotaMap) -> Result<CotaMap, Error> {
+fn parse_cota(
+    input_type: BytesOpt,
+    version: Option<u8>,
+    mut cota_map: CotaMap,
+) -> Result<CotaMap, Error> {
     match input_type.to_opt() {
         Some(input_type_) => {
             let input_type: Bytes = input_type_.unpack();
             let (tx_type, slice) = (input_type[0], &input_type[1..]);
             if tx_type == EXT_ACTION_ADD || tx_type == EXT_ACTION_UPDATE {
+                let entries = ExtensionEntries::from_slice(slice).map_err(entries_error)?;
+                cota_map.insert_obj("extension", parse_extension(entries, tx_type)?);
                 return Ok(cota_map);
             }
             if tx_type > TRANSFER_UPDATE || tx_type == 0 {
                 return Err(Error::WitnessParseInvalid("Not cota witness".to_string()));
             }
-            let cota_entries: Map<String, Value> = match tx_type {
-                CREATE => {
-                    parse_define(DefineCotaNFTEntries::from_slice(slice).map_err(entries_error)?)
-                }
-                MINT => match version {
-                    0 => parse_mint(MintCotaNFTEntries::from_slice(slice).map_err(entries_error)?),
-                    _ => parse_mint_v1(
-                        MintCotaNFTV1Entries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                },
-                WITHDRAW => match version {
-                    0 => parse_withdrawal(
-                        WithdrawalCotaNFTEntries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                    _ => parse_withdrawal_v1(
-                        WithdrawalCotaNFTV1Entries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                },
-                CLAIM => match version {
-                    0 => {
-                        parse_claim(ClaimCotaNFTEntries::from_slice(slice).map_err(entries_error)?)
-                    }
-                    _ => parse_claim_v2(
-                        ClaimCotaNFTV2Entries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                },
-                UPDATE => {
-                    parse_update(UpdateCotaNFTEntries::from_slice(slice).map_err(entries_error)?)
-                }
-                TRANSFER => match version {
-                    0 => parse_transfer(
-                        TransferCotaNFTEntries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                    1 => parse_transfer_v1(
-                        TransferCotaNFTV1Entries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                    _ => parse_transfer_v2(
-                        TransferCotaNFTV2Entries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                },
-                CLAIM_UPDATE => match version {
-                    0 => parse_claim_update(
-                        ClaimUpdateCotaNFTEntries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                    _ => parse_claim_update_v2(
-                        ClaimUpdateCotaNFTV2Entries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                },
-                _ => match version {
-                    0 => parse_transfer_update(
-                        TransferUpdateCotaNFTEntries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                    1 => parse_transfer_update_v1(
-                        TransferUpdateCotaNFTV1Entries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                    _ => parse_transfer_update_v2(
-                        TransferUpdateCotaNFTV2Entries::from_slice(slice).map_err(entries_error)?,
-                    ),
-                },
+            let (cota_entries, version) = match version {
+                Some(version) => (parse_cota_entries(tx_type, version, slice)?, version),
+                None => detect_cota_entries(tx_type, slice)?,
             };
             cota_map.insert_obj("cota", cota_entries);
+            cota_map.insert_u8("version", version);
         }
         None => {
             cota_map.insert_null("cota");
+            match version {
+                Some(version) => cota_map.insert_u8("version", version),
+                None => cota_map.insert_null("version"),
+            };
         }
     };
     Ok(cota_map)
 }
 
+// The versions which introduced a new entries layout of the action, and the entries of the
+// later versions share the layout of the last introduced one
+fn layout_versions(tx_type: u8) -> &'static [u8] {
+    match tx_type {
+        CREATE | UPDATE => &[0],
+        MINT | WITHDRAW | CLAIM | CLAIM_UPDATE => &[0, 1],
+        _ => &[0, 1, 2],
+    }
+}
+
+fn detect_cota_entries(tx_type: u8, slice: &[u8]) -> Result<(CotaMap, u8), Error> {
+    layout_versions(tx_type)
+        .iter()
+        .rev()
+        .find_map(|version| {
+            parse_cota_entries(tx_type, *version, slice)
+                .ok()
+                .map(|entries| (entries, *version))
+        })
+        .ok_or_else(|| {
+            Error::WitnessParseInvalid("No entries layout of any version matched".to_string())
+        })
+}
+
+fn parse_cota_entries(tx_type: u8, version: u8, slice: &[u8]) -> Result<CotaMap, Error> {
+    let cota_entries = match tx_type {
+        CREATE => parse_define(DefineCotaNFTEntries::from_slice(slice).map_err(entries_error)?),
+        MINT => match version {
+            0 => parse_mint(MintCotaNFTEntries::from_slice(slice).map_err(entries_error)?),
+            _ => parse_mint_v1(MintCotaNFTV1Entries::from_slice(slice).map_err(entries_error)?),
+        },
+        WITHDRAW => match version {
+            0 => parse_withdrawal(
+                WithdrawalCotaNFTEntries::from_slice(slice).map_err(entries_error)?,
+            ),
+            _ => parse_withdrawal_v1(
+                WithdrawalCotaNFTV1Entries::from_slice(slice).map_err(entries_error)?,
+            ),
+        },
+        CLAIM => match version {
+            0 => parse_claim(ClaimCotaNFTEntries::from_slice(slice).map_err(entries_error)?),
+            _ => parse_claim_v2(ClaimCotaNFTV2Entries::from_slice(slice).map_err(entries_error)?),
+        },
+        UPDATE => parse_update(UpdateCotaNFTEntries::from_slice(slice).map_err(entries_error)?),
+        TRANSFER => match version {
+            0 => parse_transfer(TransferCotaNFTEntries::from_slice(slice).map_err(entries_error)?),
+            1 => parse_transfer_v1(
+                TransferCotaNFTV1Entries::from_slice(slice).map_err(entries_error)?,
+            ),
+            _ => parse_transfer_v2(
+                TransferCotaNFTV2Entries::from_slice(slice).map_err(entries_error)?,
+            ),
+        },
+        CLAIM_UPDATE => match version {
+            0 => parse_claim_update(
+                ClaimUpdateCotaNFTEntries::from_slice(slice).map_err(entries_error)?,
+            ),
+            _ => parse_claim_update_v2(
+                ClaimUpdateCotaNFTV2Entries::from_slice(slice).map_err(entries_error)?,
+            ),
+        },
+        _ => match version {
+            0 => parse_transfer_update(
+                TransferUpdateCotaNFTEntries::from_slice(slice).map_err(entries_error)?,
+            ),
+            1 => parse_transfer_update_v1(
+                TransferUpdateCotaNFTV1Entries::from_slice(slice).map_err(entries_error)?,
+            ),
+            _ => parse_transfer_update_v2(
+                TransferUpdateCotaNFTV2Entries::from_slice(slice).map_err(entries_error)?,
+            ),
+        },
+    };
+    Ok(cota_entries)
+}
+
 fn entries_error(_e: VerificationError) -> Error {
     Error::WitnessParseInvalid("Parse cota entries error".to_string())
 }
 
 fn parse_metadata(output_type: BytesOpt, mut cota_map: CotaMap) -> Result<CotaMap, Error> {
     if let Some(output_type_) = output_type.to_opt() {
+        // The subkey and social unlock entries of JoyID lock are put into output_type
+        let raw_data = output_type_.raw_data();
+        if let Ok(entries) = SubKeyUnlockEntries::from_slice(&raw_data) {
+            cota_map.insert_obj("unlock", parse_subkey_unlock(entries));
+            cota_map.insert_null("info");
+            return Ok(cota_map);
+        }
+        if let Ok(entries) = SocialUnlockEntries::from_slice(&raw_data) {
+            cota_map.insert_obj("unlock", parse_social_unlock(entries));
+            cota_map.insert_null("info");
+            return Ok(cota_map);
+        }
         if let Ok(metadata) = from_slice::<Metadata<IssuerInfo>>(&output_type_.raw_data()) {
             if metadata.metadata.type_ == "issuer" {
                 cota_map.insert("info".to_owned(), json!(metadata.metadata.data));
@@ -167,3 +215,20 @@ fn parse_metadata(output_type: BytesOpt, mut cota_map: CotaMap) -> Result<CotaMa
 fn json_error(e: serde_json::Error) -> Error {
     Error::WitnessParseInvalid(format!("Parse metadata json error: {}", e.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLAIM_WITNESS: &str = "8107000010000000550000008107000041000000f6b9acf8267464b7aaa7729c26fcf4ce1ff16582b4bb40ea122498b8d89de2ef67e8ee44517ce204681f1ed4435d147db2b6ce345731791311ae7246987a67df00280700000227070000200000003a0000004700000054000000bc000000a6010000230700000100000081001e23dc506c1b15f286c9db84a4d12a453266097501000000000000640000006200010000000000006400000064000200000081021e23dc506c1b15f286c9db84a4d12a453266097500000062dc6fb71f2caea8949e8a7a88f53ed031589ed65d0000000081021e23dc506c1b15f286c9db84a4d12a453266097500000063dc6fb71f2caea8949e8a7a88f53ed031589ed65d00000000ea0000000c0000007b0000006f0000000c000000220000000000050505050505050505050505050505050505050549000000490000001000000030000000310000009bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce80114000000dc70f33de86fdf381b4fc5bf092bb23d027748016f0000000c000000220000000000050505050505050505050505050505050505050549000000490000001000000030000000310000009bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce80114000000dc70f33de86fdf381b4fc5bf092bb23d02774801790500004c4fa95091a4c55d156aa9299b3094733123a9ccbdf20a4ca4ea5414e0212bf6202c2dfc50edfd0724f0e61e58169515abe0ad9d58622b6c11cb8e6538333190672f6de23c51ab643619549f2d589d230daf6feebcce7385fc4efbee4499523133294ceaa7dbf68100e477dc008e244d26dc87a97f6bda123292ff0a0000000000000000000000504ba8433d54e0b8940b1f58a7ae1f58c0b360eccac36a67f6fdc5738c5ce3e3eb5011238f883e0519d2627e7f8ae405ade0ad769ab84afe70520f9c7d62d26cf842500874023b2277b101808e0faeb2a65864049e781265be50ff1d060c596d00042350fb4076fdd2e1218bddcfa8cd5e932412565814bc3544ca867c5268373ea68f294f185118f7e7780fdee42f2bcff16c1cf50c159440ce34ad34984105b0d0a08a490b28da000000000000000000000000000000000000000000000000000000000000000051c9373c9f4e89ce45af216c2ca83a19be7f9963ee92a60930f793e25f9f24c6aeb28101b066e0f068aa8be6548063a18d811c489a9e2141000000000000000000004f2951f32bb62c965b80ae2351946062f857a05740d1007cfdfcd79571395bc095d1bc8b6fc899e5a5fce9b5a62297bbffa6803530bac3715640a8f2728ff4c171e2060051f486a090928af903f712a376fd1d45c2b0bad9b3f31fcd3137816f08656fdd554a95d27744b861422abdfafeb0e40ba8c793fab8dad90cf7ab5bdc9772194d090050e73032d84ca4d732a8c3d7239c9ffc2413c8abbba6f66e03575d13ffa9bc145251f6fc114bc93ecc14ce0d584ae36703ce6fc71774cdf1755ce6cab6ce67b7801f32ab98df4af1a44b6a88b5849550431abba0ab2cfe4b3cb93bb0230f02108e3d0051f722628b8d83acb29ed79f1929328f5f2d36513215426edc610a613d23f0035139a9180f58b189c5d5cdf1114384cca860bdd49a1c88b67a670800aed1869d100050cf4bcb3dddb4f33d22dd690c5ef46f1e1819ae866b1b38df2de14d8064bc2a88500cc298e2f2db864c99ba8ba85f1cb94e6d0516dc88cfc71969fa4aed1e7e5a21501a5eb3e7d7687ee2450a884147091cda21c59c780b22c22a6426a71ccbf372b550a58d80132aa023b05c079dd276c7335a5f5512bd50cea98fd1c1f6ba788eb13750de3d22b1a7725911d0eb142dae4947b1ebd68c220471fa0c7eab6d70d60674e550e54e5228a6f740816a3538f72281bee3a5ba681f92688b4832c977574c183372501d496836b0d38dc322857e736adf869f5b57b423ec78c7089bd642f95f6998634c4ff7501d47608687cba32d95e1519b28374c681e3fc30ef6ec88174fdade1f4bb268715039e1b23de083aa42de52ad9d891a3a2c141fbe2d821411e00d16488bcbb2cb33501dc1f382523fc46c6f9f6c0e3a77c96fb473d57b4bac4037f38683c363bd8ec750e476d6204d69099795d0e7300074949377000027bb3837423917d3d5b4ea906c50ab9b35f4aad5594a87d7f68193de993fcb62e46ff985464dd251b2936b2264cb4c4ff65068a05a45b4faf89a9402b24c04b3a46fc2e18a93c12da126e11d3c8f036cd11251f712c1e6b1a0d27af3d1a72acbc92105b08626248ef932c94260accb9502afef22442d4e0cf6ab7f36e0a9834a9f338d198065a66d8d2412603188b5278b865b0050e737a5fade47517f44b6a8d5f720609a1053bd9e1428a76d96ec1e4fa3d3cacb50a766c1da702421881cd1085a83e67ca17a3dca02badecb69738f78ced905fd2950f9e79576dc135d92368a94edcd45b4cfb9dc4e6abc20411defa216c332dd486350a8dfe6c905c799e319f0f8fcdbe6f9e4bb7c57bbe85f848c6167b3a2cca345184850cf2365392df4f78464069bbcaddc3fbd5849b7d946a97a82d718c3d2416b43d850bd380a2a20fccede15c4a38e827ecb81c35d8f282ed73f4492c0d79e39c6f6564800000000";
+
+    #[test]
+    fn test_detect_witness_version() {
+        let witness = hex::decode(CLAIM_WITNESS).unwrap();
+        let detected = parse_cota_witness(witness.clone(), None).unwrap();
+        let expected = parse_cota_witness(witness, Some(1)).unwrap();
+
+        assert_eq!(detected["version"], json!(1));
+        assert_eq!(detected, expected);
+    }
+}
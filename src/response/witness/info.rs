@@ -48,7 +48,6 @@ pub struct ClassAudio {
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct InfoData<T> {
-    #[serde(skip_serializing)]
     pub target: String,
     #[serde(rename = "type")]
     pub type_:  String,
@@ -57,9 +56,7 @@ pub struct InfoData<T> {
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct Metadata<T> {
-    #[serde(skip_serializing)]
     pub id:       String,
-    #[serde(skip_serializing)]
     pub ver:      String,
     pub metadata: InfoData<T>,
 }
@@ -0,0 +1,199 @@
+use super::parser::slice_to_hex;
+use crate::request::extension::EXT_ACTION_ADD;
+use crate::response::helper::Inserter;
+use crate::utils::error::Error;
+use ckb_types::packed::Script;
+use cota_smt::extension::{ExtensionEntries, ExtensionLeaves};
+use joyid_smt::joyid::{
+    FriendPubkey, SocialEntry, SocialUnlockEntries, SocialValue, SubKeyEntries, SubKeyUnlockEntries,
+};
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::Value;
+use molecule::error::VerificationError;
+use molecule::prelude::Entity;
+
+pub fn parse_extension(obj: ExtensionEntries, ext_action: u8) -> Result<Map<String, Value>, Error> {
+    let mut map = Map::new();
+    map.insert_str("type", "extension".to_owned());
+    let action = if ext_action == EXT_ACTION_ADD {
+        "add"
+    } else {
+        "update"
+    };
+    map.insert_str("ext_action", action.to_owned());
+    let sub_type = String::from_utf8_lossy(obj.sub_type().as_slice()).to_string();
+    map.insert_obj("leaves", parse_extension_leaves(obj.leaves()));
+    let raw_data = obj.raw_data().raw_data();
+    match sub_type.as_str() {
+        "subkey" => {
+            let entries = SubKeyEntries::from_slice(&raw_data).map_err(entries_error)?;
+            map.insert_obj_vec(
+                "subkeys",
+                entries
+                    .keys()
+                    .into_iter()
+                    .zip(entries.values().into_iter())
+                    .map(|(key, value)| parse_subkey_leaf(key.as_slice(), value.as_slice()))
+                    .collect(),
+            );
+        }
+        "social" => {
+            let entry = SocialEntry::from_slice(&raw_data).map_err(entries_error)?;
+            map.insert_obj("social", parse_social_value(entry.value()));
+        }
+        _ => {
+            return Err(Error::WitnessParseInvalid(format!(
+                "Unknown extension sub type: {}",
+                sub_type
+            )))
+        }
+    }
+    map.insert_str("sub_type", sub_type);
+    Ok(map)
+}
+
+pub fn parse_subkey_unlock(obj: SubKeyUnlockEntries) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert_str("type", "subkey_unlock".to_owned());
+    map.insert_u32("ext_data", parse_u32(obj.ext_data().as_slice()));
+    map.insert_u32("alg_index", parse_u16(obj.alg_index().as_slice()) as u32);
+    map.insert_str("subkey_proof", slice_to_hex(&obj.subkey_proof().raw_data()));
+    map
+}
+
+pub fn parse_social_unlock(obj: SocialUnlockEntries) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert_str("type", "social_unlock".to_owned());
+    map.insert_obj("social", parse_social_value(obj.social_value()));
+    map.insert_str("social_proof", slice_to_hex(&obj.social_proof().raw_data()));
+    map.insert_obj_vec(
+        "social_friends",
+        obj.social_friends()
+            .into_iter()
+            .map(parse_friend_pubkey)
+            .collect(),
+    );
+    map
+}
+
+fn parse_extension_leaves(obj: ExtensionLeaves) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert_array(
+        "keys",
+        obj.keys()
+            .into_iter()
+            .map(|key| Value::String(slice_to_hex(key.as_slice())))
+            .collect(),
+    );
+    map.insert_array(
+        "values",
+        obj.values()
+            .into_iter()
+            .map(|value| Value::String(slice_to_hex(value.as_slice())))
+            .collect(),
+    );
+    map.insert_array(
+        "old_values",
+        obj.old_values()
+            .into_iter()
+            .map(|value| Value::String(slice_to_hex(value.as_slice())))
+            .collect(),
+    );
+    map.insert_str("proof", slice_to_hex(&obj.proof().raw_data()));
+    map
+}
+
+// The subkey leaf key is 0xFF00 | "subkey" | ext_data(u32) | padding and the leaf value is
+// alg_index(u16) | pubkey_hash(20 bytes) | padding | 0xFF
+fn parse_subkey_leaf(key: &[u8], value: &[u8]) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert_u32("ext_data", parse_u32(&key[8..12]));
+    map.insert_u32("alg_index", parse_u16(&value[0..2]) as u32);
+    map.insert_str("pubkey_hash", slice_to_hex(&value[2..22]));
+    map
+}
+
+fn parse_social_value(obj: SocialValue) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert_u8("recovery_mode", obj.recovery_mode().as_slice()[0]);
+    map.insert_u8("must", obj.must().as_slice()[0]);
+    map.insert_u8("total", obj.total().as_slice()[0]);
+    map.insert_array(
+        "signers",
+        obj.signers()
+            .into_iter()
+            .map(|signer| parse_signer(&signer.raw_data()))
+            .collect(),
+    );
+    map
+}
+
+fn parse_friend_pubkey(obj: FriendPubkey) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert_u8("unlock_mode", obj.unlock_mode().as_slice()[0]);
+    map.insert_u32("alg_index", parse_u16(obj.alg_index().as_slice()) as u32);
+    map.insert_str("pubkey", slice_to_hex(&obj.pubkey().raw_data()));
+    map.insert_str("signature", slice_to_hex(&obj.signature().raw_data()));
+    map.insert_str(
+        "web_authn_msg",
+        slice_to_hex(&obj.web_authn_msg().raw_data()),
+    );
+    map.insert_u32("ext_data", parse_u32(obj.ext_data().as_slice()));
+    map.insert_str("subkey_proof", slice_to_hex(&obj.subkey_proof().raw_data()));
+    map
+}
+
+// The signers are the lock scripts of the friends, and the raw bytes are kept when a signer
+// is not a valid script
+fn parse_signer(slice: &[u8]) -> Value {
+    match Script::from_slice(slice) {
+        Ok(script) => {
+            let mut map = Map::new();
+            map.insert_str("code_hash", slice_to_hex(script.code_hash().as_slice()));
+            map.insert_str("hash_type", slice_to_hex(script.hash_type().as_slice()));
+            map.insert_str("args", slice_to_hex(&script.args().raw_data()));
+            Value::Object(map)
+        }
+        Err(_) => Value::String(slice_to_hex(slice)),
+    }
+}
+
+fn parse_u16(slice: &[u8]) -> u16 {
+    let mut bytes = [0u8; 2];
+    bytes.copy_from_slice(slice);
+    u16::from_be_bytes(bytes)
+}
+
+fn parse_u32(slice: &[u8]) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(slice);
+    u32::from_be_bytes(bytes)
+}
+
+fn entries_error(_e: VerificationError) -> Error {
+    Error::WitnessParseInvalid("Parse extension entries error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_subkey_extension() {
+        let str = "f500000010000000970000009d0000008700000014000000380000005c0000008000000001000000ff007375626b6579000000010000000000000000000000000000000000000000010000000001a479e697ba1af48df75b090b03058d056cca0f2e000000000000000000ff010000000000000000000000000000000000000000000000000000000000000000000000030000004c4f007375626b657954000000540000000c0000003000000001000000ff007375626b6579000000010000000000000000000000000000000000000000010000000001a479e697ba1af48df75b090b03058d056cca0f2e000000000000000000ff";
+        let entries = ExtensionEntries::from_slice(&hex::decode(str).unwrap()).unwrap();
+        let map = parse_extension(entries, EXT_ACTION_ADD).unwrap();
+
+        assert_eq!(map["sub_type"], json!("subkey"));
+        assert_eq!(map["ext_action"], json!("add"));
+        assert_eq!(
+            map["subkeys"],
+            json!([{
+                "ext_data": 1,
+                "alg_index": 1,
+                "pubkey_hash": "0xa479e697ba1af48df75b090b03058d056cca0f2e"
+            }])
+        );
+    }
+}
@@ -1,3 +1,4 @@
 pub mod cota;
-mod info;
+pub mod info;
+mod joyid;
 pub mod parser;
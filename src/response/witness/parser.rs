@@ -678,6 +678,6 @@ fn parse_script(slice: &[u8]) -> Map<String, Value> {
     map
 }
 
-fn slice_to_hex(slice: &[u8]) -> String {
+pub(crate) fn slice_to_hex(slice: &[u8]) -> String {
     format!("0x{}", hex::encode(slice))
 }
@@ -0,0 +1,112 @@
+use crate::auth::middleware::AuthMiddleware;
+use crate::auth::ClientMeta;
+use jsonrpc_http_server::hyper::body::HttpBody;
+use jsonrpc_http_server::hyper::header::{HeaderValue, CONTENT_TYPE};
+use jsonrpc_http_server::hyper::server::conn::AddrStream;
+use jsonrpc_http_server::hyper::service::{make_service_fn, service_fn};
+use jsonrpc_http_server::hyper::{Body, Method, Request, Response, Server, StatusCode};
+use jsonrpc_http_server::jsonrpc_core::MetaIoHandler;
+use log::{error, info};
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::oneshot;
+
+const MAX_REQUEST_BODY_SIZE: usize = 5 * 1024 * 1024;
+
+type IoHandler = MetaIoHandler<ClientMeta, AuthMiddleware>;
+
+/// The JSON-RPC HTTP server which is served by hyper directly, so the peer address of every
+/// connection is known to authenticate and rate limit the calls.
+pub struct HttpServer {
+    close: oneshot::Sender<()>,
+}
+
+impl HttpServer {
+    pub fn start(io: IoHandler, addr: &SocketAddr, threads: usize) -> Self {
+        let listener = TcpListener::bind(addr).expect("Bind HTTP server error");
+        listener
+            .set_nonblocking(true)
+            .expect("Set HTTP listener non-blocking error");
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(threads)
+            .enable_all()
+            .build()
+            .expect("Build HTTP server runtime error");
+        let (close, closed) = oneshot::channel::<()>();
+        let io = Arc::new(io);
+        thread::spawn(move || {
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |conn: &AddrStream| {
+                    let io = io.clone();
+                    let peer_addr = conn.remote_addr();
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |req| {
+                            handle_request(io.clone(), peer_addr, req)
+                        }))
+                    }
+                });
+                let server = match Server::from_tcp(listener) {
+                    Ok(builder) => builder.serve(make_service),
+                    Err(err) => {
+                        error!("Start HTTP server error: {}", err);
+                        return;
+                    }
+                };
+                let graceful = server.with_graceful_shutdown(async {
+                    closed.await.ok();
+                });
+                if let Err(err) = graceful.await {
+                    error!("HTTP server error: {}", err);
+                }
+                info!("HTTP server stopped");
+            });
+        });
+        HttpServer { close }
+    }
+
+    /// Stop accepting new connections, the in-flight requests are left to finish
+    pub fn close(self) {
+        self.close.send(()).ok();
+    }
+}
+
+async fn handle_request(
+    io: Arc<IoHandler>,
+    peer_addr: SocketAddr,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if req.method() != Method::POST {
+        return Ok(status_response(StatusCode::METHOD_NOT_ALLOWED));
+    }
+    let meta = ClientMeta::from_request(req.headers(), peer_addr);
+    let mut body = req.into_body();
+    let mut request: Vec<u8> = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(_e) => return Ok(status_response(StatusCode::BAD_REQUEST)),
+        };
+        if request.len() + chunk.len() > MAX_REQUEST_BODY_SIZE {
+            return Ok(status_response(StatusCode::PAYLOAD_TOO_LARGE));
+        }
+        request.extend_from_slice(&chunk);
+    }
+    let request = match String::from_utf8(request) {
+        Ok(request) => request,
+        Err(_e) => return Ok(status_response(StatusCode::BAD_REQUEST)),
+    };
+    let output = io.handle_request(&request, meta).await.unwrap_or_default();
+    let mut response = Response::new(Body::from(output));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    Ok(response)
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
@@ -2,11 +2,12 @@ use super::schema::Col;
 use crate::smt::db::schema::COLUMNS;
 use crate::smt::db::transaction::RocksDBTransaction;
 use crate::utils::error::Error;
-use rocksdb::ops::{GetColumnFamilys, OpenCF};
+use rocksdb::ops::{FlushCF, GetColumnFamilys, OpenCF};
 use rocksdb::{
     ColumnFamily, ColumnFamilyDescriptor, OptimisticTransactionDB, OptimisticTransactionOptions,
     Options, WriteOptions,
 };
+use std::env;
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -16,7 +17,11 @@ pub struct RocksDB {
 
 impl RocksDB {
     pub fn default() -> Result<Self, Error> {
-        Self::new_with_path("./store.db")
+        let path = match env::var("ROCKSDB_PATH") {
+            Ok(path) => path,
+            Err(_e) => "./store.db".to_owned(),
+        };
+        Self::new_with_path(&path)
     }
 
     pub fn new_with_path(path: &str) -> Result<Self, Error> {
@@ -48,6 +53,17 @@ impl RocksDB {
             inner: self.inner.transaction(&write_options, &transaction_options),
         }
     }
+
+    /// Flush the memtables of all the column families to disk
+    pub fn flush(&self) -> Result<(), Error> {
+        for col in 0..COLUMNS {
+            let cf = cf_handle(&self.inner, col as Col)?;
+            self.inner.flush_cf(cf).map_err(|e| {
+                Error::RocksDBInvalid(format!("RocksDB flush error: {:?}", e.to_string()))
+            })?;
+        }
+        Ok(())
+    }
 }
 
 #[inline]
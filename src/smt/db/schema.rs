@@ -2,12 +2,14 @@
 
 pub type Col = u8;
 /// Total column number
-pub const COLUMNS: u32 = 4;
+pub const COLUMNS: u32 = 5;
 /// Column SMT branch
 pub const COLUMN_SMT_BRANCH: Col = 0;
 /// Column SMT leaf
 pub const COLUMN_SMT_LEAF: Col = 1;
 /// Column SMT root hash
 pub const COLUMN_SMT_ROOT: Col = 2;
-/// Column SMT temp leaves
+/// Column SMT pending updates keyed by lock hash and the root after the update
 pub const COLUMN_SMT_TEMP_LEAVES: Col = 3;
+/// Column webhook registry
+pub const COLUMN_WEBHOOK: Col = 4;
@@ -1,8 +1,13 @@
 use crate::smt::store::smt_store::SMTStore;
 use crate::utils::error::Error;
+use chrono::prelude::*;
 use cota_smt::smt::{Blake2bHasher, H256};
+use lazy_static::lazy_static;
 use log::debug;
+use serde_json::from_str;
 use sparse_merkle_tree::SparseMerkleTree;
+use std::collections::HashSet;
+use std::env;
 
 pub mod db;
 pub mod store;
@@ -10,20 +15,39 @@ mod tests;
 pub mod transaction;
 mod types;
 
+const DEFAULT_PENDING_TIMEOUT: u64 = 600;
+
+lazy_static! {
+    // The pending updates which are not confirmed within the timeout(in seconds) are abandoned,
+    // e.g. the transaction of the generated entries has never been sent
+    static ref PENDING_TIMEOUT: u64 = match env::var("PENDING_TIMEOUT") {
+        Ok(timeout) => from_str::<u64>(&timeout).unwrap(),
+        Err(_e) => DEFAULT_PENDING_TIMEOUT,
+    };
+}
+
 pub type CotaSMT<'a> = SparseMerkleTree<Blake2bHasher, H256, SMTStore<'a>>;
 
 pub trait RootSaver {
     fn save_root_and_leaves(&self, leaves: Vec<(H256, H256)>) -> Result<(), Error>;
     fn commit(&self) -> Result<(), Error>;
+    fn reconcile_pending(&mut self, chain_root_opt: Option<H256>) -> Result<bool, Error>;
 }
 
 impl<'a> RootSaver for CotaSMT<'a> {
     fn save_root_and_leaves(&self, leaves: Vec<(H256, H256)>) -> Result<(), Error> {
+        let previous_root = self
+            .store()
+            .get_root()
+            .map_err(|_e| Error::SMTInvalid("Get smt root".to_string()))?
+            .unwrap_or_default();
         self.store()
             .save_root(self.root())
             .expect("Save smt root error");
-        if !leaves.is_empty() {
-            self.store().insert_leaves(leaves)?;
+        if !leaves.is_empty() && &previous_root != self.root() {
+            let created_at = Local::now().timestamp_millis() as u64;
+            self.store()
+                .insert_pending(self.root(), &previous_root, created_at, leaves)?;
         }
         debug!("Save latest smt root: {:?} and leaves", self.root());
         Ok(())
@@ -32,4 +56,61 @@ impl<'a> RootSaver for CotaSMT<'a> {
     fn commit(&self) -> Result<(), Error> {
         self.store().commit()
     }
+
+    // The pending updates are walked back from the local root. If the chain reaches the root
+    // of the cell, the updates after it are kept for the transactions still in flight and the
+    // confirmed ones are pruned, unless one of them has timed out and the updates after the cell
+    // root are abandoned. Otherwise all of them are reverted and false is returned unless the
+    // reverted root matches the root of the cell.
+    fn reconcile_pending(&mut self, chain_root_opt: Option<H256>) -> Result<bool, Error> {
+        let expired_at = (Local::now().timestamp_millis() as u64)
+            .saturating_sub(PENDING_TIMEOUT.saturating_mul(1000));
+        let mut pending = vec![];
+        let mut expired = false;
+        let mut visited = HashSet::new();
+        let mut root = *self.root();
+        while Some(root) != chain_root_opt && visited.insert(root) {
+            match self.store().get_pending(&root)? {
+                Some(update) => {
+                    expired |= update.created_at < expired_at;
+                    pending.push((root, update.leaves));
+                    root = update.previous_root;
+                }
+                None => break,
+            }
+        }
+
+        if let Some(chain_root) = chain_root_opt {
+            if root == chain_root {
+                let mut confirmed_root = chain_root;
+                let mut visited = HashSet::new();
+                while visited.insert(confirmed_root) {
+                    match self.store().get_pending(&confirmed_root)? {
+                        Some(update) => {
+                            self.store().remove_pending(&confirmed_root)?;
+                            confirmed_root = update.previous_root;
+                        }
+                        None => break,
+                    }
+                }
+                if !expired {
+                    debug!(
+                        "{} pending smt updates are on top of the cell root",
+                        pending.len()
+                    );
+                    return Ok(true);
+                }
+                debug!("Drop the abandoned pending smt updates on top of the cell root");
+            }
+        }
+
+        for (root, leaves) in pending {
+            self.update_all(leaves)
+                .map_err(|e| Error::SMTInvalid(e.to_string()))?;
+            self.store().remove_pending(&root)?;
+        }
+        self.store().save_root(self.root())?;
+        debug!("Revert pending smt updates to root: {:?}", self.root());
+        Ok(Some(*self.root()) == chain_root_opt)
+    }
 }
@@ -13,6 +13,13 @@ use sparse_merkle_tree::{
 };
 use std::convert::TryInto;
 
+/// The SMT update which has been generated but not confirmed on chain yet
+pub struct PendingUpdate {
+    pub previous_root: H256,
+    pub created_at:    u64,
+    pub leaves:        Vec<(H256, H256)>,
+}
+
 pub struct SMTStore<'a> {
     lock_hash:  [u8; 32],
     leaf_col:   Col,
@@ -66,7 +73,16 @@ impl<'a> SMTStore<'a> {
         }
     }
 
-    pub fn insert_leaves(&self, leaves: Vec<(H256, H256)>) -> Result<(), Error> {
+    // Every pending update is keyed by its resulting root, and the value is the previous root,
+    // the creating timestamp in milliseconds and the previous leaves, so the updates of a lock
+    // form a chain back to the root which has been confirmed on chain
+    pub fn insert_pending(
+        &self,
+        root: &H256,
+        previous_root: &H256,
+        created_at: u64,
+        leaves: Vec<(H256, H256)>,
+    ) -> Result<(), Error> {
         let smt_leaf_vec: Vec<SMTLeaf> = leaves
             .into_iter()
             .map(|leaf| {
@@ -77,16 +93,34 @@ impl<'a> SMTStore<'a> {
             })
             .collect();
         let smt_leaves: SMTLeafVec = SMTLeafVecBuilder::default().set(smt_leaf_vec).build();
+        let mut value = Vec::with_capacity(40 + smt_leaves.as_slice().len());
+        value.extend_from_slice(previous_root.as_slice());
+        value.extend_from_slice(&created_at.to_be_bytes());
+        value.extend_from_slice(smt_leaves.as_slice());
         self.store
-            .insert_raw(self.leaves_col, &self.lock_hash, smt_leaves.as_slice())
+            .insert_raw(
+                self.leaves_col,
+                &leaf_key_to_vec(self.lock_hash, root),
+                &value,
+            )
             .map_err(|err| Error::SMTInvalid(format!("insert error {:?}", err)))?;
         Ok(())
     }
 
-    pub fn get_leaves(&self) -> Result<Option<Vec<(H256, H256)>>, Error> {
-        match self.store.get(self.leaves_col, &self.lock_hash) {
-            Some(slice) => {
-                let smt_leaves = SMTLeafVec::from_slice(&slice)
+    pub fn get_pending(&self, root: &H256) -> Result<Option<PendingUpdate>, Error> {
+        match self
+            .store
+            .get(self.leaves_col, &leaf_key_to_vec(self.lock_hash, root))
+        {
+            Some(slice) if slice.len() >= 40 => {
+                let previous_root: [u8; 32] =
+                    slice[..32].try_into().expect("stored H256 should be valid");
+                let created_at = u64::from_be_bytes(
+                    slice[32..40]
+                        .try_into()
+                        .expect("stored timestamp should be valid"),
+                );
+                let smt_leaves = SMTLeafVec::from_slice(&slice[40..])
                     .map_err(|_e| Error::SMTInvalid("SMT Leaves parse error".to_owned()))?;
                 let leaves = smt_leaves
                     .into_iter()
@@ -104,11 +138,24 @@ impl<'a> SMTStore<'a> {
                         (key.into(), value.into())
                     })
                     .collect();
-                Ok(Some(leaves))
+                Ok(Some(PendingUpdate {
+                    previous_root: previous_root.into(),
+                    created_at,
+                    leaves,
+                }))
             }
+            Some(_) => Err(Error::SMTInvalid(
+                "SMT pending update parse error".to_owned(),
+            )),
             None => Ok(None),
         }
     }
+
+    pub fn remove_pending(&self, root: &H256) -> Result<(), Error> {
+        self.store
+            .delete(self.leaves_col, &leaf_key_to_vec(self.lock_hash, root))
+            .map_err(|err| Error::SMTInvalid(format!("delete error {:?}", err)))
+    }
 }
 
 impl<'a> StoreReadOps<H256> for SMTStore<'a> {
@@ -5,7 +5,7 @@ use crate::smt::db::schema::{
 };
 use crate::smt::store::smt_store::SMTStore;
 use crate::smt::transaction::store_transaction::StoreTransaction;
-use crate::smt::CotaSMT;
+use crate::smt::{CotaSMT, RootSaver};
 use crate::RocksDB;
 use chrono::prelude::*;
 use cota_smt::smt::{H256, SMT};
@@ -104,4 +104,78 @@ mod tests {
     pub fn test_smt_with_50000_leaves(b: &mut Bencher) {
         b.iter(|| generate_smt(50000, 100));
     }
+
+    #[test]
+    fn test_reconcile_pending_updates() {
+        let mut rng = thread_rng();
+        let lock_hash: [u8; 32] = rng.gen::<[u8; 32]>();
+        let db = RocksDB::new_with_path("test_db_pending").expect("rocksdb open error");
+        let transaction = StoreTransaction::new(db.transaction());
+        let smt_store = SMTStore::new(
+            lock_hash,
+            COLUMN_SMT_LEAF,
+            COLUMN_SMT_BRANCH,
+            COLUMN_SMT_ROOT,
+            COLUMN_SMT_TEMP_LEAVES,
+            &transaction,
+        );
+        let mut smt = CotaSMT::new(H256::zero(), smt_store);
+        let mut roots = vec![*smt.root()];
+        for _ in 0..3 {
+            let key: H256 = rng.gen::<[u8; 32]>().into();
+            let value: H256 = rng.gen::<[u8; 32]>().into();
+            smt.update(key, value).expect("smt update error");
+            smt.save_root_and_leaves(vec![(key, H256::zero())])
+                .expect("smt save error");
+            roots.push(*smt.root());
+        }
+
+        // The first update is confirmed and the last two are still in flight
+        assert!(smt.reconcile_pending(Some(roots[1])).unwrap());
+        assert_eq!(*smt.root(), roots[3]);
+        assert!(smt.store().get_pending(&roots[1]).unwrap().is_none());
+        assert!(smt.store().get_pending(&roots[3]).unwrap().is_some());
+
+        // The pending updates are reverted when the cell root is unknown
+        let unknown_root: H256 = rng.gen::<[u8; 32]>().into();
+        assert!(!smt.reconcile_pending(Some(unknown_root)).unwrap());
+        assert_eq!(*smt.root(), roots[1]);
+        assert!(smt.store().get_pending(&roots[2]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reconcile_abandoned_pending_updates() {
+        let mut rng = thread_rng();
+        let lock_hash: [u8; 32] = rng.gen::<[u8; 32]>();
+        let db = RocksDB::new_with_path("test_db_abandoned").expect("rocksdb open error");
+        let transaction = StoreTransaction::new(db.transaction());
+        let smt_store = SMTStore::new(
+            lock_hash,
+            COLUMN_SMT_LEAF,
+            COLUMN_SMT_BRANCH,
+            COLUMN_SMT_ROOT,
+            COLUMN_SMT_TEMP_LEAVES,
+            &transaction,
+        );
+        let mut smt = CotaSMT::new(H256::zero(), smt_store);
+        let mut roots = vec![*smt.root()];
+        for _ in 0..2 {
+            let key: H256 = rng.gen::<[u8; 32]>().into();
+            let value: H256 = rng.gen::<[u8; 32]>().into();
+            let previous_root = *smt.root();
+            smt.update(key, value).expect("smt update error");
+            // The generated entries whose transaction has never been sent
+            smt.store()
+                .insert_pending(smt.root(), &previous_root, 0, vec![(key, H256::zero())])
+                .expect("smt save error");
+            smt.store().save_root(smt.root()).expect("smt save error");
+            roots.push(*smt.root());
+        }
+
+        // The abandoned updates on top of the cell root are reverted instead of being kept
+        assert!(smt.reconcile_pending(Some(roots[0])).unwrap());
+        assert_eq!(*smt.root(), roots[0]);
+        assert!(smt.store().get_pending(&roots[1]).unwrap().is_none());
+        assert!(smt.store().get_pending(&roots[2]).unwrap().is_none());
+    }
 }
@@ -0,0 +1,118 @@
+use crate::auth::middleware::AuthMiddleware;
+use crate::auth::WsMeta;
+use crate::request::subscription::SubscribeReq;
+use cota_smt::smt::blake2b_256;
+use jsonrpc_http_server::jsonrpc_core::futures::future;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::{Error as RpcError, Params, Value};
+use jsonrpc_pubsub::{PubSubHandler, Sink, Subscriber, SubscriptionId};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub(crate) mod poller;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CotaEvent {
+    Hold,
+    Withdrawal,
+    Claim,
+}
+
+impl CotaEvent {
+    fn notification(&self) -> &'static str {
+        match self {
+            CotaEvent::Hold => "cota_hold",
+            CotaEvent::Withdrawal => "cota_withdrawal",
+            CotaEvent::Claim => "cota_claim",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CotaSubscription {
+    pub event:       CotaEvent,
+    pub lock_script: Vec<u8>,
+    pub lock_hash:   [u8; 32],
+    pub sink:        Sink,
+}
+
+lazy_static! {
+    static ref SUBSCRIPTIONS: RwLock<HashMap<u64, CotaSubscription>> = RwLock::new(HashMap::new());
+}
+
+static NEXT_SUBSCRIPTION_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn add_subscriptions(io: &mut PubSubHandler<WsMeta, AuthMiddleware>) {
+    for event in [CotaEvent::Hold, CotaEvent::Withdrawal, CotaEvent::Claim] {
+        let notification = event.notification();
+        let subscribe_name = format!("subscribe_{}", notification);
+        let unsubscribe_name = format!("unsubscribe_{}", notification);
+        io.add_subscription(
+            notification,
+            (
+                subscribe_name.as_str(),
+                move |params: Params, _: WsMeta, subscriber: Subscriber| {
+                    subscribe(event, params, subscriber)
+                },
+            ),
+            (
+                unsubscribe_name.as_str(),
+                |id: SubscriptionId, _: Option<WsMeta>| future::ready(unsubscribe(id)),
+            ),
+        );
+    }
+}
+
+fn subscribe(event: CotaEvent, params: Params, subscriber: Subscriber) {
+    info!("Subscribe {:?} request: {:?}", event, params);
+    let lock_script = match params
+        .parse::<Map<String, Value>>()
+        .and_then(|map| SubscribeReq::from_map(&map).map_err(RpcError::from))
+    {
+        Ok(SubscribeReq { lock_script }) => lock_script,
+        Err(err) => {
+            let _ = subscriber.reject(err);
+            return;
+        }
+    };
+    let id = NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::SeqCst);
+    match subscriber.assign_id(SubscriptionId::Number(id)) {
+        Ok(sink) => {
+            let lock_hash = blake2b_256(&lock_script);
+            SUBSCRIPTIONS.write().insert(id, CotaSubscription {
+                event,
+                lock_script,
+                lock_hash,
+                sink,
+            });
+        }
+        Err(_) => warn!("Subscription {} of {:?} has been closed", id, event),
+    }
+}
+
+fn unsubscribe(id: SubscriptionId) -> Result<Value, RpcError> {
+    let removed = match id {
+        SubscriptionId::Number(id) => SUBSCRIPTIONS.write().remove(&id).is_some(),
+        SubscriptionId::String(_) => false,
+    };
+    if removed {
+        Ok(Value::Bool(true))
+    } else {
+        Err(RpcError::invalid_params("Subscription id not found"))
+    }
+}
+
+pub fn get_subscriptions() -> Vec<(u64, CotaSubscription)> {
+    SUBSCRIPTIONS
+        .read()
+        .iter()
+        .map(|(id, subscription)| (*id, subscription.clone()))
+        .collect()
+}
+
+pub fn remove_subscription(id: u64) {
+    SUBSCRIPTIONS.write().remove(&id);
+}
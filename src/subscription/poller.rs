@@ -0,0 +1,131 @@
+use super::{get_subscriptions, remove_subscription, CotaEvent, CotaSubscription};
+use crate::models::block::get_syncer_tip_block_number;
+use crate::models::claim::get_claim_cota_by_lock_hash_and_block_range;
+use crate::models::class::{get_class_info_by_cota_id, ClassInfo};
+use crate::models::hold::{get_hold_cota_by_lock_hash_and_block_range, HoldDb};
+use crate::models::scripts::get_script_id_by_lock_script;
+use crate::models::withdrawal::nft::{
+    get_withdrawal_cota_by_script_id_and_block_range, WithdrawNFTDb,
+};
+use crate::response::subscription::{
+    parse_claim_notification, parse_hold_notification, parse_withdrawal_notification,
+};
+use crate::utils::error::Error;
+use jsonrpc_http_server::jsonrpc_core::serde_json::Map;
+use jsonrpc_http_server::jsonrpc_core::{Params, Value};
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL_SECS: u64 = 3;
+
+pub fn start_tip_poller() {
+    thread::spawn(|| {
+        let mut last_block_number = get_syncer_tip_block_number().unwrap_or_default();
+        info!("Subscription poller start from block {}", last_block_number);
+        loop {
+            thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
+            match get_syncer_tip_block_number() {
+                Ok(tip_block_number) if tip_block_number > last_block_number => {
+                    notify_subscribers(last_block_number, tip_block_number);
+                    last_block_number = tip_block_number;
+                }
+                Ok(_) => {}
+                Err(err) => error!("Subscription poller error: {}", err.to_msg()),
+            }
+        }
+    });
+}
+
+fn notify_subscribers(from_block: u64, to_block: u64) {
+    let mut results: HashMap<(CotaEvent, [u8; 32]), Option<Value>> = HashMap::new();
+    for (id, subscription) in get_subscriptions() {
+        let key = (subscription.event, subscription.lock_hash);
+        if !results.contains_key(&key) {
+            let result = match query_events(&subscription, from_block, to_block) {
+                Ok(result) => result,
+                Err(err) => {
+                    error!(
+                        "Query {:?} events error: {}",
+                        subscription.event,
+                        err.to_msg()
+                    );
+                    None
+                }
+            };
+            results.insert(key, result);
+        }
+        if let Some(Some(result)) = results.get(&key) {
+            let mut params = Map::new();
+            params.insert("subscription".to_string(), Value::from(id));
+            params.insert("result".to_string(), result.clone());
+            if let Err(e) = subscription.sink.notify(Params::Map(params)) {
+                warn!("Subscription {} notify error: {:?}", id, e);
+                remove_subscription(id);
+            }
+        }
+    }
+}
+
+fn query_events(
+    subscription: &CotaSubscription,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Option<Value>, Error> {
+    let result = match subscription.event {
+        CotaEvent::Hold => {
+            let holds = get_hold_cota_by_lock_hash_and_block_range(
+                subscription.lock_hash,
+                from_block,
+                to_block,
+            )?;
+            if holds.is_empty() {
+                return Ok(None);
+            }
+            parse_hold_notification(
+                with_class_info(holds, |hold: &HoldDb| hold.cota_id)?,
+                to_block,
+            )?
+        }
+        CotaEvent::Withdrawal => {
+            let withdrawals = match get_script_id_by_lock_script(&subscription.lock_script)? {
+                Some(script_id) => get_withdrawal_cota_by_script_id_and_block_range(
+                    script_id, from_block, to_block,
+                )?,
+                None => vec![],
+            };
+            if withdrawals.is_empty() {
+                return Ok(None);
+            }
+            parse_withdrawal_notification(
+                with_class_info(withdrawals, |withdrawal: &WithdrawNFTDb| withdrawal.cota_id)?,
+                to_block,
+            )?
+        }
+        CotaEvent::Claim => {
+            let claims = get_claim_cota_by_lock_hash_and_block_range(
+                subscription.lock_hash,
+                from_block,
+                to_block,
+            )?;
+            if claims.is_empty() {
+                return Ok(None);
+            }
+            parse_claim_notification(claims, to_block)
+        }
+    };
+    Ok(Some(result))
+}
+
+fn with_class_info<T, F>(nfts: Vec<T>, cota_id: F) -> Result<Vec<(T, Option<ClassInfo>)>, Error>
+where
+    F: Fn(&T) -> [u8; 20],
+{
+    let mut result = vec![];
+    for nft in nfts {
+        let class_info = get_class_info_by_cota_id(cota_id(&nft))?;
+        result.push((nft, class_info))
+    }
+    Ok(result)
+}
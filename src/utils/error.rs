@@ -28,6 +28,66 @@ pub enum Error {
     #[fail(display = "The collection of cota_id '{}' has not defined", _0)]
     CotaIdHasNotDefined(String),
 
+    #[fail(display = "The collection of cota_id '{}' has defined", _0)]
+    CotaIdHasDefined(String),
+
+    #[fail(display = "The cota_id '{}' is not derived from the first input", _0)]
+    CotaIdNotDerived(String),
+
+    #[fail(display = "The configure '{}' sets the reserved bits", _0)]
+    CotaConfigureInvalid(u8),
+
+    #[fail(
+        display = "The issued count '{}' exceeds the total '{}'",
+        issued, total
+    )]
+    CotaIssuedExceedTotal { issued: u64, total: u32 },
+
+    #[fail(display = "The token_index '{}' is duplicated", _0)]
+    TokenIndexDuplicated(u32),
+
+    #[fail(
+        display = "The token_index must be issued sequentially, got {}, expected: {}",
+        got, expected
+    )]
+    TokenIndexNotSequential { got: u32, expected: u32 },
+
+    #[fail(
+        display = "The token_index_start {} must not be greater than the token_index_end {}",
+        start, end
+    )]
+    TokenIndexRangeInvalid { start: u32, end: u32 },
+
+    #[fail(display = "The NFT '{}' has been claimed", _0)]
+    NFTHasClaimed(String),
+
+    #[fail(display = "The NFT '{}' cannot be claimed", _0)]
+    NFTNotClaimable(String),
+
+    #[fail(display = "The NFT '{}' is locked", _0)]
+    NFTLocked(String),
+
+    #[fail(display = "The NFT '{}' cannot be transferred", _0)]
+    NFTNotTransferable(String),
+
+    #[fail(display = "The NFT '{}' cannot be updated", _0)]
+    NFTNotUpdatable(String),
+
+    #[fail(display = "The NFT '{}' cannot be locked", _0)]
+    NFTNotLockable(String),
+
+    #[fail(
+        display = "The claimed or burned state of the NFT '{}' cannot be updated",
+        _0
+    )]
+    NFTStateInvalid(String),
+
+    #[fail(display = "The NFT '{}' has been burned", _0)]
+    NFTBurned(String),
+
+    #[fail(display = "The metadata field '{}' is invalid", _0)]
+    MetadataFieldInvalid(String),
+
     #[fail(display = "The NFT of cota_id and token_index has not been withdrawn")]
     CotaIdAndTokenIndexHasNotWithdrawn,
 
@@ -37,6 +97,18 @@ pub enum Error {
     #[fail(display = "The withdrawal CoTA NFTs are not in one transaction")]
     WithdrawCotaNFTsNotInOneTx,
 
+    #[fail(
+        display = "The transaction with {} bytes of witness and {} cycles exceeds the limits",
+        witness_size, cycles
+    )]
+    TxTooLarge {
+        witness_size: usize,
+        cycles:       u64,
+    },
+
+    #[fail(display = "The lock hash '{}' has registered", _0)]
+    LockHashHasRegistered(String),
+
     #[fail(display = "The subkey not found")]
     SubkeyLeafNotFound,
 
@@ -70,6 +142,30 @@ pub enum Error {
     #[fail(display = "Witness Parse error: {}", _0)]
     WitnessParseInvalid(String),
 
+    #[fail(display = "The lock script with code hash '{}' is not supported", _0)]
+    LockScriptUnsupported(String),
+
+    #[fail(display = "The fee cell error: {}", _0)]
+    FeeCellInvalid(String),
+
+    #[fail(display = "The webhook '{}' not found", _0)]
+    WebhookNotFound(u64),
+
+    #[fail(display = "The webhook url '{}' is not allowed", _0)]
+    WebhookUrlForbidden(String),
+
+    #[fail(display = "The API key is missing or invalid")]
+    ApiKeyInvalid,
+
+    #[fail(display = "The method requires API keys which are not configured")]
+    AuthDisabled,
+
+    #[fail(display = "Rate limit of '{}' exceeded", _0)]
+    RateLimitExceeded(String),
+
+    #[fail(display = "The aggregator is shutting down")]
+    ShuttingDown,
+
     #[fail(display = "Other error: {}", _0)]
     Other(String),
 }
@@ -100,6 +196,56 @@ impl Error {
             ),
             Self::RequestParamTypeInvalid(msg) => format!("Request parameter '{}' type error", msg),
             Self::CotaIdHasNotDefined(msg) => format!("The cota_id '{}' has not defined", msg),
+            Self::CotaIdHasDefined(msg) => format!("The cota_id '{}' has defined", msg),
+            Self::CotaIdNotDerived(msg) => {
+                format!("The cota_id '{}' is not derived from the first input", msg)
+            }
+            Self::CotaConfigureInvalid(configure) => format!(
+                "The configure '{:#04x}' sets the reserved bits which must be zero",
+                configure
+            ),
+            Self::CotaIssuedExceedTotal { issued, total } => format!(
+                "The issued count '{}' exceeds the total '{}'",
+                issued, total
+            ),
+            Self::TokenIndexDuplicated(token_index) => {
+                format!("The token_index '{}' is duplicated", token_index)
+            }
+            Self::TokenIndexNotSequential { got, expected } => format!(
+                "The token_index must be issued sequentially, got {}, expected: {}",
+                got, expected
+            ),
+            Self::TokenIndexRangeInvalid { start, end } => format!(
+                "The token_index_start {} must not be greater than the token_index_end {}",
+                start, end
+            ),
+            Self::NFTHasClaimed(msg) => format!("The NFT '{}' has been claimed", msg),
+            Self::NFTNotClaimable(msg) => {
+                format!("The configure of the NFT '{}' forbids claiming", msg)
+            }
+            Self::NFTLocked(msg) => {
+                format!(
+                    "The NFT '{}' is locked and cannot be transferred or burned",
+                    msg
+                )
+            }
+            Self::NFTNotTransferable(msg) => {
+                format!("The configure of the NFT '{}' forbids transferring", msg)
+            }
+            Self::NFTNotUpdatable(msg) => {
+                format!("The configure of the NFT '{}' forbids updating", msg)
+            }
+            Self::NFTNotLockable(msg) => {
+                format!("The configure of the NFT '{}' forbids locking", msg)
+            }
+            Self::NFTStateInvalid(msg) => {
+                format!(
+                    "The claimed or burned state of the NFT '{}' cannot be updated",
+                    msg
+                )
+            }
+            Self::NFTBurned(msg) => format!("The NFT '{}' has been burned", msg),
+            Self::MetadataFieldInvalid(msg) => format!("The metadata field '{}' is invalid", msg),
             Self::CotaIdAndTokenIndexHasNotWithdrawn => {
                 "The cota_id and token_index has not withdrawn".into()
             }
@@ -109,6 +255,14 @@ impl Error {
             Self::WithdrawCotaNFTsNotInOneTx => {
                 "The withdrawal CoTA NFTs are not in one transaction".into()
             }
+            Self::TxTooLarge {
+                witness_size,
+                cycles,
+            } => format!(
+                "The transaction with {} bytes of witness and {} cycles exceeds the limits",
+                witness_size, cycles
+            ),
+            Self::LockHashHasRegistered(msg) => format!("The lock hash '{}' has registered", msg),
             Self::SubkeyLeafNotFound => "The subkey not found".into(),
             Self::SocialLeafNotFound => "The social recovery config not found".into(),
             Self::SocialFriendInfoInvalid(msg) => {
@@ -122,6 +276,17 @@ impl Error {
             Self::SMTInvalid(msg) => format!("SMT error: {}", msg),
             Self::RocksDBInvalid(msg) => format!("RocksDB error: {}", msg),
             Self::WitnessParseInvalid(msg) => format!("Witness parse error: {}", msg),
+            Self::LockScriptUnsupported(code_hash) => format!(
+                "The lock script with code hash '{}' is not supported",
+                code_hash
+            ),
+            Self::FeeCellInvalid(msg) => format!("The fee cell error: {}", msg),
+            Self::WebhookNotFound(id) => format!("The webhook '{}' not found", id),
+            Self::WebhookUrlForbidden(url) => format!("The webhook url '{}' is not allowed", url),
+            Self::ApiKeyInvalid => "The API key is missing or invalid".into(),
+            Self::AuthDisabled => "The method requires API keys which are not configured".into(),
+            Self::RateLimitExceeded(msg) => format!("Rate limit of '{}' exceeded", msg),
+            Self::ShuttingDown => "The aggregator is shutting down".into(),
             Self::Other(msg) => format!("Internal error: {:}", msg),
         }
     }
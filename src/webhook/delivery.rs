@@ -0,0 +1,103 @@
+use super::{resolve_webhook_url, Webhook};
+use hmac::{Hmac, Mac};
+use jsonrpc_http_server::jsonrpc_core::Value;
+use log::{error, info, warn};
+use sha2::Sha256;
+use std::thread;
+use std::time::Duration;
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF_SECS: u64 = 1;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Post the payload to the webhook url in a background thread and retry with exponential backoff
+/// if the target does not respond with a success status. The url is resolved again and the
+/// connection is pinned to the checked address without following redirects, so the target can
+/// not be moved into the internal network after registration.
+pub fn deliver(webhook: &Webhook, payload: Value) {
+    let url = webhook.url.clone();
+    let id = webhook.id;
+    let event = webhook.event.as_str();
+    let body = payload.to_string();
+    let signature = sign(&webhook.secret, body.as_bytes());
+    thread::spawn(move || {
+        let (host, addr) = match resolve_webhook_url(&url) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                error!("Webhook {} url error: {}", id, e.to_msg());
+                return;
+            }
+        };
+        let client = match reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, addr)
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Webhook {} client error: {}", id, e.to_string());
+                return;
+            }
+        };
+        let mut backoff = INITIAL_BACKOFF_SECS;
+        for attempt in 1..=MAX_ATTEMPTS {
+            let result = client
+                .post(&url)
+                .header("content-type", "application/json")
+                .header("x-cota-event", event)
+                .header("x-cota-signature", format!("sha256={}", signature))
+                .body(body.clone())
+                .send();
+            match result {
+                Ok(resp) if resp.status().is_success() => {
+                    info!("Webhook {} delivered at attempt {}", id, attempt);
+                    return;
+                }
+                Ok(resp) => warn!(
+                    "Webhook {} attempt {} failed with status {}",
+                    id,
+                    attempt,
+                    resp.status()
+                ),
+                Err(e) => warn!(
+                    "Webhook {} attempt {} failed: {}",
+                    id,
+                    attempt,
+                    e.to_string()
+                ),
+            }
+            if attempt < MAX_ATTEMPTS {
+                thread::sleep(Duration::from_secs(backoff));
+                backoff *= 2;
+            }
+        }
+        error!(
+            "Webhook {} delivery failed after {} attempts",
+            id, MAX_ATTEMPTS
+        );
+    });
+}
+
+/// HMAC-SHA256 signature of the request body, the receiver should verify it with the secret
+/// provided at registration.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
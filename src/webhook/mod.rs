@@ -0,0 +1,211 @@
+use crate::models::define::get_define_cota_by_cota_id;
+use crate::request::webhook::WebhookReq;
+use crate::smt::db::schema::COLUMN_WEBHOOK;
+use crate::utils::error::Error;
+use crate::ROCKS_DB;
+use lazy_static::lazy_static;
+use log::error;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+
+mod delivery;
+pub(crate) mod poller;
+
+const WEBHOOKS_KEY: &[u8] = b"webhooks";
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// An unclaimed NFT is withdrawn to the watched lock
+    Withdrawal,
+    /// The issued count of the watched collection changes
+    Issued,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Withdrawal => "withdrawal",
+            WebhookEvent::Issued => "issued",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Webhook {
+    pub id:          u64,
+    /// The hash of the API key which registered the webhook
+    pub owner:       String,
+    pub url:         String,
+    pub secret:      String,
+    pub event:       WebhookEvent,
+    pub lock_script: Option<Vec<u8>>,
+    pub cota_id:     Option<[u8; 20]>,
+    pub issued:      Option<u32>,
+}
+
+lazy_static! {
+    static ref WEBHOOKS: RwLock<Vec<Webhook>> = RwLock::new(load_webhooks());
+}
+
+pub fn register_webhook(owner: String, req: WebhookReq) -> Result<Webhook, Error> {
+    resolve_webhook_url(&req.url)?;
+    let issued = match req.cota_id {
+        Some(cota_id) => get_define_cota_by_cota_id(cota_id)?.map(|define| define.issued),
+        None => None,
+    };
+    let mut webhooks = WEBHOOKS.write();
+    let id = webhooks.iter().map(|webhook| webhook.id).max().unwrap_or(0) + 1;
+    let webhook = Webhook {
+        id,
+        owner,
+        url: req.url,
+        secret: req.secret,
+        event: req.event,
+        lock_script: req.lock_script,
+        cota_id: req.cota_id,
+        issued,
+    };
+    webhooks.push(webhook.clone());
+    save_webhooks(&webhooks)?;
+    Ok(webhook)
+}
+
+pub fn remove_webhook(owner: &str, id: u64) -> Result<(), Error> {
+    let mut webhooks = WEBHOOKS.write();
+    let length = webhooks.len();
+    webhooks.retain(|webhook| webhook.id != id || webhook.owner != owner);
+    if webhooks.len() == length {
+        return Err(Error::WebhookNotFound(id));
+    }
+    save_webhooks(&webhooks)
+}
+
+pub fn get_webhooks() -> Vec<Webhook> {
+    WEBHOOKS.read().clone()
+}
+
+pub fn get_owner_webhooks(owner: &str) -> Vec<Webhook> {
+    WEBHOOKS
+        .read()
+        .iter()
+        .filter(|webhook| webhook.owner == owner)
+        .cloned()
+        .collect()
+}
+
+/// Resolve the host of the webhook url, the url is rejected if any of the resolved addresses is
+/// not public, so the aggregator can not be used to reach the internal network. The host and
+/// the checked address are returned to pin the delivery to it.
+pub fn resolve_webhook_url(url: &str) -> Result<(String, SocketAddr), Error> {
+    let forbidden = || Error::WebhookUrlForbidden(url.to_owned());
+    let parsed = reqwest::Url::parse(url).map_err(|_e| forbidden())?;
+    let host = parsed.host_str().ok_or_else(forbidden)?;
+    let port = parsed.port_or_known_default().ok_or_else(forbidden)?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = (host, port)
+        .to_socket_addrs()
+        .map_err(|_e| forbidden())?
+        .collect();
+    if addrs.is_empty() || addrs.iter().any(|addr| !is_public_ip(&addr.ip())) {
+        return Err(forbidden());
+    }
+    Ok((host.to_owned(), addrs[0]))
+}
+
+fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || octets[0] == 0
+                // Shared address space 100.64.0.0/10
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ipv4) = ip.to_ipv4_mapped() {
+                return is_public_ip(&IpAddr::V4(ipv4));
+            }
+            let first_segment = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local fc00::/7 and link local fe80::/10
+                || (first_segment & 0xfe00) == 0xfc00
+                || (first_segment & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+pub fn update_webhook_issued(id: u64, issued: u32) -> Result<(), Error> {
+    let mut webhooks = WEBHOOKS.write();
+    if let Some(webhook) = webhooks.iter_mut().find(|webhook| webhook.id == id) {
+        webhook.issued = Some(issued);
+    }
+    save_webhooks(&webhooks)
+}
+
+fn load_webhooks() -> Vec<Webhook> {
+    let transaction = ROCKS_DB.transaction();
+    match transaction.get(COLUMN_WEBHOOK, WEBHOOKS_KEY) {
+        Ok(Some(value)) => serde_json::from_slice(&value).unwrap_or_else(|e| {
+            error!("Parse webhooks error: {}", e.to_string());
+            vec![]
+        }),
+        Ok(None) => vec![],
+        Err(e) => {
+            error!("Load webhooks error: {}", e.to_msg());
+            vec![]
+        }
+    }
+}
+
+fn save_webhooks(webhooks: &[Webhook]) -> Result<(), Error> {
+    let value = serde_json::to_vec(webhooks)
+        .map_err(|e| Error::Other(format!("Serialize webhooks error: {}", e)))?;
+    let transaction = ROCKS_DB.transaction();
+    transaction.put(COLUMN_WEBHOOK, WEBHOOKS_KEY, &value)?;
+    transaction.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(&ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["8.8.8.8", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(&ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_resolve_webhook_url() {
+        assert!(resolve_webhook_url("http://127.0.0.1:8080/hook").is_err());
+        assert!(resolve_webhook_url("http://localhost/hook").is_err());
+        assert!(resolve_webhook_url("http://[::1]/hook").is_err());
+        assert!(resolve_webhook_url("https://8.8.8.8/hook").is_ok());
+    }
+}
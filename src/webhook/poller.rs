@@ -0,0 +1,95 @@
+use super::delivery::deliver;
+use super::{get_webhooks, update_webhook_issued, Webhook, WebhookEvent};
+use crate::models::block::get_syncer_tip_block_number;
+use crate::models::class::get_class_info_by_cota_id;
+use crate::models::define::get_define_cota_by_cota_id;
+use crate::models::scripts::get_script_id_by_lock_script;
+use crate::models::withdrawal::nft::get_withdrawal_cota_by_script_id_and_block_range;
+use crate::response::subscription::parse_withdrawal_notification;
+use crate::response::webhook::{parse_issued_payload, parse_webhook_payload};
+use crate::utils::error::Error;
+use jsonrpc_http_server::jsonrpc_core::Value;
+use log::{error, info};
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL_SECS: u64 = 3;
+
+// Every webhook keeps the block number it has been notified to, so the blocks of a failed poll
+// are polled again with the next tip, and a new webhook starts from the tip of the last poll
+pub fn start_webhook_poller() {
+    thread::spawn(|| {
+        let mut last_tip_number = get_syncer_tip_block_number().unwrap_or_default();
+        let mut last_block_numbers: HashMap<u64, u64> = HashMap::new();
+        info!("Webhook poller start from block {}", last_tip_number);
+        loop {
+            thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
+            let tip_block_number = match get_syncer_tip_block_number() {
+                Ok(tip_block_number) => tip_block_number,
+                Err(err) => {
+                    error!("Webhook poller error: {}", err.to_msg());
+                    continue;
+                }
+            };
+            let webhooks = get_webhooks();
+            last_block_numbers.retain(|id, _| webhooks.iter().any(|webhook| webhook.id == *id));
+            for webhook in webhooks {
+                let last_block_number = *last_block_numbers
+                    .entry(webhook.id)
+                    .or_insert(last_tip_number);
+                if tip_block_number <= last_block_number {
+                    continue;
+                }
+                match diff_webhook(&webhook, last_block_number, tip_block_number) {
+                    Ok(data) => {
+                        if let Some(data) = data {
+                            deliver(
+                                &webhook,
+                                parse_webhook_payload(&webhook, data, tip_block_number),
+                            );
+                        }
+                        last_block_numbers.insert(webhook.id, tip_block_number);
+                    }
+                    Err(err) => error!("Webhook {} poll error: {}", webhook.id, err.to_msg()),
+                }
+            }
+            last_tip_number = last_tip_number.max(tip_block_number);
+        }
+    });
+}
+
+fn diff_webhook(webhook: &Webhook, from_block: u64, to_block: u64) -> Result<Option<Value>, Error> {
+    match webhook.event {
+        WebhookEvent::Withdrawal => {
+            let lock_script = webhook.lock_script.clone().unwrap_or_default();
+            let withdrawals = match get_script_id_by_lock_script(&lock_script)? {
+                Some(script_id) => get_withdrawal_cota_by_script_id_and_block_range(
+                    script_id, from_block, to_block,
+                )?,
+                None => vec![],
+            };
+            if withdrawals.is_empty() {
+                return Ok(None);
+            }
+            let mut nfts = vec![];
+            for withdrawal in withdrawals {
+                let class_info = get_class_info_by_cota_id(withdrawal.cota_id)?;
+                nfts.push((withdrawal, class_info));
+            }
+            parse_withdrawal_notification(nfts, to_block).map(Some)
+        }
+        WebhookEvent::Issued => {
+            let cota_id = webhook.cota_id.unwrap_or_default();
+            let define = match get_define_cota_by_cota_id(cota_id)? {
+                Some(define) => define,
+                None => return Ok(None),
+            };
+            if webhook.issued == Some(define.issued) {
+                return Ok(None);
+            }
+            update_webhook_issued(webhook.id, define.issued)?;
+            Ok(Some(parse_issued_payload(cota_id, define, webhook.issued)))
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use ckb_jsonrpc_types::{
+    BlockView as JsonBlockView, CellOutput as JsonCellOutput, HeaderView as JsonHeaderView,
+    JsonBytes, MerkleProof, OutPoint as JsonOutPoint, Script as JsonScript, TransactionProof,
+    Uint64,
+};
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{BlockBuilder, BlockView, Capacity, ScriptHashType, TransactionBuilder};
+use ckb_types::packed::{CellInput, CellOutput, OutPoint, Script, WitnessArgs};
+use ckb_types::prelude::*;
+use jsonrpc_http_server::jsonrpc_core::{Error as RpcError, IoHandler, Params, Value};
+use jsonrpc_http_server::{Server, ServerBuilder};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const TESTNET_COTA_CODE_HASH: &str =
+    "89cd8003a0eaf8e65e0c31525b7d1d5c1becefd2ea75bb4cff87810ae37764d8";
+const COTA_CELL_VERSION: u8 = 2;
+const COTA_CELL_CAPACITY: u64 = 500_0000_0000;
+
+struct LiveCell {
+    block_number: u64,
+    out_point:    OutPoint,
+    output:       CellOutput,
+    output_data:  Bytes,
+}
+
+#[derive(Default)]
+struct ChainState {
+    tip:    u64,
+    blocks: HashMap<u64, BlockView>,
+    cells:  HashMap<Vec<u8>, LiveCell>,
+}
+
+// The mock serves the subset of the CKB node and indexer RPCs which the aggregator calls, and
+// every CoTA transaction committed to it is packed into a block of its own
+pub struct MockCkb {
+    state:  Arc<Mutex<ChainState>>,
+    server: Server,
+}
+
+impl MockCkb {
+    pub fn start(tip: u64) -> Self {
+        let state = Arc::new(Mutex::new(ChainState {
+            tip,
+            ..Default::default()
+        }));
+        let mut io = IoHandler::new();
+
+        let cells_state = Arc::clone(&state);
+        io.add_sync_method("get_cells", move |params: Params| {
+            let params = params.parse::<Vec<Value>>()?;
+            let search_key = params
+                .first()
+                .ok_or_else(|| RpcError::invalid_params("search_key"))?;
+            let mut objects = vec![];
+            if search_key["script_type"] == "lock" {
+                let script: JsonScript = serde_json::from_value(search_key["script"].clone())
+                    .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                let lock_script = Script::from(script).as_slice().to_vec();
+                if let Some(cell) = cells_state.lock().unwrap().cells.get(&lock_script) {
+                    objects.push(json!({
+                        "output": JsonCellOutput::from(cell.output.clone()),
+                        "output_data": JsonBytes::from_bytes(cell.output_data.clone()),
+                        "out_point": JsonOutPoint::from(cell.out_point.clone()),
+                        "block_number": Uint64::from(cell.block_number),
+                        "tx_index": "0x0",
+                    }));
+                }
+            }
+            Ok(json!({ "objects": objects, "last_cursor": "0x" }))
+        });
+
+        let tip_state = Arc::clone(&state);
+        io.add_sync_method("get_tip_block_number", move |_params: Params| {
+            Ok(json!(Uint64::from(tip_state.lock().unwrap().tip)))
+        });
+
+        let indexer_tip_state = Arc::clone(&state);
+        io.add_sync_method("get_indexer_tip", move |_params: Params| {
+            Ok(json!({
+                "block_hash": format!("0x{}", hex::encode([0u8; 32])),
+                "block_number": Uint64::from(indexer_tip_state.lock().unwrap().tip),
+            }))
+        });
+
+        let block_state = Arc::clone(&state);
+        io.add_sync_method("get_block_by_number", move |params: Params| {
+            let block_number = parse_block_number(params)?;
+            Ok(
+                match block_state.lock().unwrap().blocks.get(&block_number) {
+                    Some(block) => json!(JsonBlockView::from(block.clone())),
+                    None => Value::Null,
+                },
+            )
+        });
+
+        let header_state = Arc::clone(&state);
+        io.add_sync_method("get_header_by_number", move |params: Params| {
+            let block_number = parse_block_number(params)?;
+            Ok(
+                match header_state.lock().unwrap().blocks.get(&block_number) {
+                    Some(block) => json!(JsonHeaderView::from(block.header())),
+                    None => Value::Null,
+                },
+            )
+        });
+
+        // The aggregator puts the proof into the claim entries without checking it, so the proof
+        // of the only transaction in the block is enough
+        io.add_sync_method("get_transaction_proof", move |params: Params| {
+            let params = params.parse::<Vec<Value>>()?;
+            let block_hash = serde_json::from_value(params.get(1).cloned().unwrap_or_default())
+                .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+            Ok(json!(TransactionProof {
+                block_hash,
+                witnesses_root: Default::default(),
+                proof: MerkleProof {
+                    indices: vec![0u32.into()],
+                    lemmas:  vec![],
+                },
+            }))
+        });
+
+        let server = ServerBuilder::new(io)
+            .threads(1)
+            .start_http(&"127.0.0.1:0".parse().unwrap())
+            .expect("Mock CKB node start error");
+        MockCkb { state, server }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.server.address())
+    }
+
+    // The out_point([12..]) of the live CoTA cell, which the generating RPCs take as the
+    // out_point of the sender
+    pub fn cota_out_point(&self, lock_script: &[u8]) -> [u8; 24] {
+        let mut out_point = [0u8; 24];
+        if let Some(cell) = self.state.lock().unwrap().cells.get(lock_script) {
+            out_point.copy_from_slice(&cell.out_point.as_slice()[12..]);
+        }
+        out_point
+    }
+
+    // Commits a CoTA transaction with the entries returned by the aggregator, and the CoTA cell
+    // of the lock script is replaced with the one holding the new SMT root
+    pub fn commit_cota_tx(
+        &self,
+        lock_script: &[u8],
+        action: u8,
+        entries: &[u8],
+        smt_root: [u8; 32],
+    ) -> (u64, [u8; 32]) {
+        let mut state = self.state.lock().unwrap();
+        let lock = Script::from_slice(lock_script).expect("Lock script format error");
+        let input_out_point = state
+            .cells
+            .get(lock_script)
+            .map(|cell| cell.out_point.clone())
+            .unwrap_or_default();
+        let cota_type = Script::new_builder()
+            .code_hash(ckb_types::H256(hex_32(TESTNET_COTA_CODE_HASH)).pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        let output = CellOutput::new_builder()
+            .capacity(Capacity::shannons(COTA_CELL_CAPACITY).pack())
+            .lock(lock)
+            .type_(Some(cota_type).pack())
+            .build();
+        let mut output_data = vec![COTA_CELL_VERSION];
+        output_data.extend_from_slice(&smt_root);
+        let mut input_type = vec![action];
+        input_type.extend_from_slice(entries);
+        let witness = WitnessArgs::new_builder()
+            .input_type(Some(Bytes::from(input_type)).pack())
+            .build();
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(input_out_point, 0))
+            .output(output.clone())
+            .output_data(Bytes::from(output_data.clone()).pack())
+            .witness(witness.as_bytes().pack())
+            .build();
+
+        state.tip += 1;
+        let block_number = state.tip;
+        let block = BlockBuilder::default()
+            .number(block_number.pack())
+            .transaction(tx.clone())
+            .build();
+        state.blocks.insert(block_number, block);
+        state.cells.insert(lock_script.to_vec(), LiveCell {
+            block_number,
+            out_point: OutPoint::new(tx.hash(), 0),
+            output,
+            output_data: Bytes::from(output_data),
+        });
+        let tx_hash: ckb_types::H256 = tx.hash().unpack();
+        (block_number, tx_hash.0)
+    }
+}
+
+fn parse_block_number(params: Params) -> Result<u64, RpcError> {
+    let params = params.parse::<Vec<Value>>()?;
+    let block_number: Uint64 = serde_json::from_value(params.first().cloned().unwrap_or_default())
+        .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+    Ok(block_number.value())
+}
+
+fn hex_32(value: &str) -> [u8; 32] {
+    let mut ret = [0u8; 32];
+    ret.copy_from_slice(&hex::decode(value).unwrap());
+    ret
+}
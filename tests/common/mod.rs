@@ -0,0 +1,180 @@
+//! The harness of the integration tests, which runs the aggregator binary against a MySQL test
+//! database, a temporary RocksDB and a mock CKB node and indexer.
+
+#![allow(dead_code)]
+
+pub mod ckb;
+pub mod mysql;
+
+use self::ckb::MockCkb;
+use self::mysql::TestDb;
+use ckb_types::bytes::Bytes;
+use ckb_types::core::ScriptHashType;
+use ckb_types::packed::{Script, WitnessArgs};
+use ckb_types::prelude::*;
+use rand::{thread_rng, Rng};
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command};
+use std::thread::sleep;
+use std::time::Duration;
+
+// The syncer tip is above the value padding block height of testnet
+pub const TIP_BLOCK_NUMBER: u64 = 6_000_000;
+const SECP256K1_CODE_HASH: &str =
+    "9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct TestEnv {
+    pub db:         TestDb,
+    pub ckb:        MockCkb,
+    pub aggregator: Aggregator,
+}
+
+impl TestEnv {
+    pub fn start() -> Self {
+        let db = TestDb::create();
+        db.set_tip(TIP_BLOCK_NUMBER);
+        let ckb = MockCkb::start(TIP_BLOCK_NUMBER);
+        let aggregator = Aggregator::start(&db.url(), &ckb.url());
+        TestEnv {
+            db,
+            ckb,
+            aggregator,
+        }
+    }
+}
+
+pub struct Aggregator {
+    process:  Child,
+    port:     u16,
+    work_dir: PathBuf,
+}
+
+impl Aggregator {
+    pub fn start(database_url: &str, ckb_node_url: &str) -> Self {
+        let work_dir = env::temp_dir().join(format!(
+            "cota-aggregator-test-{}",
+            thread_rng().gen::<u32>()
+        ));
+        fs::create_dir_all(&work_dir).expect("Create work dir error");
+        let port = free_port();
+        let process = Command::new(env!("CARGO_BIN_EXE_cota-aggregator"))
+            .current_dir(&work_dir)
+            .env("DATABASE_URL", database_url)
+            .env("CKB_NODE", ckb_node_url)
+            .env("IS_MAINNET", "false")
+            .env("HTTP_PORT", port.to_string())
+            .env("WS_PORT", free_port().to_string())
+            .env("ROCKSDB_PATH", work_dir.join("store.db"))
+            .env("AUDIT_LOG_DIR", work_dir.join("audit_log"))
+            .env_remove("API_KEYS")
+            .spawn()
+            .expect("Start aggregator error");
+        let aggregator = Aggregator {
+            process,
+            port,
+            work_dir,
+        };
+        aggregator.wait_for_startup();
+        aggregator
+    }
+
+    fn wait_for_startup(&self) {
+        let mut waited = Duration::ZERO;
+        while TcpStream::connect(("127.0.0.1", self.port)).is_err() {
+            assert!(waited < STARTUP_TIMEOUT, "The aggregator does not start");
+            sleep(Duration::from_millis(100));
+            waited += Duration::from_millis(100);
+        }
+    }
+
+    pub fn call(&self, method: &str, params: Value) -> Result<Value, Value> {
+        let mut response: Value = reqwest::blocking::Client::new()
+            .post(format!("http://127.0.0.1:{}", self.port))
+            .json(&json!({
+                "id": 1,
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .expect("Aggregator rpc error")
+            .json()
+            .expect("Aggregator response format error");
+        match response.get_mut("error") {
+            Some(error) => Err(error.take()),
+            None => Ok(response["result"].take()),
+        }
+    }
+
+    // Verifies the entries of a generating RPC as the witness of a CoTA cell whose data is
+    // updated from the old SMT root to the new one
+    pub fn verify_entries(
+        &self,
+        action: u8,
+        entries: &[u8],
+        old_smt_root: Option<[u8; 32]>,
+        new_smt_root: [u8; 32],
+    ) -> Value {
+        let mut input_type = vec![action];
+        input_type.extend_from_slice(entries);
+        let witness = WitnessArgs::new_builder()
+            .input_type(Some(Bytes::from(input_type)).pack())
+            .build();
+        let mut params = json!({
+            "witness": hex_str(witness.as_slice()),
+            "new_smt_root": hex_str(&new_smt_root),
+        });
+        if let Some(old_smt_root) = old_smt_root {
+            params["old_smt_root"] = json!(hex_str(&old_smt_root));
+        }
+        self.call("verify_cota_witness", params)
+            .expect("Verify cota witness error")
+    }
+}
+
+impl Drop for Aggregator {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = fs::remove_dir_all(&self.work_dir);
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Bind free port error")
+        .port()
+}
+
+pub fn lock_script(args: [u8; 20]) -> Vec<u8> {
+    let mut code_hash = [0u8; 32];
+    code_hash.copy_from_slice(&hex::decode(SECP256K1_CODE_HASH).unwrap());
+    Script::new_builder()
+        .code_hash(ckb_types::H256(code_hash).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(args.to_vec()).pack())
+        .build()
+        .as_slice()
+        .to_vec()
+}
+
+pub fn hex_str(value: &[u8]) -> String {
+    format!("0x{}", hex::encode(value))
+}
+
+pub fn parse_hex(value: &Value) -> Vec<u8> {
+    let value = value.as_str().expect("Hex string expected");
+    hex::decode(value.trim_start_matches("0x")).expect("Hex string format error")
+}
+
+pub fn parse_root(value: &Value) -> [u8; 32] {
+    let mut root = [0u8; 32];
+    root.copy_from_slice(&parse_hex(value));
+    root
+}
@@ -0,0 +1,279 @@
+mod common;
+
+use common::mysql::Nft;
+use common::{hex_str, lock_script, parse_hex, parse_root, TestEnv};
+use cota_smt::define::DefineCotaNFTEntries;
+use cota_smt::mint::MintCotaNFTV1Entries;
+use cota_smt::molecule::prelude::Entity;
+use cota_smt::transfer::{
+    ClaimCotaNFTV2Entries, TransferCotaNFTV2Entries, WithdrawalCotaNFTV1Entries,
+};
+use cota_smt::update::UpdateCotaNFTEntries;
+use serde_json::{json, Value};
+
+const CREATE: u8 = 1;
+const MINT: u8 = 2;
+const WITHDRAW: u8 = 3;
+const CLAIM: u8 = 4;
+const UPDATE: u8 = 5;
+const TRANSFER: u8 = 6;
+
+const COTA_ID: [u8; 20] = [0x0a; 20];
+const CHARACTERISTIC: [u8; 20] = [0x05; 20];
+
+fn nft(token_index: u32) -> Nft {
+    Nft {
+        cota_id: COTA_ID,
+        token_index,
+        state: 0,
+        configure: 0,
+        characteristic: CHARACTERISTIC,
+    }
+}
+
+fn assert_verified(verification: Value, action: &str) {
+    assert_eq!(verification["action"], action);
+    assert_eq!(verification["old_root_matched"], true);
+    assert_eq!(verification["new_root_matched"], true);
+    assert_eq!(verification["valid"], true);
+}
+
+// The issuer defines and mints two NFTs to the first receiver, who claims them, withdraws the
+// first one to the second receiver and updates the other one, and the second receiver transfers
+// the first one back to the issuer. Every transaction is committed to the mock CKB node and
+// synced to mysql before the next one, so the old SMT root of each step is the new SMT root of
+// the previous step of the same lock.
+#[test]
+#[ignore]
+fn test_define_mint_withdraw_claim_transfer_update() {
+    let env = TestEnv::start();
+    let (db, ckb, aggregator) = (&env.db, &env.ckb, &env.aggregator);
+    let issuer = lock_script([0x11; 20]);
+    let receiver1 = lock_script([0x22; 20]);
+    let receiver2 = lock_script([0x33; 20]);
+
+    // Define
+    let define = aggregator
+        .call(
+            "generate_define_cota_smt",
+            json!({
+                "lock_script": hex_str(&issuer),
+                "cota_id": hex_str(&COTA_ID),
+                "total": "0x00000064",
+                "issued": "0x00000000",
+                "configure": "0x00",
+            }),
+        )
+        .expect("Define error");
+    let define_root = parse_root(&define["smt_root_hash"]);
+    let define_entry = parse_hex(&define["define_smt_entry"]);
+    let define_entries = DefineCotaNFTEntries::from_slice(&define_entry).unwrap();
+    assert_eq!(
+        define_entries
+            .define_keys()
+            .get(0)
+            .unwrap()
+            .cota_id()
+            .as_slice(),
+        COTA_ID
+    );
+    assert_verified(
+        aggregator.verify_entries(CREATE, &define_entry, None, define_root),
+        "define",
+    );
+    let (block_number, _) = ckb.commit_cota_tx(&issuer, CREATE, &define_entry, define_root);
+    db.insert_define(&issuer, COTA_ID, 100, 0, 0, block_number);
+
+    // Mint the NFTs #0 and #1 to the receiver1
+    let mint_out_point = ckb.cota_out_point(&issuer);
+    let mint = aggregator
+        .call(
+            "generate_mint_cota_smt",
+            json!({
+                "lock_script": hex_str(&issuer),
+                "cota_id": hex_str(&COTA_ID),
+                "out_point": hex_str(&mint_out_point),
+                "withdrawals": [0u32, 1].iter().map(|index| json!({
+                    "token_index": hex_str(&index.to_be_bytes()),
+                    "state": "0x00",
+                    "characteristic": hex_str(&CHARACTERISTIC),
+                    "to_lock_script": hex_str(&receiver1),
+                })).collect::<Vec<Value>>(),
+            }),
+        )
+        .expect("Mint error");
+    let mint_root = parse_root(&mint["smt_root_hash"]);
+    let mint_entry = parse_hex(&mint["mint_smt_entry"]);
+    let mint_entries = MintCotaNFTV1Entries::from_slice(&mint_entry).unwrap();
+    assert_eq!(mint_entries.withdrawal_values().len(), 2);
+    assert_eq!(
+        mint_entries
+            .withdrawal_values()
+            .get(0)
+            .unwrap()
+            .to_lock()
+            .raw_data()
+            .as_ref(),
+        &receiver1[..]
+    );
+    assert_verified(
+        aggregator.verify_entries(MINT, &mint_entry, Some(define_root), mint_root),
+        "mint",
+    );
+    let (block_number, mint_tx_hash) = ckb.commit_cota_tx(&issuer, MINT, &mint_entry, mint_root);
+    db.update_define_issued(COTA_ID, 2, block_number);
+    for index in [0, 1] {
+        db.insert_withdrawal(
+            &issuer,
+            nft(index),
+            mint_out_point,
+            mint_tx_hash,
+            &receiver1,
+            block_number,
+        );
+    }
+
+    // The receiver1 claims the NFTs
+    let claim = aggregator
+        .call(
+            "generate_claim_cota_smt",
+            json!({
+                "lock_script": hex_str(&receiver1),
+                "withdrawal_lock_script": hex_str(&issuer),
+                "claims": [0u32, 1].iter().map(|index| json!({
+                    "cota_id": hex_str(&COTA_ID),
+                    "token_index": hex_str(&index.to_be_bytes()),
+                })).collect::<Vec<Value>>(),
+            }),
+        )
+        .expect("Claim error");
+    let claim_root = parse_root(&claim["smt_root_hash"]);
+    let claim_entry = parse_hex(&claim["claim_smt_entry"]);
+    let claim_entries = ClaimCotaNFTV2Entries::from_slice(&claim_entry).unwrap();
+    assert_eq!(claim_entries.hold_keys().len(), 2);
+    assert_verified(
+        aggregator.verify_entries(CLAIM, &claim_entry, None, claim_root),
+        "claim",
+    );
+    let (block_number, _) = ckb.commit_cota_tx(&receiver1, CLAIM, &claim_entry, claim_root);
+    for index in [0, 1] {
+        db.insert_hold(&receiver1, nft(index), block_number);
+        db.insert_claim(&receiver1, COTA_ID, index, mint_out_point, block_number);
+    }
+
+    // The receiver1 withdraws the NFT #0 to the receiver2
+    let withdrawal_out_point = ckb.cota_out_point(&receiver1);
+    let withdrawal = aggregator
+        .call(
+            "generate_withdrawal_cota_smt",
+            json!({
+                "lock_script": hex_str(&receiver1),
+                "out_point": hex_str(&withdrawal_out_point),
+                "withdrawals": [{
+                    "cota_id": hex_str(&COTA_ID),
+                    "token_index": "0x00000000",
+                    "to_lock_script": hex_str(&receiver2),
+                }],
+            }),
+        )
+        .expect("Withdrawal error");
+    let withdrawal_root = parse_root(&withdrawal["smt_root_hash"]);
+    let withdrawal_entry = parse_hex(&withdrawal["withdrawal_smt_entry"]);
+    let withdrawal_entries = WithdrawalCotaNFTV1Entries::from_slice(&withdrawal_entry).unwrap();
+    assert_eq!(
+        withdrawal_entries
+            .withdrawal_values()
+            .get(0)
+            .unwrap()
+            .to_lock()
+            .raw_data()
+            .as_ref(),
+        &receiver2[..]
+    );
+    assert_verified(
+        aggregator.verify_entries(
+            WITHDRAW,
+            &withdrawal_entry,
+            Some(claim_root),
+            withdrawal_root,
+        ),
+        "withdraw",
+    );
+    let (block_number, withdrawal_tx_hash) =
+        ckb.commit_cota_tx(&receiver1, WITHDRAW, &withdrawal_entry, withdrawal_root);
+    db.delete_hold(COTA_ID, 0);
+    db.insert_withdrawal(
+        &receiver1,
+        nft(0),
+        withdrawal_out_point,
+        withdrawal_tx_hash,
+        &receiver2,
+        block_number,
+    );
+
+    // The receiver2 transfers the NFT #0 to the issuer
+    let transfer = aggregator
+        .call(
+            "generate_transfer_cota_smt",
+            json!({
+                "lock_script": hex_str(&receiver2),
+                "withdrawal_lock_script": hex_str(&receiver1),
+                "transfer_out_point": hex_str(&ckb.cota_out_point(&receiver2)),
+                "transfers": [{
+                    "cota_id": hex_str(&COTA_ID),
+                    "token_index": "0x00000000",
+                    "to_lock_script": hex_str(&issuer),
+                }],
+            }),
+        )
+        .expect("Transfer error");
+    let transfer_root = parse_root(&transfer["smt_root_hash"]);
+    let transfer_entry = parse_hex(&transfer["transfer_smt_entry"]);
+    let transfer_entries = TransferCotaNFTV2Entries::from_slice(&transfer_entry).unwrap();
+    assert_eq!(
+        transfer_entries
+            .withdrawal_values()
+            .get(0)
+            .unwrap()
+            .to_lock()
+            .raw_data()
+            .as_ref(),
+        &issuer[..]
+    );
+    assert_verified(
+        aggregator.verify_entries(TRANSFER, &transfer_entry, None, transfer_root),
+        "transfer",
+    );
+    ckb.commit_cota_tx(&receiver2, TRANSFER, &transfer_entry, transfer_root);
+
+    // The receiver1 updates the NFT #1, and the dry run returns the same SMT root without
+    // committing it
+    let update_params = json!({
+        "lock_script": hex_str(&receiver1),
+        "nfts": [{
+            "cota_id": hex_str(&COTA_ID),
+            "token_index": "0x00000001",
+            "state": "0x01",
+            "characteristic": hex_str(&[0x06; 20]),
+        }],
+    });
+    let mut dry_run_params = update_params.clone();
+    dry_run_params["dry_run"] = json!(true);
+    let dry_run_update = aggregator
+        .call("generate_update_cota_smt", dry_run_params)
+        .expect("Update dry run error");
+    let update = aggregator
+        .call("generate_update_cota_smt", update_params)
+        .expect("Update error");
+    assert_eq!(dry_run_update["smt_root_hash"], update["smt_root_hash"]);
+    let update_root = parse_root(&update["smt_root_hash"]);
+    let update_entry = parse_hex(&update["update_smt_entry"]);
+    let update_entries = UpdateCotaNFTEntries::from_slice(&update_entry).unwrap();
+    let hold_new_value = update_entries.hold_new_values().get(0).unwrap();
+    assert_eq!(hold_new_value.state().as_slice(), [0x01]);
+    assert_eq!(hold_new_value.characteristic().as_slice(), [0x06; 20]);
+    assert_verified(
+        aggregator.verify_entries(UPDATE, &update_entry, Some(withdrawal_root), update_root),
+        "update",
+    );
+}